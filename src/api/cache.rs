@@ -0,0 +1,176 @@
+/// Copyright (c) Shipt, Inc.
+/// This source code is licensed under the MIT license found in the
+/// LICENSE file in the root directory of this source tree.
+use crate::api::checksum::{self, ExpectedChecksum};
+use anyhow::Context;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Path of a file in the download cache
+///
+/// Files are stored by content as `<cache_dir>/<algo>/<digest>`
+///
+/// # Arguments
+///
+/// * `cache_dir` - Root of the download cache
+/// * `expected` - Server-provided checksum of the file
+///
+/// # Returns
+/// * `Option<PathBuf>` - Cache path, or None when the digest is not a plain hex string
+///
+pub fn cache_path(cache_dir: &Path, expected: &ExpectedChecksum) -> Option<PathBuf> {
+    // the digest comes from a response header, so never let it escape the cache directory
+    if expected.digest.is_empty() || !expected.digest.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+
+    Some(
+        cache_dir
+            .join(expected.algo.as_str())
+            .join(&expected.digest),
+    )
+}
+
+/// Temporary path a file is copied to before it is moved to `dst`
+///
+/// The temporary name extends the full file name, so files that only differ by extension
+/// never share one. A per-process counter keeps concurrent copies to the same `dst` apart
+///
+/// # Arguments
+///
+/// * `dst` - Path the file is moved to
+///
+fn tmp_path(dst: &Path) -> Result<PathBuf, anyhow::Error> {
+    static COPIES: AtomicUsize = AtomicUsize::new(0);
+
+    let mut file_name = dst
+        .file_name()
+        .with_context(|| format!("Invalid file path {:?}", dst))?
+        .to_owned();
+    file_name.push(format!(
+        ".tmp-{}-{}",
+        std::process::id(),
+        COPIES.fetch_add(1, Ordering::Relaxed)
+    ));
+
+    Ok(dst.with_file_name(file_name))
+}
+
+/// Copies `src` to `dst` through a temporary file, so a partial copy is never visible
+///
+/// Files are copied rather than hardlinked, so editing a downloaded file cannot change the
+/// cached copy
+///
+/// # Arguments
+///
+/// * `src` - Existing file
+/// * `dst` - Path to create. Replaced if it already exists
+///
+fn copy_file(src: &Path, dst: &Path) -> Result<(), anyhow::Error> {
+    let tmp_path = tmp_path(dst)?;
+    fs::copy(src, &tmp_path)
+        .with_context(|| format!("Failed to copy {:?} to {:?}", src, tmp_path))?;
+    fs::rename(&tmp_path, dst)
+        .with_context(|| format!("Failed to move {:?} to {:?}", tmp_path, dst))?;
+
+    Ok(())
+}
+
+/// Restores a file from the download cache
+///
+/// The cached file is checked against the server checksum first. A cached file that no
+/// longer matches is removed and treated as a miss
+///
+/// # Arguments
+///
+/// * `cached` - Path of the file in the cache
+/// * `lpath` - Path to write the file to
+/// * `expected` - Server-provided checksum of the file
+///
+/// # Returns
+/// * `Result<Option<u64>, String>` - Size of the restored file, or None on a cache miss
+///
+pub fn restore(
+    cached: &Path,
+    lpath: &Path,
+    expected: &ExpectedChecksum,
+) -> Result<Option<u64>, anyhow::Error> {
+    let metadata = match fs::metadata(cached) {
+        Ok(metadata) if metadata.is_file() => metadata,
+        _ => return Ok(None),
+    };
+
+    if checksum::file_checksum(cached, expected.algo)? != expected.digest {
+        fs::remove_file(cached)
+            .with_context(|| format!("Failed to remove corrupt cache file {:?}", cached))?;
+        return Ok(None);
+    }
+
+    copy_file(cached, lpath)
+        .with_context(|| format!("Failed to restore {:?} from cache", lpath))?;
+
+    Ok(Some(metadata.len()))
+}
+
+/// Adds a downloaded file to the download cache
+///
+/// # Arguments
+///
+/// * `lpath` - Downloaded file
+/// * `cached` - Path of the file in the cache
+///
+pub fn store(lpath: &Path, cached: &Path) -> Result<(), anyhow::Error> {
+    let parent = cached
+        .parent()
+        .with_context(|| "Failed to get cache directory")?;
+    fs::create_dir_all(parent)
+        .with_context(|| format!("Failed to create cache directory {:?}", parent))?;
+
+    copy_file(lpath, cached)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::checksum::ChecksumAlgo;
+
+    #[test]
+    fn test_cache_path() {
+        let cache_dir = Path::new("/tmp/opsml-cache");
+        let expected =
+            ExpectedChecksum::parse("md5:5EB63BBBE01EEED093CB22BB8F5ACDC3", ChecksumAlgo::Sha256)
+                .unwrap();
+        assert_eq!(
+            cache_path(cache_dir, &expected),
+            Some(PathBuf::from(
+                "/tmp/opsml-cache/md5/5eb63bbbe01eeed093cb22bb8f5acdc3"
+            ))
+        );
+
+        let traversal = ExpectedChecksum::parse("../../etc/passwd", ChecksumAlgo::Sha256).unwrap();
+        assert_eq!(cache_path(cache_dir, &traversal), None);
+    }
+
+    #[test]
+    fn test_tmp_path() {
+        let onnx = tmp_path(Path::new("models/model.onnx")).unwrap();
+        let bin = tmp_path(Path::new("models/model.bin")).unwrap();
+
+        // files differing only by extension get their own temporary file
+        assert_eq!(onnx.parent(), Some(Path::new("models")));
+        assert!(onnx
+            .file_name()
+            .unwrap()
+            .to_string_lossy()
+            .starts_with(&format!("model.onnx.tmp-{}-", std::process::id())));
+        assert!(bin
+            .file_name()
+            .unwrap()
+            .to_string_lossy()
+            .starts_with("model.bin.tmp-"));
+
+        // so do repeated copies to the same path
+        assert_ne!(onnx, tmp_path(Path::new("models/model.onnx")).unwrap());
+    }
+}
@@ -1,18 +1,103 @@
 /// Copyright (c) Shipt, Inc.
 /// This source code is licensed under the MIT license found in the
 /// LICENSE file in the root directory of this source tree.
+use crate::api::output;
 use crate::api::route_helper::RouteHelper;
 use crate::api::types;
 use crate::api::utils;
 use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use clap::ValueEnum;
 use owo_colors::OwoColorize;
 use reqwest::{self, Response};
 use serde_json;
-use std::collections::HashMap;
-use tabled::settings::style::Style;
-use tabled::{settings::Alignment, Table};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::env;
+use std::time::Duration;
+use tabled::{builder::Builder, settings::Alignment, Table};
 
-struct CardLister<'a> {
+const REGISTRIES: [&str; 6] = ["data", "model", "run", "pipeline", "audit", "project"];
+const ALL_REGISTRIES: &str = "all";
+/// Largest edit distance at which an invalid registry gets a suggestion
+const MAX_SUGGESTION_DISTANCE: usize = 2;
+pub const DEFAULT_RECENT_LIMIT: usize = 10;
+const NO_CARDS_FOUND: &str = "No cards found matching the given filters.";
+pub const CARD_COLUMNS: [&str; 6] = ["name", "repository", "date", "contact", "version", "uid"];
+
+/// Card identifier given as a single argument instead of --registry/--name/--version/--uid
+#[derive(Debug, Default, PartialEq)]
+pub struct CardId {
+    pub registry: Option<String>,
+    pub name: Option<String>,
+    pub version: Option<String>,
+    pub uid: Option<String>,
+}
+
+impl CardId {
+    /// Parses a card identifier
+    ///
+    /// Accepted forms are `registry:name:version`, `name:version` and `registry::uid`
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - Card identifier
+    ///
+    /// # Returns
+    /// * `Result<CardId, String>` - Identifier components
+    ///
+    pub fn parse(id: &str) -> Result<CardId, anyhow::Error> {
+        let invalid = |reason: &str| {
+            anyhow::Error::msg(format!(
+                "Invalid card identifier {:?}: {}. Expected registry:name:version, name:version or registry::uid",
+                id, reason
+            ))
+        };
+
+        let parts: Vec<&str> = id.trim().split(':').map(|part| part.trim()).collect();
+        let (registry, name, version, uid) = match parts.as_slice() {
+            [registry, "", uid] => (Some(*registry), None, None, Some(*uid)),
+            [registry, name, version] => (Some(*registry), Some(*name), Some(*version), None),
+            [name, version] => (None, Some(*name), Some(*version), None),
+            [_] => return Err(invalid("missing version or uid")),
+            _ => return Err(invalid("too many ':' separators")),
+        };
+
+        if [registry, name, version, uid]
+            .iter()
+            .flatten()
+            .any(|part| part.is_empty())
+        {
+            return Err(invalid("empty component"));
+        }
+
+        if let Some(registry) = registry {
+            if !REGISTRIES.contains(&registry) {
+                return Err(invalid(&format!(
+                    "unknown registry {}. Valid registries are: {}",
+                    registry,
+                    REGISTRIES.join(", ")
+                )));
+            }
+        }
+
+        Ok(CardId {
+            registry: registry.map(String::from),
+            name: name.map(String::from),
+            version: version.map(String::from),
+            uid: uid.map(String::from),
+        })
+    }
+}
+
+/// Column used to group listed cards
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum CardGroup {
+    /// Team (repository) that owns the card
+    #[value(alias = "repository")]
+    Team,
+}
+
+pub struct CardLister<'a> {
     pub registry_type: &'a str,
     pub name: Option<&'a str>,
     pub repository: Option<&'a str>,
@@ -21,7 +106,15 @@ struct CardLister<'a> {
     pub limit: Option<&'a i16>,
     pub tags: HashMap<String, String>,
     pub max_date: Option<&'a str>,
+    pub min_date: Option<&'a str>,
+    pub name_contains: Option<&'a str>,
     pub ignore_release_candidates: &'a bool,
+    pub all_versions: &'a bool,
+    pub table_style: utils::TableStyle,
+    pub output: output::OutputOptions,
+    pub group_by: Option<CardGroup>,
+    pub columns: Option<&'a [String]>,
+    pub template: Option<&'a str>,
 }
 impl CardLister<'_> {
     /// Checks if registry is valid
@@ -33,18 +126,54 @@ impl CardLister<'_> {
     fn validate_registry(&self) -> Result<(), anyhow::Error> {
         // Determines correct  registry to use
 
-        let registries = ["data", "model", "run", "pipeline", "audit", "project"];
-
-        if registries.contains(&self.registry_type) {
+        if REGISTRIES.contains(&self.registry_type) {
             Ok(())
         } else {
+            let suggestion = suggest_registry(self.registry_type)
+                .map_or(String::new(), |registry| {
+                    format!(" Did you mean '{}'?", registry)
+                });
             Err(anyhow::Error::msg(format!(
-                "Invalid registry: {}. Valid registries are: data, model, run, pipeline, audit, project",
-                self.registry_type
+                "Invalid registry: {}.{} Valid registries are: {}, {}",
+                self.registry_type,
+                suggestion,
+                REGISTRIES.join(", "),
+                ALL_REGISTRIES
             )))
         }
     }
 
+    /// Resolves the table columns to render
+    ///
+    /// Columns given on the command line take precedence over the registry's configured defaults
+    ///
+    /// # Returns
+    /// * `Option<Vec<String>>` - Columns to render, or None for the default table
+    ///
+    fn table_columns(&self) -> Option<Vec<String>> {
+        match self.columns {
+            Some(columns) => Some(columns.to_vec()),
+            None => utils::registry_columns(self.registry_type),
+        }
+    }
+
+    /// Checks that every column to render is a known card column
+    fn validate_columns(&self) -> Result<(), anyhow::Error> {
+        let columns = self.table_columns().unwrap_or_default();
+
+        match columns
+            .iter()
+            .find(|column| !CARD_COLUMNS.contains(&column.as_str()))
+        {
+            Some(column) => Err(anyhow::Error::msg(format!(
+                "Invalid column: {}. Valid columns are: {}",
+                column,
+                CARD_COLUMNS.join(", ")
+            ))),
+            None => Ok(()),
+        }
+    }
+
     /// Parse card list response
     ///
     /// # Arguments
@@ -55,31 +184,406 @@ impl CardLister<'_> {
     ///  String - Table of cards
     ///
     fn parse_list_response(&self, response: &str) -> Result<String, anyhow::Error> {
+        if self.output.raw {
+            return Ok(response.to_string());
+        }
+
         // Parses response and creates a table
 
         let cards: types::ListCardResponse = serde_json::from_str(response)
             .with_context(|| "Failed to load response to ListCardResponse JSON")
             .unwrap();
+        let cards = self.group_cards(self.filter_cards(cards.cards));
+
+        if self.output.is_json() {
+            return self.output.render_json_list(&cards);
+        }
+
+        if let Some(template) = self.template {
+            return render_card_template(template, &cards);
+        }
+
+        if cards.is_empty() {
+            return Ok(NO_CARDS_FOUND.to_string());
+        }
+
+        Ok(self.build_card_table(&cards, &HashSet::new()))
+    }
+
+    /// Filters cards to those whose name contains `name_contains`, ignoring case
+    ///
+    /// # Arguments
+    ///
+    /// * `cards` - Cards returned by the server
+    ///
+    /// # Returns
+    ///  Vec<Card> - Matching cards. All cards are returned when no filter is set
+    ///
+    fn filter_cards(&self, cards: Vec<types::Card>) -> Vec<types::Card> {
+        match self.name_contains {
+            Some(pattern) => {
+                let pattern = pattern.to_lowercase();
+                cards
+                    .into_iter()
+                    .filter(|card| card.name.to_lowercase().contains(&pattern))
+                    .collect()
+            }
+            None => cards,
+        }
+    }
+
+    /// Orders cards so that cards sharing a group are adjacent
+    ///
+    /// Sorting is stable, so the server order is kept within each group
+    ///
+    /// # Arguments
+    ///
+    /// * `cards` - Cards to order
+    ///
+    /// # Returns
+    ///  Vec<Card> - Cards sorted by group. Cards are returned unchanged when no grouping is set
+    ///
+    fn group_cards(&self, mut cards: Vec<types::Card>) -> Vec<types::Card> {
+        match self.group_by {
+            Some(CardGroup::Team) => {
+                cards.sort_by(|a, b| a.repository.cmp(&b.repository));
+                cards
+            }
+            None => cards,
+        }
+    }
+
+    /// Builds a table of cards grouped by team
+    ///
+    /// The team is printed on the first row of each group only
+    ///
+    /// # Arguments
+    ///
+    /// * `cards` - Cards to render, already ordered by team
+    /// * `highlight` - Uids of cards to highlight
+    ///
+    /// # Returns
+    ///  String - Table of cards
+    ///
+    fn build_grouped_card_table(
+        &self,
+        cards: &[types::Card],
+        highlight: &HashSet<String>,
+    ) -> String {
+        // the team is already the leading column
+        let columns: Vec<String> = self
+            .table_columns()
+            .unwrap_or(CARD_COLUMNS.map(String::from).to_vec())
+            .into_iter()
+            .filter(|column| column != "repository")
+            .collect();
+
+        let mut builder = Builder::default();
+        builder.set_header(std::iter::once("team".to_string()).chain(columns.clone()));
+
+        let mut previous_team: Option<&str> = None;
+        for card in cards.iter() {
+            let team = match previous_team {
+                Some(team) if team == card.repository => "",
+                _ => card.repository.as_str(),
+            };
+            previous_team = Some(card.repository.as_str());
+
+            let row = std::iter::once(team.to_string())
+                .chain(columns.iter().map(|column| card_cell(card, column)));
+
+            if highlight.contains(&card.uid) {
+                builder.push_record(row.map(|cell| cell.green().to_string()));
+            } else {
+                builder.push_record(row);
+            }
+        }
+
+        let mut list_table = builder.build();
+        list_table.with(Alignment::center());
+
+        self.table_style.render(&mut list_table)
+    }
+
+    /// Builds a table of cards
+    ///
+    /// # Arguments
+    ///
+    /// * `cards` - Cards to render
+    /// * `highlight` - Uids of cards to highlight
+    ///
+    /// # Returns
+    ///  String - Table of cards
+    ///
+    fn build_card_table(&self, cards: &[types::Card], highlight: &HashSet<String>) -> String {
+        if self.group_by.is_some() {
+            return self.build_grouped_card_table(cards, highlight);
+        }
+
+        if let Some(columns) = self.table_columns() {
+            return self.build_column_card_table(cards, highlight, &columns);
+        }
 
         let mut card_table: Vec<types::CardTable> = Vec::new();
 
-        for card in cards.cards.iter() {
-            card_table.push(types::CardTable {
+        for card in cards.iter() {
+            let mut row = types::CardTable {
                 name: card.name.clone(),
                 repository: card.repository.clone(),
                 date: card.date.clone().unwrap_or("".to_string()),
                 contact: card.contact.clone(),
                 version: card.version.clone(),
                 uid: card.uid.clone(),
-            });
+            };
+
+            if highlight.contains(&card.uid) {
+                row = types::CardTable {
+                    name: row.name.green().to_string(),
+                    repository: row.repository.green().to_string(),
+                    date: row.date.green().to_string(),
+                    contact: row.contact.green().to_string(),
+                    version: row.version.green().to_string(),
+                    uid: row.uid.green().to_string(),
+                };
+            }
+
+            card_table.push(row);
         }
 
-        let list_table = Table::new(card_table)
-            .with(Alignment::center())
-            .with(Style::sharp())
-            .to_string();
+        let mut list_table = Table::new(card_table);
+        list_table.with(Alignment::center());
+
+        self.table_style.render(&mut list_table)
+    }
+
+    /// Builds a table of cards limited to the given columns
+    ///
+    /// # Arguments
+    ///
+    /// * `cards` - Cards to render
+    /// * `highlight` - Uids of cards to highlight
+    /// * `columns` - Columns to render, in order
+    ///
+    /// # Returns
+    ///  String - Table of cards
+    ///
+    fn build_column_card_table(
+        &self,
+        cards: &[types::Card],
+        highlight: &HashSet<String>,
+        columns: &[String],
+    ) -> String {
+        let mut builder = Builder::default();
+        builder.set_header(columns.iter().cloned());
+
+        for card in cards.iter() {
+            let row = columns.iter().map(|column| card_cell(card, column));
+
+            if highlight.contains(&card.uid) {
+                builder.push_record(row.map(|cell| cell.green().to_string()));
+            } else {
+                builder.push_record(row);
+            }
+        }
+
+        let mut list_table = builder.build();
+        list_table.with(Alignment::center());
+
+        self.table_style.render(&mut list_table)
+    }
+
+    /// Builds a table of cards with a leading registry column
+    ///
+    /// # Arguments
+    ///
+    /// * `cards` - Cards to render, paired with the registry they were listed from
+    ///
+    /// # Returns
+    ///  String - Table of cards
+    ///
+    fn build_registry_card_table(&self, cards: &[(&str, types::Card)]) -> String {
+        let card_table: Vec<types::RegistryCardTable> = cards
+            .iter()
+            .map(|(registry, card)| types::RegistryCardTable {
+                registry: registry.to_string(),
+                name: card.name.clone(),
+                repository: card.repository.clone(),
+                date: card.date.clone().unwrap_or("".to_string()),
+                contact: card.contact.clone(),
+                version: card.version.clone(),
+                uid: card.uid.clone(),
+            })
+            .collect();
+
+        let mut list_table = Table::new(card_table);
+        list_table.with(Alignment::center());
+
+        self.table_style.render(&mut list_table)
+    }
+
+    /// Lists cards from every registry
+    ///
+    /// A failing registry is recorded rather than returned so the remaining registries are still listed
+    ///
+    /// # Returns
+    /// * `(Vec<(&str, types::Card)>, Vec<String>)` - Cards paired with their registry, and errors per registry
+    ///
+    async fn get_all_registry_cards(&self) -> (Vec<(&'static str, types::Card)>, Vec<String>) {
+        let mut cards = Vec::new();
+        let mut errors = Vec::new();
+
+        for registry in REGISTRIES.iter() {
+            let card_lister = CardLister {
+                registry_type: registry,
+                tags: self.tags.clone(),
+                ..*self
+            };
+
+            match card_lister.get_card_response().await {
+                Ok(response) => {
+                    cards.extend(response.cards.into_iter().map(|card| (*registry, card)))
+                }
+                Err(e) => errors.push(format!("{}: {}", registry, e)),
+            }
+        }
+
+        (cards, errors)
+    }
+
+    /// Lists and prints cards from every registry
+    ///
+    /// # Arguments
+    ///
+    /// * `count` - Print only the number of matching cards
+    ///
+    /// # Returns
+    /// * `Result<usize, String>` - Number of cards listed
+    ///
+    async fn print_all_registry_cards(&self, count: bool) -> Result<usize, anyhow::Error> {
+        let (cards, errors) = self.get_all_registry_cards().await;
+
+        for error in errors.iter() {
+            eprintln!(
+                "{}",
+                output::styled(format!("Failed to list cards from {}", error).yellow())
+            );
+        }
+
+        if errors.len() == REGISTRIES.len() {
+            return Err(anyhow::Error::msg(
+                "Failed to list cards from every registry",
+            ));
+        }
+
+        if count {
+            output::print(cards.len())?;
+            return Ok(cards.len());
+        }
+
+        if self.output.is_json() {
+            let registry_cards: Vec<types::RegistryCard> = cards
+                .iter()
+                .map(|(registry, card)| types::RegistryCard { registry, card })
+                .collect();
+            output::print(self.output.render_json_list(&registry_cards)?)?;
+            return Ok(cards.len());
+        }
+
+        output::print(format!(
+            "\nListing cards from {} registries",
+            ALL_REGISTRIES.bold().green()
+        ))?;
+        if cards.is_empty() {
+            output::print(NO_CARDS_FOUND)?;
+            return Ok(0);
+        }
+        output::print(self.build_registry_card_table(&cards))?;
+        Ok(cards.len())
+    }
+
+    /// Polls the server for cards, reprinting the table on each interval and
+    /// highlighting cards that appeared since the previous poll. Exits on Ctrl-C
+    ///
+    /// # Arguments
+    ///
+    /// * `interval` - Seconds between polls
+    ///
+    async fn watch_cards(&self, interval: u64) -> Result<(), anyhow::Error> {
+        let mut ticker = tokio::time::interval(Duration::from_secs(interval.max(1)));
+        let mut seen: Option<HashSet<String>> = None;
+
+        // created once so a Ctrl-C pressed while a table is printing is not lost
+        let interrupted = tokio::signal::ctrl_c();
+        tokio::pin!(interrupted);
+
+        loop {
+            tokio::select! {
+                _ = ticker.tick() => {
+                    let mut cards = self.get_card_response().await?;
+                    cards.cards = self.group_cards(cards.cards);
+                    let new_uids = match &seen {
+                        Some(previous) => find_new_uids(previous, &cards.cards),
+                        None => HashSet::new(),
+                    };
+
+                    if self.output.is_json() {
+                        output::print(self.output.render_json_list(&cards.cards)?)?;
+                    } else {
+                        output::print(format!(
+                            "\nListing cards from {} registry ({} new)",
+                            self.registry_type.to_string().bold().green(),
+                            new_uids.len()
+                        ))?;
+                        output::print(self.build_card_table(&cards.cards, &new_uids))?;
+                    }
+
+                    seen = Some(cards.cards.iter().map(|card| card.uid.clone()).collect());
+                }
+                _ = &mut interrupted => {
+                    // a status line, so keep it out of --output-file and JSON streams
+                    eprintln!("\nStopped watching {} registry", self.registry_type);
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    /// Parse card list response into the number of cards returned
+    ///
+    /// # Arguments
+    ///
+    /// * `response` - Response from server
+    ///
+    /// # Returns
+    ///  usize - Number of cards
+    ///
+    fn parse_count_response(&self, response: &str) -> Result<usize, anyhow::Error> {
+        let cards: types::ListCardResponse = serde_json::from_str(response)
+            .with_context(|| "Failed to load response to ListCardResponse JSON")?;
+
+        Ok(self.filter_cards(cards.cards).len())
+    }
+
+    /// Parse card list response into a sorted, de-duplicated list of teams
+    ///
+    /// # Arguments
+    ///
+    /// * `response` - Response from server
+    ///
+    /// # Returns
+    ///  Vec<String> - Sorted team (repository) names
+    ///
+    fn parse_team_response(&self, response: &str) -> Result<Vec<String>, anyhow::Error> {
+        let cards: types::ListCardResponse = serde_json::from_str(response)
+            .with_context(|| "Failed to load response to ListCardResponse JSON")?;
+
+        let teams: BTreeSet<String> = cards
+            .cards
+            .iter()
+            .map(|card| card.repository.clone())
+            .collect();
 
-        Ok(list_table)
+        Ok(teams.into_iter().collect())
     }
 
     /// Constructs tags hashmap from supplied value key pairs
@@ -94,11 +598,10 @@ impl CardLister<'_> {
     ///
     fn construct_tags(&mut self, tag_name: Option<Vec<String>>, tag_value: Option<Vec<String>>) {
         let mut tags: HashMap<String, String> = HashMap::new();
-        if tag_name.is_some() && tag_value.is_some() {
+        if let (Some(tag_name), Some(tag_value)) = (tag_name, tag_value) {
             tags = tag_name
-                .unwrap()
                 .iter()
-                .zip(tag_value.unwrap().iter())
+                .zip(tag_value.iter())
                 .map(|(k, v)| (k.to_string(), v.to_string()))
                 .collect();
         };
@@ -117,26 +620,95 @@ impl CardLister<'_> {
     async fn make_card_request(&self) -> Result<Response, anyhow::Error> {
         let list_table_request = types::ListTableRequest {
             registry_type: self.registry_type,
-            name: self.name,
-            repository: self.repository,
-            version: self.version,
+            name: utils::trim_arg(self.name),
+            repository: utils::trim_arg(self.repository),
+            version: utils::trim_arg(self.version),
             limit: self.limit,
-            uid: self.uid,
+            uid: utils::trim_arg(self.uid),
             tags: &self.tags,
             max_date: self.max_date,
+            min_date: self.min_date,
             ignore_release_candidates: self.ignore_release_candidates,
+            all_versions: self.all_versions,
         };
 
         let response = RouteHelper::make_post_request(
             &utils::OpsmlPaths::ListCard.as_str(),
             &list_table_request,
         )
-        .await
-        .unwrap();
+        .await?;
 
         Ok(response)
     }
 
+    /// Requests cards from the server and parses the response
+    ///
+    /// # Returns
+    /// * `Result<types::ListCardResponse, String>` - Cards matching the lister's filters
+    ///
+    pub async fn get_card_response(&self) -> Result<types::ListCardResponse, anyhow::Error> {
+        self.validate_registry()?;
+        let response = self.make_card_request().await?;
+
+        if response.status().is_success() {
+            let cards: types::ListCardResponse = serde_json::from_str(&response.text().await?)
+                .with_context(|| "Failed to load response to ListCardResponse JSON")?;
+            Ok(types::ListCardResponse {
+                cards: self.filter_cards(cards.cards),
+            })
+        } else {
+            Err(RouteHelper::response_error(response, "Failed to make call to list cards").await)
+        }
+    }
+
+    /// Lists the most recently dated cards, newest first
+    ///
+    /// Cards are sorted client-side because the server does not order by date, so every card
+    /// is requested and the list is truncated after sorting
+    ///
+    /// # Arguments
+    ///
+    /// * `limit` - Maximum number of cards to return
+    ///
+    /// # Returns
+    /// * `Result<Vec<types::Card>, String>` - Most recent cards
+    ///
+    async fn get_recent_cards(&self, limit: usize) -> Result<Vec<types::Card>, anyhow::Error> {
+        let mut cards = self.get_card_response().await?.cards;
+
+        // dates are ISO formatted, so they sort lexically. Undated cards sort last
+        cards.sort_by(|a, b| b.date.cmp(&a.date));
+        cards.truncate(limit);
+
+        Ok(cards)
+    }
+
+    /// Prints the most recently dated cards
+    ///
+    /// # Arguments
+    ///
+    /// * `limit` - Maximum number of cards to print
+    ///
+    async fn print_recent_cards(&self, limit: usize) -> Result<(), anyhow::Error> {
+        self.validate_columns()?;
+        let cards = self.get_recent_cards(limit).await?;
+
+        if self.output.is_json() {
+            return output::print(self.output.render_json_list(&cards)?);
+        }
+
+        if cards.is_empty() {
+            return output::print(NO_CARDS_FOUND);
+        }
+
+        output::print(format!(
+            "\nListing {} most recent cards from {} registry",
+            cards.len(),
+            self.registry_type.to_string().bold().green()
+        ))?;
+        output::print(self.build_card_table(&cards, &HashSet::new()))
+    }
+
     #[allow(clippy::too_many_arguments)]
     async fn get_cards(
         registry: &str,
@@ -148,7 +720,18 @@ impl CardLister<'_> {
         tag_name: Option<Vec<String>>,
         tag_value: Option<Vec<String>>,
         max_date: Option<&str>,
+        min_date: Option<&str>,
+        name_contains: Option<&str>,
         ignore_release_candidates: bool,
+        all_versions: bool,
+        table_style: utils::TableStyle,
+        output: output::OutputOptions,
+        count: bool,
+        watch: Option<u64>,
+        group_by: Option<CardGroup>,
+        columns: Option<&[String]>,
+        template: Option<&str>,
+        fail_if_empty: bool,
     ) -> Result<(), anyhow::Error> {
         let tags: HashMap<String, String> = HashMap::new();
         let mut card_lister = CardLister {
@@ -160,38 +743,349 @@ impl CardLister<'_> {
             limit: limit.as_ref(),
             tags,
             max_date,
+            min_date,
+            name_contains,
             ignore_release_candidates: &ignore_release_candidates,
+            all_versions: &all_versions,
+            table_style,
+            output,
+            group_by,
+            columns,
+            template,
         };
 
-        card_lister.validate_registry()?;
         card_lister.construct_tags(tag_name, tag_value);
+
+        if output.raw && (registry == ALL_REGISTRIES || watch.is_some() || count) {
+            return Err(anyhow::Error::msg(
+                "--raw cannot be combined with --registry all, --watch or --count",
+            ));
+        }
+
+        if group_by.is_some() && (registry == ALL_REGISTRIES || output.raw) {
+            return Err(anyhow::Error::msg(
+                "--group-by cannot be combined with --registry all or --raw",
+            ));
+        }
+
+        if columns.is_some() && (registry == ALL_REGISTRIES || output.raw) {
+            return Err(anyhow::Error::msg(
+                "--columns cannot be combined with --registry all or --raw",
+            ));
+        }
+
+        if let Some(template) = template {
+            if registry == ALL_REGISTRIES || output.is_json() {
+                return Err(anyhow::Error::msg(
+                    "--format-template cannot be combined with --registry all or --output json",
+                ));
+            }
+            parse_template(template)?;
+        }
+
+        if registry == ALL_REGISTRIES {
+            if watch.is_some() {
+                return Err(anyhow::Error::msg(
+                    "--watch is not supported with --registry all",
+                ));
+            }
+            let card_count = card_lister.print_all_registry_cards(count).await?;
+            return check_not_empty(card_count, fail_if_empty);
+        }
+
+        card_lister.validate_registry()?;
+        card_lister.validate_columns()?;
+
+        if let Some(interval) = watch {
+            return card_lister.watch_cards(interval).await;
+        }
+
         let response = card_lister.make_card_request().await?;
 
         if response.status().is_success() {
-            let card_table = card_lister.parse_list_response(&response.text().await.unwrap());
+            let body = response.text().await?;
 
-            println!(
-                "\nListing cards from {} registry",
-                registry.to_string().bold().green()
-            );
-            println!("{}", card_table?);
+            if count {
+                let card_count = card_lister.parse_count_response(&body)?;
+                output::print(card_count)?;
+                return check_not_empty(card_count, fail_if_empty);
+            }
+
+            let card_table = card_lister.parse_list_response(&body)?;
+
+            if !card_lister.output.is_plain() && template.is_none() {
+                output::print(format!(
+                    "\nListing cards from {} registry",
+                    registry.to_string().bold().green()
+                ))?;
+            }
+            // a template prints nothing when no cards match
+            if !card_table.is_empty() {
+                output::print(card_table)?;
+            }
+
+            if fail_if_empty {
+                check_not_empty(card_lister.parse_count_response(&body)?, fail_if_empty)?;
+            }
             Ok(())
         } else {
-            Err(anyhow::Error::msg(format!(
-                "Failed to make call to list cards: {}",
-                response.text().await.unwrap()
-            )))
+            Err(RouteHelper::response_error(response, "Failed to make call to list cards").await)
         }
     }
-}
 
-/// List cards
-///     
-/// # Arguments
-///
-/// * `registry` - Registry to list cards from
-/// * `name` - Name of card
-/// * `repository` - repository name
+    async fn get_teams(registry: &str) -> Result<(), anyhow::Error> {
+        let card_lister = CardLister {
+            registry_type: registry,
+            name: None,
+            repository: None,
+            version: None,
+            uid: None,
+            limit: None,
+            tags: HashMap::new(),
+            max_date: None,
+            min_date: None,
+            name_contains: None,
+            ignore_release_candidates: &false,
+            all_versions: &false,
+            table_style: utils::TableStyle::Sharp,
+            output: output::OutputOptions::default(),
+            group_by: None,
+            columns: None,
+            template: None,
+        };
+
+        card_lister.validate_registry()?;
+        let response = card_lister.make_card_request().await?;
+
+        if response.status().is_success() {
+            let teams = card_lister.parse_team_response(&response.text().await?)?;
+
+            output::print(format!(
+                "\nListing teams from {} registry",
+                registry.to_string().bold().green()
+            ))?;
+            for team in teams.iter() {
+                output::print(team)?;
+            }
+            Ok(())
+        } else {
+            Err(RouteHelper::response_error(response, "Failed to make call to list teams").await)
+        }
+    }
+}
+
+/// Gets the value of a card column for a table cell
+///
+/// # Arguments
+///
+/// * `card` - Card to read
+/// * `column` - Column name, one of `CARD_COLUMNS`
+///
+fn card_cell(card: &types::Card, column: &str) -> String {
+    match column {
+        "name" => card.name.clone(),
+        "repository" => card.repository.clone(),
+        "date" => card.date.clone().unwrap_or_default(),
+        "contact" => card.contact.clone(),
+        "version" => card.version.clone(),
+        "uid" => card.uid.clone(),
+        _ => String::new(),
+    }
+}
+
+/// Part of a card template
+#[derive(Debug, PartialEq)]
+enum TemplatePart<'a> {
+    Text(&'a str),
+    Field(&'a str),
+}
+
+/// Parses a card template into text and `{field}` placeholders
+///
+/// # Arguments
+///
+/// * `template` - Template such as `{name} {version} {uid}`
+///
+/// # Returns
+/// * `Result<Vec<TemplatePart>, String>` - Template parts, in order
+///
+fn parse_template(template: &str) -> Result<Vec<TemplatePart<'_>>, anyhow::Error> {
+    let mut parts = Vec::new();
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        if start > 0 {
+            parts.push(TemplatePart::Text(&rest[..start]));
+        }
+        let end = rest[start..]
+            .find('}')
+            .with_context(|| format!("Unclosed placeholder in template {:?}", template))?;
+        let field = rest[start + 1..start + end].trim();
+
+        if !CARD_COLUMNS.contains(&field) {
+            return Err(anyhow::Error::msg(format!(
+                "Unknown placeholder {{{}}} in template. Valid placeholders are: {}",
+                field,
+                CARD_COLUMNS.join(", ")
+            )));
+        }
+        parts.push(TemplatePart::Field(field));
+        rest = &rest[start + end + 1..];
+    }
+
+    if !rest.is_empty() {
+        parts.push(TemplatePart::Text(rest));
+    }
+
+    Ok(parts)
+}
+
+/// Renders a template once per card
+///
+/// # Arguments
+///
+/// * `template` - Template such as `{name} {version} {uid}`
+/// * `cards` - Cards to render
+///
+/// # Returns
+/// * `Result<String, String>` - One line per card
+///
+fn render_card_template(template: &str, cards: &[types::Card]) -> Result<String, anyhow::Error> {
+    let parts = parse_template(template)?;
+
+    Ok(cards
+        .iter()
+        .map(|card| {
+            parts
+                .iter()
+                .map(|part| match part {
+                    TemplatePart::Text(text) => text.to_string(),
+                    TemplatePart::Field(field) => card_cell(card, field),
+                })
+                .collect::<String>()
+        })
+        .collect::<Vec<String>>()
+        .join("\n"))
+}
+
+/// Fails when no cards were listed and `--fail-if-empty` is set
+///
+/// # Arguments
+///
+/// * `card_count` - Number of cards listed
+/// * `fail_if_empty` - Whether an empty listing is an error
+///
+fn check_not_empty(card_count: usize, fail_if_empty: bool) -> Result<(), anyhow::Error> {
+    if fail_if_empty && card_count == 0 {
+        return Err(anyhow::Error::msg(NO_CARDS_FOUND));
+    }
+    Ok(())
+}
+
+/// Finds uids of cards that were not present in a previous poll
+///
+/// # Arguments
+///
+/// * `previous` - Uids seen in the previous poll
+/// * `cards` - Cards returned by the current poll
+///
+fn find_new_uids(previous: &HashSet<String>, cards: &[types::Card]) -> HashSet<String> {
+    cards
+        .iter()
+        .filter(|card| !previous.contains(&card.uid))
+        .map(|card| card.uid.clone())
+        .collect()
+}
+
+/// Parses a `key=value` card tag given on the command line
+///
+/// # Arguments
+///
+/// * `tag` - Tag in `key=value` form
+///
+/// # Returns
+/// * `Result<(String, String), String>` - Tag name and value
+///
+pub fn parse_tag(tag: &str) -> Result<(String, String), String> {
+    match tag.split_once('=') {
+        Some((key, value)) if !key.trim().is_empty() => {
+            Ok((key.trim().to_string(), value.trim().to_string()))
+        }
+        _ => Err(format!("Invalid tag {:?}. Expected key=value", tag)),
+    }
+}
+
+/// Earliest date to list cards from for `--since`
+///
+/// The server filters cards by date, so the cutoff is rounded down to the start of its day
+///
+/// # Arguments
+///
+/// * `since` - How long before `now` to list cards from
+/// * `now` - Current time
+///
+/// # Returns
+/// * `Result<String, String>` - Cutoff date as `YYYY-MM-DD`
+///
+fn since_min_date(since: Duration, now: DateTime<Utc>) -> Result<String, anyhow::Error> {
+    chrono::Duration::from_std(since)
+        .ok()
+        .and_then(|since| now.checked_sub_signed(since))
+        .map(|cutoff| cutoff.format("%Y-%m-%d").to_string())
+        .with_context(|| format!("--since {:?} is too far in the past", since))
+}
+
+/// Finds the valid registry closest to a mistyped one
+///
+/// # Arguments
+///
+/// * `registry` - Invalid registry name
+///
+/// # Returns
+/// * `Option<&str>` - Closest registry, or None when no registry is within `MAX_SUGGESTION_DISTANCE` edits
+///
+fn suggest_registry(registry: &str) -> Option<&'static str> {
+    let registry = registry.to_lowercase();
+
+    REGISTRIES
+        .iter()
+        .chain([ALL_REGISTRIES].iter())
+        .map(|candidate| (strsim::levenshtein(&registry, candidate), *candidate))
+        .filter(|(distance, _)| *distance <= MAX_SUGGESTION_DISTANCE)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+/// Resolves the registry to list cards from
+///
+/// An explicit registry takes precedence over `OPSML_DEFAULT_REGISTRY`, which is set from
+/// `default_registry` in the config file
+///
+/// # Arguments
+///
+/// * `registry` - Registry given on the command line
+///
+/// # Returns
+/// * `Result<String, String>` - Registry name
+///
+fn resolve_registry(registry: Option<&str>) -> Result<String, anyhow::Error> {
+    match registry {
+        Some(registry) => Ok(registry.to_string()),
+        None => env::var("OPSML_DEFAULT_REGISTRY").map_err(|_| {
+            anyhow::Error::msg(
+                "No registry given. Pass --registry, a registry:name:version identifier, or set default_registry in the config file",
+            )
+        }),
+    }
+}
+
+/// List cards
+///     
+/// # Arguments
+///
+/// * `registry` - Registry to list cards from, or `all` to list every registry. Falls back to the configured default registry
+/// * `name` - Name of card
+/// * `repository` - repository name
 /// * `version` - Card version
 /// * `uid` - Card uid
 /// * `limit` - Limit number of cards returned
@@ -199,11 +1093,23 @@ impl CardLister<'_> {
 /// * `tag_name` - Tag name
 /// * `tag_value` - Tag value
 /// * `max_date` - Max date
+/// * `since` - Only list cards dated within this long before now
+/// * `name_contains` - Case-insensitive substring card names must contain
+/// * `ignore_release_candidates` - Exclude release candidates
+/// * `all_versions` - Ask the server for every version of matching cards instead of the latest
+/// * `table_style` - Style used to render the table
+/// * `output` - Output format options
+/// * `count` - Print only the number of matching cards
+/// * `watch` - Poll interval in seconds. Re-lists cards until interrupted when set
+/// * `group_by` - Column to group cards by
+/// * `columns` - Table columns to render. Falls back to the registry's configured columns
+/// * `template` - Template such as `{name} {version}` printed once per card instead of a table
+/// * `fail_if_empty` - Return an error when no cards match, after printing the empty result
 ///
 #[tokio::main]
 #[allow(clippy::too_many_arguments)]
 pub async fn list_cards(
-    registry: &str,
+    registry: Option<&str>,
     name: Option<&str>,
     repository: Option<&str>,
     version: Option<&str>,
@@ -212,10 +1118,26 @@ pub async fn list_cards(
     tag_name: Option<Vec<String>>,
     tag_value: Option<Vec<String>>,
     max_date: Option<&str>,
+    since: Option<Duration>,
+    name_contains: Option<&str>,
     ignore_release_candidates: bool,
+    all_versions: bool,
+    table_style: utils::TableStyle,
+    output: output::OutputOptions,
+    count: bool,
+    watch: Option<u64>,
+    group_by: Option<CardGroup>,
+    columns: Option<&[String]>,
+    template: Option<&str>,
+    fail_if_empty: bool,
 ) -> Result<(), anyhow::Error> {
-    CardLister::get_cards(
-        registry,
+    let registry = resolve_registry(registry)?;
+    let min_date = match since {
+        Some(since) => Some(since_min_date(since, Utc::now())?),
+        None => None,
+    };
+    utils::with_shared_client(CardLister::get_cards(
+        &registry,
         name,
         repository,
         version,
@@ -224,15 +1146,76 @@ pub async fn list_cards(
         tag_name,
         tag_value,
         max_date,
+        min_date.as_deref(),
+        name_contains,
         ignore_release_candidates,
-    )
+        all_versions,
+        table_style,
+        output,
+        count,
+        watch,
+        group_by,
+        columns,
+        template,
+        fail_if_empty,
+    ))
     .await
 }
 
+/// List the most recently dated cards in a registry, newest first
+///
+/// # Arguments
+///
+/// * `registry` - Registry to list cards from
+/// * `limit` - Maximum number of cards to list
+/// * `table_style` - Style used to render the table
+/// * `output` - Output format options
+///
+#[tokio::main]
+pub async fn list_recent_cards(
+    registry: &str,
+    limit: usize,
+    table_style: utils::TableStyle,
+    output: output::OutputOptions,
+) -> Result<(), anyhow::Error> {
+    let card_lister = CardLister {
+        registry_type: registry,
+        name: None,
+        repository: None,
+        version: None,
+        uid: None,
+        limit: None,
+        tags: HashMap::new(),
+        max_date: None,
+        min_date: None,
+        name_contains: None,
+        ignore_release_candidates: &false,
+        all_versions: &false,
+        table_style,
+        output,
+        group_by: None,
+        columns: None,
+        template: None,
+    };
+
+    utils::with_shared_client(card_lister.print_recent_cards(limit)).await
+}
+
+/// List teams that own cards in a registry
+///
+/// # Arguments
+///
+/// * `registry` - Registry to list teams from
+///
+#[tokio::main]
+pub async fn list_teams(registry: &str) -> Result<(), anyhow::Error> {
+    utils::with_shared_client(CardLister::get_teams(registry)).await
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::env;
+    use crate::api::test_utils::{TestDir, TestEnv};
     use std::fs;
     use tokio;
 
@@ -261,7 +1244,15 @@ mod tests {
             limit: None,
             tags: HashMap::new(),
             max_date: None,
+            min_date: None,
+            name_contains: None,
             ignore_release_candidates: &false,
+            all_versions: &false,
+            table_style: utils::TableStyle::Sharp,
+            output: output::OutputOptions::default(),
+            group_by: None,
+            columns: None,
+            template: None,
         };
 
         let card_table = card_lister.parse_list_response(&string_response);
@@ -277,29 +1268,1034 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_parse_response_name_contains() {
+        let mut vec = Vec::new();
+        for (name, uid) in [
+            ("Churn-Model", "uid-1"),
+            ("fraud-model", "uid-2"),
+            ("churn-features", "uid-3"),
+        ] {
+            vec.push(types::Card {
+                name: name.to_string(),
+                repository: "repo".to_string(),
+                date: None,
+                contact: "fake_email".to_string(),
+                version: "1.0.0".to_string(),
+                uid: uid.to_string(),
+                tags: HashMap::new(),
+            });
+        }
+        let mock_response = types::ListCardResponse { cards: vec };
+        let string_response = serde_json::to_string(&mock_response).unwrap();
+
+        let mut card_lister = CardLister {
+            registry_type: "model",
+            name: None,
+            repository: None,
+            version: None,
+            uid: None,
+            limit: None,
+            tags: HashMap::new(),
+            max_date: None,
+            min_date: None,
+            name_contains: Some("CHURN"),
+            ignore_release_candidates: &false,
+            all_versions: &false,
+            table_style: utils::TableStyle::Markdown,
+            output: output::OutputOptions::default(),
+            group_by: None,
+            columns: None,
+            template: None,
+        };
+
+        let card_table = card_lister.parse_list_response(&string_response).unwrap();
+        assert!(card_table.contains("Churn-Model"));
+        assert!(card_table.contains("churn-features"));
+        assert!(!card_table.contains("fraud-model"));
+        assert_eq!(
+            card_lister.parse_count_response(&string_response).unwrap(),
+            2
+        );
+
+        card_lister.name_contains = Some("model");
+        assert_eq!(
+            card_lister.parse_count_response(&string_response).unwrap(),
+            2
+        );
+
+        card_lister.name_contains = Some("missing");
+        assert_eq!(
+            card_lister.parse_count_response(&string_response).unwrap(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_parse_response_jsonl() {
+        let cards: Vec<types::Card> = ["model-a", "model-b", "model-c"]
+            .iter()
+            .map(|name| types::Card {
+                name: name.to_string(),
+                repository: "repo".to_string(),
+                date: None,
+                contact: "fake_email".to_string(),
+                version: "1.0.0".to_string(),
+                uid: format!("{}-uid", name),
+                tags: HashMap::new(),
+            })
+            .collect();
+        let string_response = serde_json::to_string(&types::ListCardResponse { cards }).unwrap();
+
+        let card_lister = CardLister {
+            registry_type: "model",
+            name: None,
+            repository: None,
+            version: None,
+            uid: None,
+            limit: None,
+            tags: HashMap::new(),
+            max_date: None,
+            min_date: None,
+            name_contains: None,
+            ignore_release_candidates: &false,
+            all_versions: &false,
+            table_style: utils::TableStyle::Sharp,
+            output: output::OutputOptions {
+                format: output::OutputFormat::Jsonl,
+                ..Default::default()
+            },
+            group_by: None,
+            columns: None,
+            template: None,
+        };
+
+        let output = card_lister.parse_list_response(&string_response).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines.len(), 3);
+        for (line, name) in lines.iter().zip(["model-a", "model-b", "model-c"]) {
+            let card: types::Card = serde_json::from_str(line).unwrap();
+            assert_eq!(card.name, name);
+        }
+    }
+
+    #[test]
+    fn test_parse_response_group_by_team() {
+        let card = |name: &str, repository: &str| types::Card {
+            name: name.to_string(),
+            repository: repository.to_string(),
+            date: None,
+            contact: "fake_email".to_string(),
+            version: "1.0.0".to_string(),
+            uid: name.to_string(),
+            tags: HashMap::new(),
+        };
+        let mock_response = types::ListCardResponse {
+            cards: vec![
+                card("a", "team-b"),
+                card("b", "team-a"),
+                card("c", "team-b"),
+            ],
+        };
+        let string_response = serde_json::to_string(&mock_response).unwrap();
+
+        let card_lister = CardLister {
+            registry_type: "model",
+            name: None,
+            repository: None,
+            version: None,
+            uid: None,
+            limit: None,
+            tags: HashMap::new(),
+            max_date: None,
+            min_date: None,
+            name_contains: None,
+            ignore_release_candidates: &false,
+            all_versions: &false,
+            table_style: utils::TableStyle::Sharp,
+            output: output::OutputOptions::default(),
+            group_by: Some(CardGroup::Team),
+            columns: None,
+            template: None,
+        };
+
+        let card_table = card_lister.parse_list_response(&string_response);
+        assert_eq!(
+            card_table.unwrap(),
+            concat!(
+                "┌────────┬──────┬──────┬────────────┬─────────┬─────┐\n",
+                "│  team  │ name │ date │  contact   │ version │ uid │\n",
+                "├────────┼──────┼──────┼────────────┼─────────┼─────┤\n",
+                "│ team-a │  b   │      │ fake_email │  1.0.0  │  b  │\n",
+                "│ team-b │  a   │      │ fake_email │  1.0.0  │  a  │\n",
+                "│        │  c   │      │ fake_email │  1.0.0  │  c  │\n",
+                "└────────┴──────┴──────┴────────────┴─────────┴─────┘",
+            )
+        );
+    }
+
     #[tokio::test]
-    async fn test_list_cards() {
+    async fn test_get_recent_cards() {
+        let _env = TestEnv::lock();
         let mut server = mockito::Server::new();
         let url = server.url();
-
         env::set_var("OPSML_TRACKING_URI", url);
 
-        let path = "./src/api/test_utils/list_cards.json";
-        let data = fs::read_to_string(path).expect("Unable to read file");
-
-        // Create a mock server
+        let card = |name: &str, date: Option<&str>| types::Card {
+            name: name.to_string(),
+            repository: "team".to_string(),
+            date: date.map(String::from),
+            contact: "fake_email".to_string(),
+            version: "1.0.0".to_string(),
+            uid: name.to_string(),
+            tags: HashMap::new(),
+        };
+        let mock_response = types::ListCardResponse {
+            cards: vec![
+                card("undated", None),
+                card("oldest", Some("2023-01-05")),
+                card("newest", Some("2023-11-20")),
+                card("middle", Some("2023-06-01")),
+            ],
+        };
         let mock = server
             .mock("POST", "/opsml/cards/list")
             .with_status(201)
-            .with_body(data)
+            .with_body(serde_json::to_string(&mock_response).unwrap())
+            .expect(2)
             .create();
 
-        CardLister::get_cards(
-            "model", None, None, None, None, None, None, None, None, false,
-        )
-        .await
-        .unwrap();
+        let card_lister = CardLister {
+            registry_type: "model",
+            name: None,
+            repository: None,
+            version: None,
+            uid: None,
+            limit: None,
+            tags: HashMap::new(),
+            max_date: None,
+            min_date: None,
+            name_contains: None,
+            ignore_release_candidates: &false,
+            all_versions: &false,
+            table_style: utils::TableStyle::Sharp,
+            output: output::OutputOptions::default(),
+            group_by: None,
+            columns: None,
+            template: None,
+        };
 
+        let names = |cards: Vec<types::Card>| -> Vec<String> {
+            cards.into_iter().map(|card| card.name).collect()
+        };
+        assert_eq!(
+            names(card_lister.get_recent_cards(10).await.unwrap()),
+            ["newest", "middle", "oldest", "undated"]
+        );
+        assert_eq!(
+            names(card_lister.get_recent_cards(2).await.unwrap()),
+            ["newest", "middle"]
+        );
         mock.assert();
     }
+
+    #[test]
+    fn test_parse_response_empty() {
+        let string_response =
+            serde_json::to_string(&types::ListCardResponse { cards: vec![] }).unwrap();
+
+        let mut card_lister = CardLister {
+            registry_type: "model",
+            name: None,
+            repository: None,
+            version: None,
+            uid: None,
+            limit: None,
+            tags: HashMap::new(),
+            max_date: None,
+            min_date: None,
+            name_contains: None,
+            ignore_release_candidates: &false,
+            all_versions: &false,
+            table_style: utils::TableStyle::Sharp,
+            output: output::OutputOptions::default(),
+            group_by: None,
+            columns: None,
+            template: None,
+        };
+        assert_eq!(
+            card_lister.parse_list_response(&string_response).unwrap(),
+            NO_CARDS_FOUND
+        );
+
+        card_lister.output = output::OutputOptions {
+            format: output::OutputFormat::Json,
+            ..Default::default()
+        };
+        assert_eq!(
+            card_lister.parse_list_response(&string_response).unwrap(),
+            "[]"
+        );
+    }
+
+    #[test]
+    fn test_parse_response_raw() {
+        let card_lister = CardLister {
+            registry_type: "model",
+            name: None,
+            repository: None,
+            version: None,
+            uid: None,
+            limit: None,
+            tags: HashMap::new(),
+            max_date: None,
+            min_date: None,
+            name_contains: Some("churn"),
+            ignore_release_candidates: &false,
+            all_versions: &false,
+            table_style: utils::TableStyle::Sharp,
+            output: output::OutputOptions {
+                raw: true,
+                ..Default::default()
+            },
+            group_by: None,
+            columns: None,
+            template: None,
+        };
+
+        // body is returned untouched, including fields the cli does not know about
+        let body = r#"{"cards": [{"name": "fraud", "extra": 1}],  "server": "v2"}"#;
+        assert_eq!(card_lister.parse_list_response(body).unwrap(), body);
+    }
+
+    #[test]
+    fn test_parse_team_response() {
+        let mut vec = Vec::new();
+        for repository in ["team-b", "team-a", "team-b", "team-c", "team-a"] {
+            vec.push(types::Card {
+                name: "test".to_string(),
+                repository: repository.to_string(),
+                date: None,
+                contact: "fake_email".to_string(),
+                version: "1.0.0".to_string(),
+                uid: "uid".to_string(),
+                tags: HashMap::new(),
+            });
+        }
+        let mock_response = types::ListCardResponse { cards: vec };
+        let string_response = serde_json::to_string(&mock_response).unwrap();
+
+        let card_lister = CardLister {
+            registry_type: "model",
+            name: None,
+            repository: None,
+            version: None,
+            uid: None,
+            limit: None,
+            tags: HashMap::new(),
+            max_date: None,
+            min_date: None,
+            name_contains: None,
+            ignore_release_candidates: &false,
+            all_versions: &false,
+            table_style: utils::TableStyle::Sharp,
+            output: output::OutputOptions::default(),
+            group_by: None,
+            columns: None,
+            template: None,
+        };
+
+        let teams = card_lister.parse_team_response(&string_response).unwrap();
+        assert_eq!(teams, vec!["team-a", "team-b", "team-c"]);
+    }
+
+    #[test]
+    fn test_parse_response_markdown() {
+        let path = "./src/api/test_utils/list_cards.json";
+        let data = fs::read_to_string(path).expect("Unable to read file");
+
+        let card_lister = CardLister {
+            registry_type: "model",
+            name: None,
+            repository: None,
+            version: None,
+            uid: None,
+            limit: None,
+            tags: HashMap::new(),
+            max_date: None,
+            min_date: None,
+            name_contains: None,
+            ignore_release_candidates: &false,
+            all_versions: &false,
+            table_style: utils::TableStyle::Markdown,
+            output: output::OutputOptions::default(),
+            group_by: None,
+            columns: None,
+            template: None,
+        };
+
+        let card_table = card_lister.parse_list_response(&data).unwrap();
+        for line in card_table.lines() {
+            assert!(line.starts_with('|'));
+            assert!(line.ends_with('|'));
+        }
+        assert_eq!(card_table.lines().count(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_parse_count_response() {
+        let _env = TestEnv::lock();
+        let mut vec = Vec::new();
+        for uid in ["uid1", "uid2", "uid3"] {
+            vec.push(types::Card {
+                name: "test".to_string(),
+                repository: "test".to_string(),
+                date: None,
+                contact: "fake_email".to_string(),
+                version: "1.0.0".to_string(),
+                uid: uid.to_string(),
+                tags: HashMap::new(),
+            });
+        }
+        let mock_response = types::ListCardResponse { cards: vec };
+        let string_response = serde_json::to_string(&mock_response).unwrap();
+
+        let card_lister = CardLister {
+            registry_type: "model",
+            name: None,
+            repository: None,
+            version: None,
+            uid: None,
+            limit: None,
+            tags: HashMap::new(),
+            max_date: None,
+            min_date: None,
+            name_contains: None,
+            ignore_release_candidates: &false,
+            all_versions: &false,
+            table_style: utils::TableStyle::Sharp,
+            output: output::OutputOptions::default(),
+            group_by: None,
+            columns: None,
+            template: None,
+        };
+
+        assert_eq!(
+            card_lister.parse_count_response(&string_response).unwrap(),
+            3
+        );
+
+        let mut server = mockito::Server::new();
+        env::set_var("OPSML_TRACKING_URI", server.url());
+        let mock = server
+            .mock("POST", "/opsml/cards/list")
+            .with_status(201)
+            .with_body(&string_response)
+            .create();
+
+        // --count prints only the number of cards, without a header or table
+        output::capture();
+        CardLister::get_cards(
+            "model",
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            false,
+            false,
+            utils::TableStyle::Sharp,
+            output::OutputOptions::default(),
+            true,
+            None,
+            None,
+            None,
+            None,
+            false,
+        )
+        .await
+        .unwrap();
+
+        mock.assert();
+        assert_eq!(output::take_captured(), "3\n");
+    }
+
+    #[test]
+    fn test_find_new_uids() {
+        let card = |uid: &str| types::Card {
+            name: "test".to_string(),
+            repository: "test".to_string(),
+            date: None,
+            contact: "fake_email".to_string(),
+            version: "1.0.0".to_string(),
+            uid: uid.to_string(),
+            tags: HashMap::new(),
+        };
+
+        let previous: HashSet<String> = ["uid1".to_string(), "uid2".to_string()].into();
+        let cards = vec![card("uid1"), card("uid2"), card("uid3")];
+
+        let new_uids = find_new_uids(&previous, &cards);
+        assert_eq!(new_uids, HashSet::from(["uid3".to_string()]));
+
+        // nothing new when the same cards are returned
+        let previous: HashSet<String> = cards.iter().map(|card| card.uid.clone()).collect();
+        assert!(find_new_uids(&previous, &cards).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_list_all_versions() {
+        let _env = TestEnv::lock();
+        let mut server = mockito::Server::new();
+        let url = server.url();
+        env::set_var("OPSML_TRACKING_URI", url);
+
+        let response = types::ListCardResponse {
+            cards: ["1.1.0", "1.0.0"]
+                .into_iter()
+                .map(|version| types::Card {
+                    name: "card".to_string(),
+                    repository: "repo".to_string(),
+                    date: None,
+                    contact: "fake_email".to_string(),
+                    version: version.to_string(),
+                    uid: format!("uid-{}", version),
+                    tags: HashMap::new(),
+                })
+                .collect(),
+        };
+
+        let mock_list = server
+            .mock("POST", "/opsml/cards/list")
+            .match_body(mockito::Matcher::PartialJson(serde_json::json!({
+                "name": "card",
+                "all_versions": true,
+            })))
+            .with_status(201)
+            .with_body(serde_json::to_string(&response).unwrap())
+            .create();
+
+        let card_lister = CardLister {
+            registry_type: "model",
+            name: Some("card"),
+            repository: None,
+            version: None,
+            uid: None,
+            limit: None,
+            tags: HashMap::new(),
+            max_date: None,
+            min_date: None,
+            name_contains: None,
+            ignore_release_candidates: &false,
+            all_versions: &true,
+            table_style: utils::TableStyle::Markdown,
+            output: output::OutputOptions::default(),
+            group_by: None,
+            columns: None,
+            template: None,
+        };
+
+        let cards = card_lister.get_card_response().await.unwrap();
+        mock_list.assert();
+
+        assert_eq!(
+            card_lister.build_card_table(&cards.cards, &HashSet::new()),
+            concat!(
+                "| name | repository | date |  contact   | version |    uid    |\n",
+                "|------|------------|------|------------|---------|-----------|\n",
+                "| card |    repo    |      | fake_email |  1.1.0  | uid-1.1.0 |\n",
+                "| card |    repo    |      | fake_email |  1.0.0  | uid-1.0.0 |",
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn test_list_all_registries() {
+        let _env = TestEnv::lock();
+        let mut server = mockito::Server::new();
+        let url = server.url();
+        env::set_var("OPSML_TRACKING_URI", url);
+
+        let mut mocks = Vec::new();
+        for registry in ["data", "model"] {
+            let response = types::ListCardResponse {
+                cards: vec![types::Card {
+                    name: format!("{}-card", registry),
+                    repository: "repo".to_string(),
+                    date: None,
+                    contact: "fake_email".to_string(),
+                    version: "1.0.0".to_string(),
+                    uid: format!("{}-uid", registry),
+                    tags: HashMap::new(),
+                }],
+            };
+
+            mocks.push(
+                server
+                    .mock("POST", "/opsml/cards/list")
+                    .match_body(mockito::Matcher::PartialJson(
+                        serde_json::json!({ "registry_type": registry }),
+                    ))
+                    .with_status(201)
+                    .with_body(serde_json::to_string(&response).unwrap())
+                    .create(),
+            );
+        }
+
+        // remaining registries fail
+        let mock_error = server
+            .mock("POST", "/opsml/cards/list")
+            .with_status(500)
+            .with_body("registry unavailable")
+            .expect(4)
+            .create();
+
+        let card_lister = CardLister {
+            registry_type: ALL_REGISTRIES,
+            name: None,
+            repository: None,
+            version: None,
+            uid: None,
+            limit: None,
+            tags: HashMap::new(),
+            max_date: None,
+            min_date: None,
+            name_contains: None,
+            ignore_release_candidates: &false,
+            all_versions: &false,
+            table_style: utils::TableStyle::Markdown,
+            output: output::OutputOptions::default(),
+            group_by: None,
+            columns: None,
+            template: None,
+        };
+
+        let (cards, errors) = card_lister.get_all_registry_cards().await;
+        for mock in mocks.iter() {
+            mock.assert();
+        }
+        mock_error.assert();
+
+        assert_eq!(errors.len(), 4);
+        assert!(errors[0].starts_with("run: "));
+
+        assert_eq!(
+            card_lister.build_registry_card_table(&cards),
+            concat!(
+                "| registry |    name    | repository | date |  contact   | version |    uid    |\n",
+                "|----------|------------|------------|------|------------|---------|-----------|\n",
+                "|   data   | data-card  |    repo    |      | fake_email |  1.0.0  | data-uid  |\n",
+                "|  model   | model-card |    repo    |      | fake_email |  1.0.0  | model-uid |",
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn test_list_cards() {
+        let _env = TestEnv::lock();
+        let mut server = mockito::Server::new();
+        let url = server.url();
+
+        env::set_var("OPSML_TRACKING_URI", url);
+
+        let path = "./src/api/test_utils/list_cards.json";
+        let data = fs::read_to_string(path).expect("Unable to read file");
+
+        // Create a mock server
+        let mock = server
+            .mock("POST", "/opsml/cards/list")
+            .with_status(201)
+            .with_body(data)
+            .create();
+
+        CardLister::get_cards(
+            "model",
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            false,
+            false,
+            utils::TableStyle::Sharp,
+            output::OutputOptions::default(),
+            false,
+            None,
+            None,
+            None,
+            None,
+            false,
+        )
+        .await
+        .unwrap();
+
+        mock.assert();
+    }
+
+    #[tokio::test]
+    async fn test_list_cards_fail_if_empty() {
+        let _env = TestEnv::lock();
+        let mut server = mockito::Server::new();
+        let url = server.url();
+        env::set_var("OPSML_TRACKING_URI", url);
+
+        let data = fs::read_to_string("./src/api/test_utils/list_cards.json").unwrap();
+        let empty = serde_json::to_string(&types::ListCardResponse { cards: vec![] }).unwrap();
+
+        for (body, expect_ok) in [(data, true), (empty, false)] {
+            let mock = server
+                .mock("POST", "/opsml/cards/list")
+                .with_status(201)
+                .with_body(body)
+                .create();
+
+            let result = CardLister::get_cards(
+                "model",
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                false,
+                false,
+                utils::TableStyle::Sharp,
+                output::OutputOptions::default(),
+                false,
+                None,
+                None,
+                None,
+                None,
+                true,
+            )
+            .await;
+
+            mock.assert();
+            if expect_ok {
+                result.unwrap();
+            } else {
+                assert_eq!(result.unwrap_err().to_string(), NO_CARDS_FOUND);
+            }
+            mock.remove();
+        }
+    }
+
+    #[test]
+    fn test_parse_response_registry_columns() {
+        let _env = TestEnv::lock();
+        let output_dir = TestDir::new();
+        let test_dir = output_dir.path();
+        fs::create_dir_all(test_dir).unwrap();
+        let config_path = format!("{}/config.toml", test_dir);
+        fs::write(
+            &config_path,
+            "[columns]\nmodel = [\"name\", \"version\", \"uid\"]\n",
+        )
+        .unwrap();
+
+        env::set_var("OPSML_CONFIG_PATH", &config_path);
+        crate::api::config::apply_config(None).unwrap();
+        env::remove_var("OPSML_CONFIG_PATH");
+
+        let data = fs::read_to_string("./src/api/test_utils/list_cards.json").unwrap();
+        let mut card_lister = CardLister {
+            registry_type: "model",
+            name: None,
+            repository: None,
+            version: None,
+            uid: None,
+            limit: None,
+            tags: HashMap::new(),
+            max_date: None,
+            min_date: None,
+            name_contains: None,
+            ignore_release_candidates: &false,
+            all_versions: &false,
+            table_style: utils::TableStyle::Markdown,
+            output: output::OutputOptions::default(),
+            group_by: None,
+            columns: None,
+            template: None,
+        };
+
+        // model cards use the configured columns
+        assert_eq!(
+            card_lister.parse_list_response(&data).unwrap(),
+            concat!(
+                "| name | version |    uid     |\n",
+                "|------|---------|------------|\n",
+                "| name |  1.0.0  | 1234567890 |",
+            )
+        );
+
+        // --columns overrides the configured columns
+        let columns = vec!["uid".to_string(), "contact".to_string()];
+        card_lister.columns = Some(&columns);
+        assert_eq!(
+            card_lister.parse_list_response(&data).unwrap(),
+            concat!(
+                "|    uid     |     contact      |\n",
+                "|------------|------------------|\n",
+                "| 1234567890 | devops@opsml.com |",
+            )
+        );
+
+        // other registries keep every column
+        card_lister.columns = None;
+        card_lister.registry_type = "data";
+        assert!(card_lister
+            .parse_list_response(&data)
+            .unwrap()
+            .contains("repository"));
+
+        env::remove_var("OPSML_COLUMNS_MODEL");
+    }
+
+    #[tokio::test]
+    async fn test_card_request_trims_args() {
+        let _env = TestEnv::lock();
+        let mut server = mockito::Server::new();
+        env::set_var("OPSML_TRACKING_URI", server.url());
+
+        let mock = server
+            .mock("POST", "/opsml/cards/list")
+            .match_body(mockito::Matcher::PartialJson(serde_json::json!({
+                "name": "My-Model",
+                "repository": "team",
+                "version": "1.0.0",
+            })))
+            .with_status(201)
+            .with_body(r#"{"cards": []}"#)
+            .create();
+
+        let card_lister = CardLister {
+            registry_type: "model",
+            name: Some(" My-Model"),
+            repository: Some(" team "),
+            version: Some("1.0.0\t"),
+            uid: None,
+            limit: None,
+            tags: HashMap::new(),
+            max_date: None,
+            min_date: None,
+            name_contains: None,
+            ignore_release_candidates: &false,
+            all_versions: &false,
+            table_style: utils::TableStyle::Sharp,
+            output: output::OutputOptions::default(),
+            group_by: None,
+            columns: None,
+            template: None,
+        };
+
+        // whitespace is trimmed but case is kept
+        let response = card_lister.make_card_request().await.unwrap();
+        assert_eq!(response.status(), 201);
+        mock.assert();
+    }
+
+    #[test]
+    fn test_parse_response_format_template() {
+        let card = |name: &str, version: &str, uid: &str| types::Card {
+            name: name.to_string(),
+            repository: "team".to_string(),
+            date: None,
+            contact: "fake_email".to_string(),
+            version: version.to_string(),
+            uid: uid.to_string(),
+            tags: HashMap::new(),
+        };
+        let response = serde_json::to_string(&types::ListCardResponse {
+            cards: vec![
+                card("model-a", "1.0.0", "uid-a"),
+                card("model-b", "2.1.0", "uid-b"),
+            ],
+        })
+        .unwrap();
+
+        let mut card_lister = CardLister {
+            registry_type: "model",
+            name: None,
+            repository: None,
+            version: None,
+            uid: None,
+            limit: None,
+            tags: HashMap::new(),
+            max_date: None,
+            min_date: None,
+            name_contains: None,
+            ignore_release_candidates: &false,
+            all_versions: &false,
+            table_style: utils::TableStyle::Sharp,
+            output: output::OutputOptions::default(),
+            group_by: None,
+            columns: None,
+            template: Some("{name}@{version} ({ uid }) date={date}"),
+        };
+
+        assert_eq!(
+            card_lister.parse_list_response(&response).unwrap(),
+            "model-a@1.0.0 (uid-a) date=\nmodel-b@2.1.0 (uid-b) date=",
+        );
+
+        card_lister.template = Some("{name} {team}");
+        let error = card_lister
+            .parse_list_response(&response)
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("Unknown placeholder {team}"), "{}", error);
+
+        assert!(parse_template("{name").is_err());
+        assert_eq!(
+            parse_template("uid: {uid}").unwrap(),
+            vec![TemplatePart::Text("uid: "), TemplatePart::Field("uid")]
+        );
+    }
+
+    #[test]
+    fn test_parse_tag() {
+        assert_eq!(
+            parse_tag("status=prod").unwrap(),
+            ("status".to_string(), "prod".to_string())
+        );
+        assert_eq!(
+            parse_tag("query= a=b ").unwrap(),
+            ("query".to_string(), "a=b".to_string())
+        );
+        for tag in ["status", "=prod"] {
+            assert!(parse_tag(tag).unwrap_err().contains("Expected key=value"));
+        }
+    }
+
+    #[test]
+    fn test_since_min_date() {
+        let now = DateTime::parse_from_rfc3339("2024-03-10T08:30:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+
+        assert_eq!(
+            since_min_date(Duration::from_secs(7 * 24 * 60 * 60), now).unwrap(),
+            "2024-03-03"
+        );
+        // the cutoff is rounded down to the day it falls on
+        assert_eq!(
+            since_min_date(Duration::from_secs(9 * 60 * 60), now).unwrap(),
+            "2024-03-09"
+        );
+        assert_eq!(
+            since_min_date(Duration::from_secs(30 * 60), now).unwrap(),
+            "2024-03-10"
+        );
+        assert!(since_min_date(Duration::MAX, now).is_err());
+    }
+
+    #[test]
+    fn test_suggest_registry() {
+        assert_eq!(suggest_registry("modle"), Some("model"));
+        assert_eq!(suggest_registry("Models"), Some("model"));
+        assert_eq!(suggest_registry("pipline"), Some("pipeline"));
+        assert_eq!(suggest_registry("zzzzzz"), None);
+
+        let card_lister = CardLister {
+            registry_type: "modle",
+            name: None,
+            repository: None,
+            version: None,
+            uid: None,
+            limit: None,
+            tags: HashMap::new(),
+            max_date: None,
+            min_date: None,
+            name_contains: None,
+            ignore_release_candidates: &false,
+            all_versions: &false,
+            table_style: utils::TableStyle::default(),
+            output: output::OutputOptions::default(),
+            group_by: None,
+            columns: None,
+            template: None,
+        };
+        let error = card_lister.validate_registry().unwrap_err().to_string();
+        assert!(error.contains("Did you mean 'model'?"), "{}", error);
+
+        let card_lister = CardLister {
+            registry_type: "zzzzzz",
+            ..card_lister
+        };
+        let error = card_lister.validate_registry().unwrap_err().to_string();
+        assert!(!error.contains("Did you mean"), "{}", error);
+        assert!(error.contains("Valid registries are"));
+    }
+
+    #[test]
+    fn test_resolve_registry() {
+        let _env = TestEnv::lock();
+        env::set_var("OPSML_DEFAULT_REGISTRY", "model");
+        let default = resolve_registry(None);
+        let explicit = resolve_registry(Some("data"));
+        env::remove_var("OPSML_DEFAULT_REGISTRY");
+
+        assert_eq!(default.unwrap(), "model");
+        assert_eq!(explicit.unwrap(), "data");
+        assert!(resolve_registry(None)
+            .unwrap_err()
+            .to_string()
+            .contains("default_registry"));
+    }
+
+    #[test]
+    fn test_parse_card_id() {
+        assert_eq!(
+            CardId::parse("model:my-card:1.0.0").unwrap(),
+            CardId {
+                registry: Some("model".to_string()),
+                name: Some("my-card".to_string()),
+                version: Some("1.0.0".to_string()),
+                uid: None,
+            }
+        );
+        assert_eq!(
+            CardId::parse("my-card:1.0.0").unwrap(),
+            CardId {
+                registry: None,
+                name: Some("my-card".to_string()),
+                version: Some("1.0.0".to_string()),
+                uid: None,
+            }
+        );
+        assert_eq!(
+            CardId::parse("model::uid-1234").unwrap(),
+            CardId {
+                registry: Some("model".to_string()),
+                name: None,
+                version: None,
+                uid: Some("uid-1234".to_string()),
+            }
+        );
+
+        for (id, reason) in [
+            ("my-card", "missing version or uid"),
+            ("model:my-card:1.0.0:extra", "too many ':' separators"),
+            ("model::", "empty component"),
+            (":1.0.0", "empty component"),
+            ("models:my-card:1.0.0", "unknown registry models"),
+        ] {
+            let error = CardId::parse(id).unwrap_err().to_string();
+            assert!(error.contains(reason), "{}: {}", id, error);
+        }
+    }
 }
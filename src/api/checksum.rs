@@ -0,0 +1,296 @@
+/// Copyright (c) Shipt, Inc.
+/// This source code is licensed under the MIT license found in the
+/// LICENSE file in the root directory of this source tree.
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use md5::Md5;
+use sha2::{Digest, Sha256};
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, ValueEnum)]
+pub enum ChecksumAlgo {
+    #[default]
+    Sha256,
+    Md5,
+    Blake3,
+}
+
+impl ChecksumAlgo {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChecksumAlgo::Sha256 => "sha256",
+            ChecksumAlgo::Md5 => "md5",
+            ChecksumAlgo::Blake3 => "blake3",
+        }
+    }
+
+    /// Parses an algorithm name, ignoring case
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Algorithm name
+    ///
+    pub fn from_name(name: &str) -> Option<ChecksumAlgo> {
+        ChecksumAlgo::from_str(name, true).ok()
+    }
+}
+
+/// Running checksum of downloaded bytes
+pub enum Hasher {
+    Sha256(Sha256),
+    Md5(Md5),
+    Blake3(Box<blake3::Hasher>),
+}
+
+impl Hasher {
+    pub fn new(algo: ChecksumAlgo) -> Self {
+        match algo {
+            ChecksumAlgo::Sha256 => Hasher::Sha256(Sha256::new()),
+            ChecksumAlgo::Md5 => Hasher::Md5(Md5::new()),
+            ChecksumAlgo::Blake3 => Hasher::Blake3(Box::new(blake3::Hasher::new())),
+        }
+    }
+
+    pub fn update(&mut self, data: &[u8]) {
+        match self {
+            Hasher::Sha256(hasher) => hasher.update(data),
+            Hasher::Md5(hasher) => hasher.update(data),
+            Hasher::Blake3(hasher) => {
+                hasher.update(data);
+            }
+        }
+    }
+
+    /// Hex encoded checksum of all bytes written
+    pub fn finalize(self) -> String {
+        match self {
+            Hasher::Sha256(hasher) => format!("{:x}", hasher.finalize()),
+            Hasher::Md5(hasher) => format!("{:x}", hasher.finalize()),
+            Hasher::Blake3(hasher) => hasher.finalize().to_hex().to_string(),
+        }
+    }
+}
+
+/// Running checksums of a download: the checksum used for verification, and a sha256
+///
+/// The sha256 reuses the verification checksum when that is already sha256
+pub struct DownloadHasher {
+    checksum: Hasher,
+    sha256: Option<Hasher>,
+}
+
+impl DownloadHasher {
+    pub fn new(algo: ChecksumAlgo) -> Self {
+        DownloadHasher {
+            checksum: Hasher::new(algo),
+            sha256: (algo != ChecksumAlgo::Sha256).then(|| Hasher::new(ChecksumAlgo::Sha256)),
+        }
+    }
+
+    pub fn update(&mut self, data: &[u8]) {
+        self.checksum.update(data);
+        if let Some(sha256) = self.sha256.as_mut() {
+            sha256.update(data);
+        }
+    }
+
+    /// Adds the contents of a local file, such as the part of a download already on disk
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - File to read
+    ///
+    pub fn update_file(&mut self, path: &Path) -> Result<(), anyhow::Error> {
+        let mut file = File::open(path).with_context(|| format!("Failed to open {:?}", path))?;
+        let mut buffer = vec![0; 64 * 1024];
+
+        loop {
+            let read = file
+                .read(&mut buffer)
+                .with_context(|| format!("Failed to read {:?}", path))?;
+            if read == 0 {
+                return Ok(());
+            }
+            self.update(&buffer[..read]);
+        }
+    }
+
+    /// Hex encoded checksum and sha256 of all bytes written
+    pub fn finalize(self) -> (String, String) {
+        let checksum = self.checksum.finalize();
+        let sha256 = match self.sha256 {
+            Some(sha256) => sha256.finalize(),
+            None => checksum.clone(),
+        };
+
+        (checksum, sha256)
+    }
+}
+
+/// Computes the checksum of a local file
+///
+/// # Arguments
+///
+/// * `path` - File to read
+/// * `algo` - Checksum algorithm
+///
+/// # Returns
+/// * `Result<String, String>` - Hex encoded checksum
+///
+pub fn file_checksum(path: &Path, algo: ChecksumAlgo) -> Result<String, anyhow::Error> {
+    let mut file = File::open(path).with_context(|| format!("Failed to open {:?}", path))?;
+    let mut hasher = Hasher::new(algo);
+    let mut buffer = vec![0; 64 * 1024];
+
+    loop {
+        let read = file
+            .read(&mut buffer)
+            .with_context(|| format!("Failed to read {:?}", path))?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+
+    Ok(hasher.finalize())
+}
+
+/// Checksum provided by the server for a file
+#[derive(Debug, PartialEq)]
+pub struct ExpectedChecksum {
+    pub algo: ChecksumAlgo,
+    pub digest: String,
+}
+
+impl ExpectedChecksum {
+    /// Parses a server-provided checksum
+    ///
+    /// The algorithm is read from a prefix such as `sha256:` when present, otherwise
+    /// the default algorithm is used
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - Checksum, optionally prefixed with the algorithm
+    /// * `default_algo` - Algorithm to use when the checksum has no prefix
+    ///
+    /// # Returns
+    /// * `Result<ExpectedChecksum, String>` - Expected checksum
+    ///
+    pub fn parse(value: &str, default_algo: ChecksumAlgo) -> Result<Self, anyhow::Error> {
+        let (algo, digest) = match value.split_once(':') {
+            Some((name, digest)) => {
+                let algo = ChecksumAlgo::from_name(name.trim()).ok_or_else(|| {
+                    anyhow::Error::msg(format!("Unsupported checksum algorithm: {}", name))
+                })?;
+                (algo, digest)
+            }
+            None => (default_algo, value),
+        };
+
+        Ok(ExpectedChecksum {
+            algo,
+            digest: digest.trim().to_lowercase(),
+        })
+    }
+
+    /// Checks a computed checksum against the expected checksum
+    ///
+    /// # Arguments
+    ///
+    /// * `checksum` - Hex encoded checksum of the downloaded bytes
+    ///
+    pub fn verify(&self, checksum: &str) -> Result<(), anyhow::Error> {
+        if self.digest != checksum {
+            return Err(anyhow::Error::msg(format!(
+                "Checksum mismatch: expected {}:{}, got {}:{}",
+                self.algo.as_str(),
+                self.digest,
+                self.algo.as_str(),
+                checksum
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PAYLOAD: &[u8] = b"hello world";
+
+    fn checksum(algo: ChecksumAlgo, chunks: &[&[u8]]) -> String {
+        let mut hasher = Hasher::new(algo);
+        for chunk in chunks.iter() {
+            hasher.update(chunk);
+        }
+        hasher.finalize()
+    }
+
+    #[test]
+    fn test_download_hasher_sha256() {
+        let sha256 = checksum(ChecksumAlgo::Sha256, &[PAYLOAD]);
+
+        for algo in [ChecksumAlgo::Sha256, ChecksumAlgo::Md5] {
+            let mut hasher = DownloadHasher::new(algo);
+            hasher.update(b"hello");
+            hasher.update(b" world");
+
+            let (digest, download_sha256) = hasher.finalize();
+            assert_eq!(digest, checksum(algo, &[PAYLOAD]));
+            assert_eq!(download_sha256, sha256);
+        }
+    }
+
+    #[test]
+    fn test_checksum_algorithms() {
+        let known = [
+            (
+                ChecksumAlgo::Sha256,
+                "sha256:b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9",
+            ),
+            (ChecksumAlgo::Md5, "md5:5eb63bbbe01eeed093cb22bb8f5acdc3"),
+            (
+                ChecksumAlgo::Blake3,
+                "blake3:d74981efa70a0c880b8d8c1985d075dbcbf679b99a5f9914e5aaf96b831a9e24",
+            ),
+        ];
+
+        for (algo, server_checksum) in known.iter() {
+            let expected = ExpectedChecksum::parse(server_checksum, ChecksumAlgo::Sha256).unwrap();
+            assert_eq!(expected.algo, *algo);
+
+            // chunked updates match a single update
+            expected.verify(&checksum(*algo, &[PAYLOAD])).unwrap();
+            expected
+                .verify(&checksum(*algo, &[b"hello", b" ", b"world"]))
+                .unwrap();
+            assert!(expected.verify(&checksum(*algo, &[b"hello"])).is_err());
+        }
+    }
+
+    #[test]
+    fn test_parse_expected_checksum() {
+        // unprefixed checksums use the default algorithm
+        let expected =
+            ExpectedChecksum::parse("5EB63BBBE01EEED093CB22BB8F5ACDC3", ChecksumAlgo::Md5).unwrap();
+        assert_eq!(
+            expected,
+            ExpectedChecksum {
+                algo: ChecksumAlgo::Md5,
+                digest: "5eb63bbbe01eeed093cb22bb8f5acdc3".to_string(),
+            }
+        );
+
+        assert_eq!(
+            ExpectedChecksum::parse("SHA256:abc", ChecksumAlgo::Md5)
+                .unwrap()
+                .algo,
+            ChecksumAlgo::Sha256
+        );
+        assert!(ExpectedChecksum::parse("crc32:abc", ChecksumAlgo::Sha256).is_err());
+    }
+}
@@ -2,67 +2,408 @@
 /// This source code is licensed under the MIT license found in the
 /// LICENSE file in the root directory of this source tree.
 use crate::api::commands::{
-    CompareMetricArgs, DownloadModelArgs, ListCards, ModelMetadataArgs, ModelMetricArgs,
+    CompareMetricArgs, ConfigArgs, DiffModelArgs, DownloadModelArgs, DownloadModelsArgs, ListCards,
+    ListFilesArgs, ListTeamArgs, MetadataArgs, ModelMetadataArgs, ModelMetricArgs, RecentCardArgs,
+    SchemaArgs, SyncModelArgs, ValidateMetadataArgs,
 };
 
-use clap::command;
+#[cfg(feature = "python")]
+use crate::api::commands::LaunchAppArgs;
+
+use crate::api::cards::CardId;
+use crate::api::checksum::ChecksumAlgo;
+use crate::api::output::{OutputFormat, OutputOptions};
+use crate::api::utils::{self, TableStyle};
 use clap::Parser;
 use clap::Subcommand;
+use std::env;
+use std::time::Duration;
+
+/// Formats example invocations for a subcommand's help output
+macro_rules! examples {
+    ($($example:literal),+ $(,)?) => {
+        concat!("Examples:\n", $("  ", $example, "\n"),+)
+    };
+}
 
 #[derive(Parser)]
 #[command(about = "CLI tool for Interacting with an Opsml server")]
-
 pub struct Cli {
     #[command(subcommand)]
     pub command: Option<Commands>,
+
+    /// Env file to load variables from (defaults to .env if present)
+    #[arg(long = "env-file", global = true)]
+    pub env_file: Option<String>,
+
+    /// Config profile to load from ~/.opsml/config.toml (defaults to OPSML_PROFILE)
+    #[arg(long = "profile", global = true)]
+    pub profile: Option<String>,
+
+    /// Style used to render tables
+    #[arg(long = "table-style", global = true, value_enum, default_value_t = TableStyle::Sharp)]
+    pub table_style: TableStyle,
+
+    /// Output format for list and metric commands
+    #[arg(long = "output", global = true, value_enum, default_value_t = OutputFormat::Table)]
+    pub output: OutputFormat,
+
+    /// Write the output of list and metric commands to a file instead of stdout. Colors are
+    /// removed from the file
+    #[arg(long = "output-file", global = true)]
+    pub output_file: Option<String>,
+
+    /// Print JSON output on a single line instead of pretty-printing it
+    #[arg(long = "compact", global = true, default_value = "false")]
+    pub compact: bool,
+
+    /// Draw tables with ascii characters instead of Unicode box characters (also set by
+    /// OPSML_ASCII). Used automatically on terminals that cannot draw box characters
+    #[arg(long = "ascii", global = true, default_value = "false")]
+    pub ascii: bool,
+
+    /// Disable colored output (also set by the NO_COLOR environment variable)
+    #[arg(long = "no-color", global = true, default_value = "false")]
+    pub no_color: bool,
+
+    /// Timeout in seconds for file downloads. Downloads are not timed out by default
+    #[arg(long = "download-timeout", global = true)]
+    pub download_timeout: Option<u64>,
+
+    /// Timeout in seconds for each file of a multi-file download. A stuck file fails on its
+    /// own instead of hanging the download
+    #[arg(long = "file-timeout", global = true)]
+    pub file_timeout: Option<u64>,
+
+    /// Longest wait in seconds before retrying a request, including waits requested by a
+    /// rate-limited server's Retry-After header
+    #[arg(long = "retry-max-delay", global = true)]
+    pub retry_max_delay: Option<u64>,
+
+    /// Retry model metadata requests that return 404, for registries that can briefly miss a
+    /// model right after it is registered
+    #[arg(long = "retry-on-404", global = true, default_value = "false")]
+    pub retry_on_404: bool,
+
+    /// Longest total time to spend retrying failed requests across the whole run, e.g. 60s or
+    /// 5m. Once used up, the next failure is returned instead of retried
+    #[arg(long = "retry-budget", global = true, value_parser = utils::parse_duration)]
+    pub retry_budget: Option<Duration>,
+
+    /// Response schema version to request from the server, sent as
+    /// `Accept: application/json; version=<n>` (default 1)
+    #[arg(long = "api-version", global = true)]
+    pub api_version: Option<u32>,
+
+    /// Most redirects to follow before a request fails (default 5). 0 to follow none
+    #[arg(long = "max-redirects", global = true)]
+    pub max_redirects: Option<usize>,
+
+    /// Client certificate for servers that require mutual TLS, as PEM or PKCS#12. The
+    /// PKCS#12 password is read from OPSML_CLIENT_CERT_PASSWORD
+    #[arg(long = "client-cert", global = true)]
+    pub client_cert: Option<String>,
+
+    /// PKCS#8 PEM private key for a PEM --client-cert
+    #[arg(long = "client-key", global = true, requires = "client_cert")]
+    pub client_key: Option<String>,
+
+    /// Checksum algorithm for downloads when the server's checksum has no algorithm prefix
+    #[arg(long = "checksum-algo", global = true, value_enum)]
+    pub checksum_algo: Option<ChecksumAlgo>,
+
+    /// Hide progress spinners and per-file download lines. Implied by JSON output
+    #[arg(long = "quiet", global = true, default_value = "false")]
+    pub quiet: bool,
+
+    /// Always download files, ignoring OPSML_CACHE_DIR
+    #[arg(long = "no-cache", global = true, default_value = "false")]
+    pub no_cache: bool,
+
+    /// Fail when model metadata contains fields this version does not know about
+    #[arg(long = "strict", global = true, default_value = "false")]
+    pub strict: bool,
+
+    /// Log requests to stderr. Credentials and tokens are redacted
+    #[arg(long = "verbose", global = true, default_value = "false")]
+    pub verbose: bool,
+
+    /// Log each response status and its content-type, content-length and server headers to
+    /// stderr, to diagnose proxy and auth issues without --verbose
+    #[arg(long = "debug-headers", global = true, default_value = "false")]
+    pub debug_headers: bool,
+
+    /// Extra header sent with every request, as 'Key: Value'. Can be repeated
+    #[arg(long = "header", global = true, value_name = "HEADER")]
+    pub header: Vec<String>,
+
+    /// Use https instead of http when OPSML_TRACKING_URI has no scheme
+    #[arg(long = "https", global = true, default_value = "false")]
+    pub https: bool,
+}
+
+impl Cli {
+    /// Output options set by global flags
+    pub fn output_options(&self) -> OutputOptions {
+        OutputOptions {
+            format: self.output,
+            compact: self.compact,
+            raw: false,
+        }
+    }
+
+    /// Expands a positional card identifier into the registry, name, version and uid args
+    ///
+    /// Commands other than list-cards only work with the model registry
+    pub fn resolve_card_id(&mut self) -> Result<(), anyhow::Error> {
+        let (id, registry, name, version, uid) = match &mut self.command {
+            Some(Commands::ListCards(args)) => (
+                &args.id,
+                Some(&mut args.registry),
+                &mut args.name,
+                &mut args.version,
+                &mut args.uid,
+            ),
+            Some(Commands::DownloadModelMetadata(args)) => (
+                &args.id,
+                None,
+                &mut args.name,
+                &mut args.version,
+                &mut args.uid,
+            ),
+            Some(Commands::Metadata(args)) => (
+                &args.id,
+                None,
+                &mut args.name,
+                &mut args.version,
+                &mut args.uid,
+            ),
+            Some(Commands::ListFiles(args)) => (
+                &args.id,
+                None,
+                &mut args.name,
+                &mut args.version,
+                &mut args.uid,
+            ),
+            Some(Commands::DownloadModel(args)) => (
+                &args.id,
+                None,
+                &mut args.name,
+                &mut args.version,
+                &mut args.uid,
+            ),
+            Some(Commands::GetModelMetrics(args)) => (
+                &args.id,
+                None,
+                &mut args.name,
+                &mut args.version,
+                &mut args.uid,
+            ),
+            _ => return Ok(()),
+        };
+
+        let Some(id) = id else {
+            return Ok(());
+        };
+        let card_id = CardId::parse(id)?;
+
+        match (registry, card_id.registry) {
+            // list-cards takes the registry from the identifier unless --registry disagrees
+            (Some(registry), Some(id_registry)) => match registry {
+                Some(registry) if *registry != id_registry => {
+                    return Err(anyhow::Error::msg(format!(
+                        "Identifier registry {} conflicts with --registry {}",
+                        id_registry, registry
+                    )));
+                }
+                _ => *registry = Some(id_registry),
+            },
+            (None, Some(id_registry)) if id_registry != "model" => {
+                return Err(anyhow::Error::msg(format!(
+                    "Only model cards are supported by this command, got registry {}",
+                    id_registry
+                )));
+            }
+            _ => {}
+        }
+
+        *name = card_id.name;
+        *version = card_id.version;
+        *uid = card_id.uid;
+
+        Ok(())
+    }
+
+    /// Exports global flags as environment variables read when building requests
+    ///
+    /// Flags take precedence over existing environment variables and the config file
+    pub fn apply_env(&self) {
+        if self.no_color {
+            env::set_var("NO_COLOR", "1");
+        }
+        if self.ascii {
+            env::set_var("OPSML_ASCII", "1");
+        }
+        if let Some(download_timeout) = self.download_timeout {
+            env::set_var("OPSML_DOWNLOAD_TIMEOUT", download_timeout.to_string());
+        }
+        if let Some(file_timeout) = self.file_timeout {
+            env::set_var("OPSML_FILE_TIMEOUT", file_timeout.to_string());
+        }
+        if let Some(retry_max_delay) = self.retry_max_delay {
+            env::set_var("OPSML_RETRY_MAX_DELAY", retry_max_delay.to_string());
+        }
+        if self.retry_on_404 {
+            env::set_var("OPSML_RETRY_ON_404", "1");
+        }
+        if let Some(retry_budget) = self.retry_budget {
+            env::set_var("OPSML_RETRY_BUDGET", retry_budget.as_secs().to_string());
+        }
+        if let Some(api_version) = self.api_version {
+            env::set_var("OPSML_API_VERSION", api_version.to_string());
+        }
+        if let Some(max_redirects) = self.max_redirects {
+            env::set_var("OPSML_MAX_REDIRECTS", max_redirects.to_string());
+        }
+        if let Some(client_cert) = &self.client_cert {
+            env::set_var("OPSML_CLIENT_CERT", client_cert);
+        }
+        if let Some(client_key) = &self.client_key {
+            env::set_var("OPSML_CLIENT_KEY", client_key);
+        }
+        // keep progress lines out of stdout when it carries JSON
+        if self.quiet || self.output_options().is_json() {
+            env::set_var("OPSML_QUIET", "1");
+        }
+        if self.no_cache {
+            env::set_var("OPSML_NO_CACHE", "1");
+        }
+        if self.strict {
+            env::set_var("OPSML_STRICT", "1");
+        }
+        if self.verbose {
+            env::set_var("OPSML_VERBOSE", "1");
+        }
+        if self.debug_headers {
+            env::set_var("OPSML_DEBUG_HEADERS", "1");
+        }
+        if self.https {
+            env::set_var("OPSML_DEFAULT_SCHEME", "https");
+        }
+        if let Some(checksum_algo) = self.checksum_algo {
+            env::set_var("OPSML_CHECKSUM_ALGO", checksum_algo.as_str());
+        }
+        if let Some(output_file) = &self.output_file {
+            env::set_var("OPSML_OUTPUT_FILE", output_file);
+        }
+        if !self.header.is_empty() {
+            env::set_var("OPSML_HEADERS", self.header.join("\n"));
+        }
+    }
 }
 
 #[derive(Subcommand)]
 pub enum Commands {
     /// Lists cards from a registry
-    ///
-    /// # Example
-    ///
-    /// opsml-cli list-cards --registry data
+    #[command(after_help = examples!(
+        "opsml-cli list-cards --registry data",
+        "opsml-cli list-cards model:model_name:1.0.0",
+        "opsml-cli list-cards --registry model --group-by team",
+    ))]
     ListCards(ListCards),
+    /// Lists the most recently dated cards in a registry, newest first
+    #[command(after_help = examples!("opsml-cli recent --registry model --limit 10"))]
+    Recent(RecentCardArgs),
+    /// Lists teams (repositories) that own cards in a registry
+    #[command(after_help = examples!("opsml-cli list-teams --registry model"))]
+    ListTeams(ListTeamArgs),
     /// Download model metadata from the model registry
-    ///
-    /// # Example
-    ///
-    /// opsml-cli download-model-metadata --name model_name --version 1.0.0
+    #[command(after_help = examples!(
+        "opsml-cli download-model-metadata --name model_name --version 1.0.0",
+        "opsml-cli download-model-metadata --uid uid --format yaml",
+    ))]
     DownloadModelMetadata(ModelMetadataArgs),
+    /// Print model metadata without writing any files
+    #[command(after_help = examples!("opsml-cli metadata --name model_name --version 1.0.0"))]
+    Metadata(MetadataArgs),
+    /// Show the metadata fields that differ between two versions of a model
+    #[command(after_help = examples!(
+        "opsml-cli diff-model --name model_name --version 1.0.0 --other-version 1.1.0",
+    ))]
+    DiffModel(DiffModelArgs),
+    /// Validate a local model metadata file
+    #[command(after_help = examples!(
+        "opsml-cli validate-metadata --path model-metadata.json",
+        "generate-metadata | opsml-cli validate-metadata --stdin",
+    ))]
+    ValidateMetadata(ValidateMetadataArgs),
+    /// List the files of a model with their sizes without downloading them
+    #[command(after_help = examples!(
+        "opsml-cli list-files --name model_name --version 1.0.0",
+        "opsml-cli list-files --name model_name --version 1.0.0 --onnx --preprocessor",
+    ))]
+    ListFiles(ListFilesArgs),
     /// Download a model and its metadata from the model registry
-    ///
-    /// # Example
-    ///
-    /// opsml-cli download-model --name model_name --version 1.0.0
-    /// opsml-cli download-model --name model_name --version 1.0.0 --no-onnx
+    #[command(after_help = examples!(
+        "opsml-cli download-model --name model_name --version 1.0.0",
+        "opsml-cli download-model --name model_name --version 1.0.0 --onnx",
+        "opsml-cli download-model --uid uid --write-dir models --verify-only",
+        "opsml-cli download-model --name model_name --since-version 1.2.0",
+    ))]
     DownloadModel(DownloadModelArgs),
+    /// Download several models by uid concurrently and print a summary
+    #[command(after_help = examples!(
+        "opsml-cli download-models --uids uid1,uid2,uid3 --write-dir models --jobs 2",
+    ))]
+    DownloadModels(DownloadModelsArgs),
+    /// Download all models registered since a date
+    #[command(after_help = examples!(
+        "opsml-cli sync-models --since 2023-01-01 --state-file .sync-state",
+    ))]
+    SyncModels(SyncModelArgs),
     /// Retrieve model metrics
-    ///
-    /// # Example
-    ///
-    /// opsml-cli get-model-metrics --name model_name --version 1.0.0
+    #[command(after_help = examples!(
+        "opsml-cli get-model-metrics --name model_name --version 1.0.0",
+        "opsml-cli get-model-metrics --uid uid --sort value --top 5",
+    ))]
     GetModelMetrics(ModelMetricArgs),
     /// Compare model metrics
-    ///
-    /// # Example
-    ///
-    /// opsml-cli compare-model-metrics
+    #[command(after_help = examples!(
+        "opsml-cli compare-model-metrics --metric-name mae --challenger-uid uid1 --champion-uid uid2",
+        "opsml-cli compare-model-metrics --pairs pairs.csv",
+    ))]
     CompareModelMetrics(CompareMetricArgs),
+    /// View and set values in ~/.opsml/config.toml. Use --profile to target a profile
+    #[command(after_help = examples!(
+        "opsml-cli config set tracking_uri http://localhost:8080",
+        "opsml-cli config get tracking_uri",
+        "opsml-cli config list",
+        "opsml-cli config show",
+    ))]
+    Config(ConfigArgs),
+    /// Print the JSON Schema of a type the cli reads or writes
+    #[command(after_help = examples!("opsml-cli schema metadata > model-metadata.schema.json"))]
+    Schema(SchemaArgs),
+    /// Check the tracking uri, config file, server and auth, printing hints for failed checks
+    #[command(
+        visible_alias = "config-check",
+        after_help = examples!("opsml-cli doctor", "opsml-cli --profile prod doctor")
+    )]
+    Doctor,
+    /// Launch the opsml server using the local python installation
+    #[cfg(feature = "python")]
+    #[command(after_help = examples!(
+        "opsml-cli launch-app --port 8888",
+        "opsml-cli launch-app --port 8888 --login --startup-timeout 30",
+    ))]
+    LaunchApp(LaunchAppArgs),
 
     ///  Show opsml-cli version
-    ///
-    /// # Example
-    ///
-    /// opsml-cli version
+    #[command(after_help = examples!("opsml-cli version"))]
     Version,
 
     ///  Show opsml-cli info
-    ///
-    /// # Example
-    ///
-    /// opsml-cli info
+    #[command(after_help = examples!("opsml-cli info"))]
     Info,
 }
 
@@ -73,3 +414,165 @@ pub const LOGO_TEXT: &str = "
 ██    ██ ██           ██ ██  ██  ██ ██            ██      ██      ██ 
  ██████  ██      ███████ ██      ██ ███████        ██████ ███████ ██ 
 ";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    #[test]
+    fn test_help_examples() {
+        let mut command = Cli::command();
+        let download_model = command.find_subcommand_mut("download-model").unwrap();
+
+        // examples are shown by both -h and --help
+        for help in [
+            download_model.render_help().to_string(),
+            download_model.render_long_help().to_string(),
+        ] {
+            assert!(help.contains(concat!(
+                "Examples:\n",
+                "  opsml-cli download-model --name model_name --version 1.0.0\n"
+            )));
+            assert!(!help.contains("# Example"));
+        }
+    }
+
+    #[cfg(feature = "python")]
+    #[test]
+    fn test_launch_app_args() {
+        let cli =
+            Cli::try_parse_from(["opsml-cli", "launch-app", "--port", "9000", "--login"]).unwrap();
+        let Some(Commands::LaunchApp(args)) = &cli.command else {
+            panic!("expected launch-app");
+        };
+        assert_eq!(args.port, 9000);
+        assert!(args.login);
+
+        let cli = Cli::try_parse_from(["opsml-cli", "launch-app"]).unwrap();
+        let Some(Commands::LaunchApp(args)) = &cli.command else {
+            panic!("expected launch-app");
+        };
+        assert_eq!(args.port, 8888);
+        assert!(!args.login);
+        assert_eq!(args.host, "0.0.0.0");
+        assert_eq!(args.workers, 1);
+
+        let cli = Cli::try_parse_from([
+            "opsml-cli",
+            "launch-app",
+            "--host",
+            "127.0.0.1",
+            "--workers",
+            "4",
+        ])
+        .unwrap();
+        let Some(Commands::LaunchApp(args)) = &cli.command else {
+            panic!("expected launch-app");
+        };
+        assert_eq!(args.host, "127.0.0.1");
+        assert_eq!(args.workers, 4);
+
+        for args in [
+            ["opsml-cli", "launch-app", "--port", "0"],
+            ["opsml-cli", "launch-app", "--port", "65536"],
+            ["opsml-cli", "launch-app", "--workers", "0"],
+        ] {
+            assert!(Cli::try_parse_from(args).is_err());
+        }
+    }
+
+    #[test]
+    fn test_list_cards_count_conflicts_with_watch() {
+        assert!(Cli::try_parse_from(["opsml-cli", "list-cards", "--count"]).is_ok());
+        assert!(Cli::try_parse_from(["opsml-cli", "list-cards", "--watch"]).is_ok());
+        assert!(Cli::try_parse_from(["opsml-cli", "list-cards", "--count", "--watch"]).is_err());
+    }
+
+    #[test]
+    fn test_compare_metrics_requires_challenger() {
+        assert!(Cli::try_parse_from([
+            "opsml-cli",
+            "compare-model-metrics",
+            "--metric-name",
+            "mae",
+            "--challenger-uid",
+            "uid-1"
+        ])
+        .is_ok());
+        assert!(Cli::try_parse_from([
+            "opsml-cli",
+            "compare-model-metrics",
+            "--pairs",
+            "pairs.csv"
+        ])
+        .is_ok());
+
+        for args in [
+            vec!["opsml-cli", "compare-model-metrics", "--metric-name", "mae"],
+            vec![
+                "opsml-cli",
+                "compare-model-metrics",
+                "--challenger-uid",
+                "uid-1",
+            ],
+        ] {
+            assert!(Cli::try_parse_from(args).is_err());
+        }
+    }
+
+    #[test]
+    fn test_resolve_card_id() {
+        let mut cli =
+            Cli::try_parse_from(["opsml-cli", "list-cards", "model:my-card:1.0.0"]).unwrap();
+        cli.resolve_card_id().unwrap();
+        let Some(Commands::ListCards(args)) = &cli.command else {
+            panic!("expected list-cards");
+        };
+        assert_eq!(args.registry.as_deref(), Some("model"));
+        assert_eq!(args.name.as_deref(), Some("my-card"));
+        assert_eq!(args.version.as_deref(), Some("1.0.0"));
+
+        let mut cli =
+            Cli::try_parse_from(["opsml-cli", "download-model", "model::uid-1234"]).unwrap();
+        cli.resolve_card_id().unwrap();
+        let Some(Commands::DownloadModel(args)) = &cli.command else {
+            panic!("expected download-model");
+        };
+        assert_eq!(args.uid.as_deref(), Some("uid-1234"));
+        assert_eq!(args.name, None);
+
+        // identifiers replace, rather than combine with, the individual flags
+        assert!(Cli::try_parse_from([
+            "opsml-cli",
+            "download-model",
+            "my-card:1.0.0",
+            "--name",
+            "x"
+        ])
+        .is_err());
+
+        // without a registry, list-cards falls back to the configured default registry
+        let mut cli = Cli::try_parse_from(["opsml-cli", "list-cards", "my-card:1.0.0"]).unwrap();
+        cli.resolve_card_id().unwrap();
+        let Some(Commands::ListCards(args)) = &cli.command else {
+            panic!("expected list-cards");
+        };
+        assert_eq!(args.registry, None);
+        assert_eq!(args.name.as_deref(), Some("my-card"));
+
+        for args in [
+            vec!["opsml-cli", "download-model", "data:my-card:1.0.0"],
+            vec![
+                "opsml-cli",
+                "list-cards",
+                "model:my-card:1.0.0",
+                "--registry",
+                "data",
+            ],
+        ] {
+            let mut cli = Cli::try_parse_from(args).unwrap();
+            assert!(cli.resolve_card_id().is_err());
+        }
+    }
+}
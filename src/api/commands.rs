@@ -1,13 +1,24 @@
 /// Copyright (c) Shipt, Inc.
 /// This source code is licensed under the MIT license found in the
 /// LICENSE file in the root directory of this source tree.
-use clap::Args;
+use crate::api::cards::{parse_tag, CardGroup, CARD_COLUMNS, DEFAULT_RECENT_LIMIT};
+use crate::api::metrics::MetricSortKey;
+use crate::api::model::{MetadataFormat, DEFAULT_MAX_DOWNLOADS};
+use crate::api::utils;
+use clap::{Args, Subcommand};
+use std::time::Duration;
 
 #[derive(Args)]
 pub struct ListCards {
-    /// Name of the registry (data, model, run, etc)
+    /// Card identifier (registry:name:version, name:version or registry::uid) in place of
+    /// --name/--version/--uid
+    #[arg(value_name = "ID", conflicts_with_all = ["name", "version", "uid"])]
+    pub id: Option<String>,
+
+    /// Name of the registry (data, model, run, etc), or all to list every registry.
+    /// Defaults to default_registry from the config file
     #[arg(long = "registry")]
-    pub registry: String,
+    pub registry: Option<String>,
 
     /// Name given to a card
     #[arg(long = "name")]
@@ -41,13 +52,90 @@ pub struct ListCards {
     #[arg(long = "max_date")]
     pub max_date: Option<String>,
 
+    /// Only list cards dated within this long before now, e.g. 7d, 12h or 30m. The cutoff is
+    /// rounded down to the start of its day
+    #[arg(long = "since", value_parser = utils::parse_duration)]
+    pub since: Option<Duration>,
+
+    /// Only list cards whose name contains this text (case-insensitive)
+    #[arg(long = "name-contains")]
+    pub name_contains: Option<String>,
+
     /// ignore release candidate
     #[arg(long = "ignore_release_candidate", default_value = "false")]
     pub ignore_release_candidates: bool,
+
+    /// List every version of matching cards rather than only the latest
+    #[arg(long = "all-versions", default_value = "false")]
+    pub all_versions: bool,
+
+    /// Print only the number of matching cards
+    #[arg(long = "count", default_value = "false", conflicts_with = "watch")]
+    pub count: bool,
+
+    /// Re-list cards on an interval, highlighting new cards, until interrupted
+    #[arg(long = "watch", default_value = "false")]
+    pub watch: bool,
+
+    /// Seconds between polls in watch mode
+    #[arg(long = "interval", default_value = "10")]
+    pub interval: u64,
+
+    /// Print the server response verbatim instead of rendering it
+    #[arg(long = "raw", default_value = "false")]
+    pub raw: bool,
+
+    /// Exit with an error when no cards match the filters, after printing the empty result
+    #[arg(
+        long = "fail-if-empty",
+        default_value = "false",
+        conflicts_with = "watch"
+    )]
+    pub fail_if_empty: bool,
+
+    /// Sort cards by a column and group the rendered table by it
+    #[arg(long = "group-by", value_enum)]
+    pub group_by: Option<CardGroup>,
+
+    /// Comma-separated table columns to render. Overrides the registry's columns from the
+    /// config file
+    #[arg(long = "columns", value_delimiter = ',', value_parser = CARD_COLUMNS)]
+    pub columns: Option<Vec<String>>,
+
+    /// Print one line per card from a template instead of a table, e.g. '{name} {version} {uid}'.
+    /// Placeholders are name, repository, date, contact, version and uid
+    #[arg(
+        long = "format-template",
+        conflicts_with_all = ["raw", "count", "watch", "group_by", "columns"]
+    )]
+    pub format_template: Option<String>,
+}
+
+#[derive(Args)]
+pub struct RecentCardArgs {
+    /// Name of the registry (data, model, run, etc)
+    #[arg(long = "registry")]
+    pub registry: String,
+
+    /// Number of cards to list
+    #[arg(long = "limit", default_value_t = DEFAULT_RECENT_LIMIT)]
+    pub limit: usize,
+}
+
+#[derive(Args)]
+pub struct ListTeamArgs {
+    /// Name of the registry (data, model, run, etc)
+    #[arg(long = "registry")]
+    pub registry: String,
 }
 
 #[derive(Args)]
 pub struct ModelMetadataArgs {
+    /// Card identifier (registry:name:version, name:version or registry::uid) in place of
+    /// --name/--version/--uid
+    #[arg(value_name = "ID", conflicts_with_all = ["name", "version", "uid"])]
+    pub id: Option<String>,
+
     /// Name given to card
     #[arg(long = "name")]
     pub name: Option<String>,
@@ -71,10 +159,91 @@ pub struct ModelMetadataArgs {
     /// ignore release candidate
     #[arg(long = "ignore_release_candidate", default_value = "false")]
     pub ignore_release_candidates: bool,
+
+    /// Print a single metadata field (e.g. model_uri or data_schema.data_type) instead of writing a file
+    #[arg(long = "print", visible_alias = "field")]
+    pub print: Option<String>,
+
+    /// Format of the metadata file (model-metadata.json or model-metadata.yaml)
+    #[arg(long = "format", value_enum, default_value_t = MetadataFormat::Json)]
+    pub format: MetadataFormat,
+}
+
+#[derive(Args)]
+pub struct MetadataArgs {
+    /// Card identifier (registry:name:version, name:version or registry::uid) in place of
+    /// --name/--version/--uid
+    #[arg(value_name = "ID", conflicts_with_all = ["name", "version", "uid"])]
+    pub id: Option<String>,
+
+    /// Name given to card
+    #[arg(long = "name")]
+    pub name: Option<String>,
+
+    /// Card version
+    #[arg(long = "version")]
+    pub version: Option<String>,
+
+    /// Card repository
+    #[arg(long = "repository")]
+    pub repository: Option<String>,
+
+    /// Card uid
+    #[arg(long = "uid")]
+    pub uid: Option<String>,
+
+    /// ignore release candidate
+    #[arg(long = "ignore_release_candidate", default_value = "false")]
+    pub ignore_release_candidates: bool,
+}
+
+#[derive(Args)]
+pub struct ListFilesArgs {
+    /// Card identifier (registry:name:version, name:version or registry::uid) in place of
+    /// --name/--version/--uid
+    #[arg(value_name = "ID", conflicts_with_all = ["name", "version", "uid"])]
+    pub id: Option<String>,
+
+    /// Name given to card
+    #[arg(long = "name")]
+    pub name: Option<String>,
+
+    /// Card version
+    #[arg(long = "version")]
+    pub version: Option<String>,
+
+    /// Card repository
+    #[arg(long = "repository")]
+    pub repository: Option<String>,
+
+    /// Card uid
+    #[arg(long = "uid")]
+    pub uid: Option<String>,
+
+    /// Boolean indicating whether to list the onnx or trained model files
+    #[arg(long = "onnx", default_value = "false")]
+    pub onnx: bool,
+
+    /// Boolean indicating whether to use the quantized version of the model (huggingface only)
+    #[arg(long = "quantize", default_value = "false")]
+    pub quantize: bool,
+
+    /// Boolean indicating whether to also list any preprocessor files
+    #[arg(long = "preprocessor", default_value = "false")]
+    pub preprocessor: bool,
+
+    /// ignore release candidate
+    #[arg(long = "ignore_release_candidate", default_value = "false")]
+    pub ignore_release_candidates: bool,
 }
 
 #[derive(Args)]
 pub struct DownloadModelArgs {
+    /// Card identifier (registry:name:version, name:version or registry::uid) in place of
+    /// --name/--version/--uid
+    #[arg(value_name = "ID", conflicts_with_all = ["name", "version", "uid"])]
+    pub id: Option<String>,
+
     /// Name given to card
     #[arg(long = "name")]
     pub name: Option<String>,
@@ -110,14 +279,235 @@ pub struct DownloadModelArgs {
     /// ignore release candidate
     #[arg(long = "ignore_release_candidate", default_value = "false")]
     pub ignore_release_candidates: bool,
+
+    /// Boolean indicating whether to write a manifest.json of downloaded files
+    #[arg(long = "manifest", default_value = "false")]
+    pub manifest: bool,
+
+    /// Path of a tar.gz archive to write the model into instead of write_dir
+    #[arg(long = "archive")]
+    pub archive: Option<String>,
+
+    /// Name of a single model file to download (used with --to-stdout)
+    #[arg(long = "file", requires = "to_stdout")]
+    pub file: Option<String>,
+
+    /// Write the file named by --file to stdout instead of write_dir
+    #[arg(long = "to-stdout", default_value = "false", requires = "file")]
+    pub to_stdout: bool,
+
+    /// Only download files matching these glob patterns (e.g. '*.onnx')
+    #[arg(long = "include", value_delimiter = ',')]
+    pub include: Vec<String>,
+
+    /// Skip files matching these glob patterns (e.g. '*.bin'). Takes precedence over --include
+    #[arg(long = "exclude", value_delimiter = ',')]
+    pub exclude: Vec<String>,
+
+    /// Only download files with these names (e.g. config.json). Can be repeated
+    #[arg(long = "only")]
+    pub only: Vec<String>,
+
+    /// Check the files already in write_dir against the server (size and checksum) instead
+    /// of downloading. Exits non-zero if any file is missing or different
+    #[arg(
+        long = "verify-only",
+        default_value = "false",
+        conflicts_with_all = ["archive", "to_stdout", "manifest"]
+    )]
+    pub verify_only: bool,
+
+    /// Print the resolved download urls of the model (and preprocessor with --preprocessor)
+    /// without downloading anything
+    #[arg(
+        long = "resolve-only",
+        default_value = "false",
+        conflicts_with_all = ["archive", "to_stdout", "verify_only", "since_version", "tag", "manifest", "post_hook"]
+    )]
+    pub resolve_only: bool,
+
+    /// Download every version of the model newer than this semver version, each into a
+    /// subdirectory of write_dir named after the version
+    #[arg(
+        long = "since-version",
+        requires = "name",
+        conflicts_with_all = ["version", "uid", "archive", "to_stdout", "verify_only"]
+    )]
+    pub since_version: Option<String>,
+
+    /// Check that the write directory has enough free space before downloading
+    #[arg(
+        long = "check-space",
+        default_value = "true",
+        default_missing_value = "true",
+        num_args = 0..=1,
+        action = clap::ArgAction::Set
+    )]
+    pub check_space: bool,
+
+    /// Print the size, duration and throughput of each downloaded file
+    #[arg(long = "report", default_value = "false")]
+    pub report: bool,
+
+    /// Number of files to download at once. Each batch finishes before the next starts.
+    /// Files download in parallel by default; pass 1 to download one file at a time
+    #[arg(
+        long = "batch-size",
+        visible_alias = "page-size",
+        default_value = "50",
+        value_parser = clap::value_parser!(u16).range(1..)
+    )]
+    pub batch_size: u16,
+
+    /// Pick the number of files to download at once from the file sizes the server reports
+    /// instead of --batch-size: more at once for many small files, fewer for large ones
+    #[arg(
+        long = "auto-concurrency",
+        default_value = "false",
+        conflicts_with = "batch_size"
+    )]
+    pub auto_concurrency: bool,
+
+    /// Do not write the model metadata file. The metadata is still fetched to resolve the
+    /// files to download
+    #[arg(long = "no-metadata-file", default_value = "false", conflicts_with_all = ["to_stdout", "verify_only", "resolve_only"])]
+    pub no_metadata_file: bool,
+
+    /// Download the model cards with this tag (key=value). Can be repeated. When several cards
+    /// match, each is written to a subdirectory of write_dir named after its uid
+    #[arg(
+        long = "tag",
+        value_parser = parse_tag,
+        conflicts_with_all = ["id", "version", "uid", "since_version", "to_stdout", "verify_only", "archive"]
+    )]
+    pub tag: Vec<(String, String)>,
+
+    /// Only download the first card matching --tag instead of every match
+    #[arg(long = "first", default_value = "false", requires = "tag")]
+    pub first: bool,
+
+    /// Abort before downloading when --since-version or --tag match more models than this.
+    /// 0 for no limit
+    #[arg(long = "max-downloads", default_value_t = DEFAULT_MAX_DOWNLOADS)]
+    pub max_downloads: usize,
+
+    /// Write a sha256sum-compatible SHA256SUMS file listing every downloaded file to write_dir
+    #[arg(
+        long = "write-checksums",
+        visible_alias = "checksum-output",
+        default_value = "false",
+        conflicts_with_all = ["archive", "to_stdout", "verify_only"]
+    )]
+    pub write_checksums: bool,
+
+    /// Resume an interrupted download into write_dir. Files whose size matches the server's
+    /// are kept and partially written files are resumed from where they stopped
+    #[arg(
+        long = "resume",
+        default_value = "false",
+        conflicts_with_all = ["archive", "to_stdout", "verify_only", "resolve_only"]
+    )]
+    pub resume: bool,
+
+    /// Shell command to run after a successful download, e.g. 'python convert.py {dir}'.
+    /// {dir} is replaced with the quoted write directory, which is also set in OPSML_WRITE_DIR.
+    /// Not run when the download fails
+    #[arg(
+        long = "post-hook",
+        conflicts_with_all = ["archive", "to_stdout", "verify_only"]
+    )]
+    pub post_hook: Option<String>,
+}
+
+#[derive(Args)]
+pub struct DownloadModelsArgs {
+    /// Uids of the models to download
+    #[arg(long = "uids", required = true, value_delimiter = ',')]
+    pub uids: Vec<String>,
+
+    /// Directory to write models to. Each model is written to a subdirectory named after its uid
+    #[arg(long = "write-dir", default_value = "models")]
+    pub write_dir: String,
+
+    /// Boolean indicating whether to download onnx or trained models
+    #[arg(long = "onnx", default_value = "false")]
+    pub onnx: bool,
+
+    /// Boolean indicating whether to use the quantized version of the models (huggingface only)
+    #[arg(long = "quantize", default_value = "false")]
+    pub quantize: bool,
+
+    /// Boolean indicating whether to download any preprocessors with the models
+    #[arg(long = "preprocessor", default_value = "false")]
+    pub preprocessor: bool,
+
+    /// Number of models to download at once
+    #[arg(
+        long = "jobs",
+        default_value = "4",
+        value_parser = clap::value_parser!(u16).range(1..)
+    )]
+    pub jobs: u16,
+}
+
+#[derive(Args)]
+pub struct SyncModelArgs {
+    /// Name of the registry to sync from
+    #[arg(long = "registry", default_value = "model")]
+    pub registry: String,
+
+    /// Initial date to sync from when no previous sync is recorded in the state file
+    #[arg(long = "since")]
+    pub since: Option<String>,
+
+    /// Write directory
+    #[arg(long = "write-dir", default_value = "models")]
+    pub write_dir: String,
+
+    /// File used to record the date of the last successful sync
+    #[arg(long = "state-file")]
+    pub state_file: Option<String>,
+
+    /// Boolean indicating whether to download onnx or trained model
+    #[arg(long = "onnx", default_value = "false")]
+    pub onnx: bool,
+
+    /// Boolean indicating whether to download any preprocessors with the model
+    #[arg(long = "preprocessor", default_value = "false")]
+    pub preprocessor: bool,
+
+    /// Abort before downloading when more models than this were registered since the last
+    /// sync. 0 for no limit
+    #[arg(long = "max-downloads", default_value_t = DEFAULT_MAX_DOWNLOADS)]
+    pub max_downloads: usize,
+}
+
+#[derive(Args)]
+pub struct ValidateMetadataArgs {
+    /// Path to a local metadata json file
+    #[arg(long = "path", required_unless_present = "stdin")]
+    pub path: Option<String>,
+
+    /// Read metadata json from stdin instead of --path
+    #[arg(long = "stdin", default_value = "false", conflicts_with = "path")]
+    pub stdin: bool,
 }
 
 #[derive(Args)]
 pub struct ModelMetricArgs {
+    /// Card identifier (registry:name:version, name:version or registry::uid) in place of
+    /// --name/--version/--uid
+    #[arg(value_name = "ID", conflicts_with_all = ["name", "version", "uid"])]
+    pub id: Option<String>,
+
     /// Name given to card
     #[arg(long = "name")]
     pub name: Option<String>,
 
+    /// Card repository (team). Disambiguates models with the same name
+    #[arg(long = "repository", visible_alias = "team")]
+    pub repository: Option<String>,
+
     /// Card version
     #[arg(long = "version")]
     pub version: Option<String>,
@@ -125,6 +515,53 @@ pub struct ModelMetricArgs {
     /// Card uid
     #[arg(long = "uid")]
     pub uid: Option<String>,
+
+    /// Print the server response verbatim instead of rendering it
+    #[arg(long = "raw", default_value = "false")]
+    pub raw: bool,
+
+    /// Sort metric rows by name or value (values sort largest first)
+    #[arg(long = "sort", value_enum)]
+    pub sort: Option<MetricSortKey>,
+
+    /// Sort values in ascending order (smallest value first). Names always sort alphabetically
+    #[arg(long = "ascending", default_value = "false", requires = "sort")]
+    pub ascending: bool,
+
+    /// Only show the first N metric rows
+    #[arg(long = "top", visible_alias = "top-n")]
+    pub top: Option<usize>,
+
+    /// Show one row per metric with a column per step
+    #[arg(
+        long = "pivot",
+        visible_alias = "compact-metrics",
+        default_value = "false"
+    )]
+    pub pivot: bool,
+}
+
+#[derive(Args)]
+pub struct DiffModelArgs {
+    /// Name given to card
+    #[arg(long = "name", required = true)]
+    pub name: String,
+
+    /// Card version to compare from
+    #[arg(long = "version", required = true)]
+    pub version: String,
+
+    /// Card version to compare against
+    #[arg(long = "other-version", required = true)]
+    pub other_version: String,
+
+    /// Card repository
+    #[arg(long = "repository")]
+    pub repository: Option<String>,
+
+    /// ignore release candidate
+    #[arg(long = "ignore_release_candidate", default_value = "false")]
+    pub ignore_release_candidates: bool,
 }
 
 #[derive(Args)]
@@ -133,7 +570,8 @@ pub struct CompareMetricArgs {
     #[arg(
         long = "metric-name",
         use_value_delimiter = true,
-        value_delimiter = ','
+        value_delimiter = ',',
+        required_unless_present = "pairs"
     )]
     pub metric_name: Vec<String>,
 
@@ -146,9 +584,14 @@ pub struct CompareMetricArgs {
     )]
     pub lower_is_better: Vec<bool>,
 
-    /// Id of new model challenger
-    #[arg(long = "challenger-uid")]
-    pub challenger_uid: String,
+    /// Ids of new model challengers. Each challenger is compared against the champions
+    #[arg(
+        long = "challenger-uid",
+        use_value_delimiter = true,
+        value_delimiter = ',',
+        required_unless_present = "pairs"
+    )]
+    pub challenger_uid: Vec<String>,
 
     /// Id of new model challenger
     #[arg(
@@ -158,6 +601,58 @@ pub struct CompareMetricArgs {
         default_value = "true"
     )]
     pub champion_uid: Vec<String>,
+
+    /// CSV file of challenger_uid,champion_uid,metric_name,lower_is_better rows.
+    /// Each row is compared separately and the results are combined
+    #[arg(
+        long = "pairs",
+        conflicts_with_all = ["metric_name", "challenger_uid", "champion_uid"]
+    )]
+    pub pairs: Option<String>,
+
+    /// Print the server response verbatim instead of rendering it
+    #[arg(long = "raw", default_value = "false")]
+    pub raw: bool,
+}
+
+#[derive(Args)]
+pub struct ConfigArgs {
+    #[command(subcommand)]
+    pub action: ConfigAction,
+}
+
+#[derive(Subcommand)]
+pub enum ConfigAction {
+    /// Print a config value (tracking_uri or max_retries)
+    Get {
+        /// Config key
+        key: String,
+    },
+    /// Set a config value, creating the config file if missing
+    Set {
+        /// Config key
+        key: String,
+
+        /// Config value
+        value: String,
+    },
+    /// Print all config values that are set
+    List,
+    /// Print the settings in effect after merging flags, environment and config file.
+    /// Credentials are masked
+    Show,
+}
+
+#[derive(Args)]
+pub struct SchemaArgs {
+    #[command(subcommand)]
+    pub kind: SchemaKind,
+}
+
+#[derive(Subcommand)]
+pub enum SchemaKind {
+    /// Model metadata, as returned by the server and written by download-model
+    Metadata,
 }
 
 #[derive(Args)]
@@ -167,6 +662,26 @@ pub struct LaunchAppArgs {
     pub login: bool,
 
     /// Default port to use with the opsml server
-    #[arg(long = "port", default_value = "8888")]
+    #[arg(
+        long = "port",
+        default_value = "8888",
+        value_parser = clap::value_parser!(i32).range(1..=65535)
+    )]
     pub port: i32,
+
+    /// Host the opsml server binds
+    #[arg(long = "host", default_value = "0.0.0.0")]
+    pub host: String,
+
+    /// Number of uvicorn workers
+    #[arg(
+        long = "workers",
+        default_value = "1",
+        value_parser = clap::value_parser!(u16).range(1..)
+    )]
+    pub workers: u16,
+
+    /// Seconds the server has to start listening on its port before launch fails
+    #[arg(long = "startup-timeout")]
+    pub startup_timeout: Option<u64>,
 }
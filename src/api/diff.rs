@@ -0,0 +1,304 @@
+/// Copyright (c) Shipt, Inc.
+/// This source code is licensed under the MIT license found in the
+/// LICENSE file in the root directory of this source tree.
+use crate::api::model::{self, FileFilter, ModelDownloader};
+use crate::api::output;
+use crate::api::types;
+use crate::api::utils;
+use anyhow::{Context, Result};
+use owo_colors::OwoColorize;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+use tabled::{builder::Builder, settings::Alignment};
+
+/// Shown for a field that only one of the models has
+const MISSING: &str = "-";
+
+/// Metadata field whose value differs between two models
+#[derive(Debug, PartialEq, Serialize)]
+pub struct FieldDiff {
+    pub field: String,
+    pub left: String,
+    pub right: String,
+}
+
+/// Flattens a JSON value into dotted field paths and their values
+///
+/// Objects are expanded field by field. Any other value, including arrays, is kept whole
+///
+/// # Arguments
+///
+/// * `prefix` - Path of `value`
+/// * `value` - Value to flatten
+/// * `fields` - Map to add field paths and values to
+///
+fn flatten_value(prefix: &str, value: &Value, fields: &mut BTreeMap<String, String>) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map.iter() {
+                let path = match prefix {
+                    "" => key.clone(),
+                    _ => format!("{}.{}", prefix, key),
+                };
+                flatten_value(&path, value, fields);
+            }
+        }
+        Value::String(string) => {
+            fields.insert(prefix.to_string(), string.clone());
+        }
+        _ => {
+            fields.insert(prefix.to_string(), value.to_string());
+        }
+    }
+}
+
+/// Finds the metadata fields that differ between two models
+///
+/// Nested fields such as the data schema are compared field by field
+///
+/// # Arguments
+///
+/// * `left` - Metadata of the first model
+/// * `right` - Metadata of the model to compare against
+///
+/// # Returns
+/// * `Result<Vec<FieldDiff>, String>` - Differing fields, sorted by field path
+///
+pub fn diff_metadata(
+    left: &types::ModelMetadata,
+    right: &types::ModelMetadata,
+) -> Result<Vec<FieldDiff>, anyhow::Error> {
+    let mut left_fields = BTreeMap::new();
+    let mut right_fields = BTreeMap::new();
+    flatten_value(
+        "",
+        &serde_json::to_value(left).with_context(|| "Failed to serialize model metadata")?,
+        &mut left_fields,
+    );
+    flatten_value(
+        "",
+        &serde_json::to_value(right).with_context(|| "Failed to serialize model metadata")?,
+        &mut right_fields,
+    );
+
+    let fields: BTreeSet<&String> = left_fields.keys().chain(right_fields.keys()).collect();
+
+    Ok(fields
+        .into_iter()
+        .filter(|field| left_fields.get(*field) != right_fields.get(*field))
+        .map(|field| FieldDiff {
+            field: field.clone(),
+            left: left_fields
+                .get(field)
+                .cloned()
+                .unwrap_or(MISSING.to_string()),
+            right: right_fields
+                .get(field)
+                .cloned()
+                .unwrap_or(MISSING.to_string()),
+        })
+        .collect())
+}
+
+/// Builds a table of differing fields, with old values in red and new values in green
+///
+/// # Arguments
+///
+/// * `diffs` - Differing fields
+/// * `left_label` - Column header for the first model
+/// * `right_label` - Column header for the model compared against
+/// * `table_style` - Style used to render the table
+///
+/// # Returns
+///  String - Table with one row per differing field
+///
+fn build_diff_table(
+    diffs: &[FieldDiff],
+    left_label: &str,
+    right_label: &str,
+    table_style: utils::TableStyle,
+) -> String {
+    let mut builder = Builder::default();
+    builder.set_header(["field", left_label, right_label]);
+
+    for diff in diffs.iter() {
+        builder.push_record([
+            diff.field.clone(),
+            diff.left.red().to_string(),
+            diff.right.green().to_string(),
+        ]);
+    }
+
+    let mut table = builder.build();
+    table.with(Alignment::left());
+
+    table_style.render(&mut table)
+}
+
+/// Requests the metadata of a model version
+///
+/// # Arguments
+///
+/// * `name` - Name of model
+/// * `version` - Version of model
+/// * `repository` - repository associated with model
+/// * `ignore_release_candidates` - Flag to ignore release candidates
+///
+async fn request_version_metadata(
+    name: &str,
+    version: &str,
+    repository: Option<&str>,
+    ignore_release_candidates: &bool,
+) -> Result<types::ModelMetadata, anyhow::Error> {
+    let model_downloader = ModelDownloader {
+        name: Some(name),
+        version: Some(version),
+        repository,
+        uid: None,
+        write_dir: "",
+        ignore_release_candidates,
+        onnx: &false,
+        quantize: &false,
+        preprocessor: &false,
+        manifest: &false,
+        archive: None,
+        file_filter: FileFilter::default(),
+        check_space: &false,
+        report: None,
+        batch_size: model::DEFAULT_BATCH_SIZE,
+        metadata_format: model::MetadataFormat::Json,
+        write_checksums: false,
+        resume: false,
+        auto_concurrency: false,
+        write_metadata: true,
+    };
+
+    model_downloader
+        .request_metadata()
+        .await
+        .with_context(|| format!("Failed to get metadata for {} version {}", name, version))
+}
+
+/// Prints the metadata fields that differ between two versions of a model
+///
+/// # Arguments
+///
+/// * `name` - Name of model
+/// * `version` - Version to compare from
+/// * `other_version` - Version to compare against
+/// * `repository` - repository associated with model
+/// * `ignore_release_candidates` - Flag to ignore release candidates
+/// * `table_style` - Style used to render the table
+/// * `output` - Output format options
+///
+#[tokio::main]
+pub async fn diff_model(
+    name: &str,
+    version: &str,
+    other_version: &str,
+    repository: Option<&str>,
+    ignore_release_candidates: &bool,
+    table_style: utils::TableStyle,
+    output: output::OutputOptions,
+) -> Result<(), anyhow::Error> {
+    let (left, right) = utils::with_shared_client(async {
+        Ok(tokio::try_join!(
+            request_version_metadata(name, version, repository, ignore_release_candidates),
+            request_version_metadata(name, other_version, repository, ignore_release_candidates),
+        )?)
+    })
+    .await?;
+
+    let diffs = diff_metadata(&left, &right)?;
+
+    if output.is_json() {
+        return output::print(output.render_json_list(&diffs)?);
+    }
+
+    if diffs.is_empty() {
+        return output::print(format!(
+            "No metadata differences between {} {} and {}",
+            name, version, other_version
+        ));
+    }
+
+    output::print(format!(
+        "\n{} metadata fields differ between {} {} and {}",
+        diffs.len().to_string().bold().green(),
+        name.bold(),
+        version,
+        other_version
+    ))?;
+    output::print(build_diff_table(
+        &diffs,
+        version,
+        other_version,
+        table_style,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_diff_metadata() {
+        let metadata = fs::read_to_string("./src/api/test_utils/metadata.json").unwrap();
+        let left: types::ModelMetadata = serde_json::from_str(&metadata).unwrap();
+
+        let mut value: Value = serde_json::from_str(&metadata).unwrap();
+        value["model_version"] = Value::from("1.2.0");
+        value["model_type"] = Value::from("Ridge");
+        value["onnx_uri"] = Value::Null;
+        value["data_schema"]["input_features"]["inputs"]["shape"] = serde_json::json!([1, 12]);
+        value["data_schema"]["input_features"]["extra"] =
+            serde_json::json!({ "feature_type": "int64", "shape": [1] });
+        let right: types::ModelMetadata = serde_json::from_value(value).unwrap();
+
+        // identical metadata has no differences
+        assert!(diff_metadata(&left, &left).unwrap().is_empty());
+
+        let diffs = diff_metadata(&left, &right).unwrap();
+        assert_eq!(
+            diffs,
+            vec![
+                FieldDiff {
+                    field: "data_schema.input_features.extra.feature_type".to_string(),
+                    left: "-".to_string(),
+                    right: "int64".to_string(),
+                },
+                FieldDiff {
+                    field: "data_schema.input_features.extra.shape".to_string(),
+                    left: "-".to_string(),
+                    right: "[1]".to_string(),
+                },
+                FieldDiff {
+                    field: "data_schema.input_features.inputs.shape".to_string(),
+                    left: "[1,11]".to_string(),
+                    right: "[1,12]".to_string(),
+                },
+                FieldDiff {
+                    field: "model_type".to_string(),
+                    left: "LinearRegression".to_string(),
+                    right: "Ridge".to_string(),
+                },
+                FieldDiff {
+                    field: "model_version".to_string(),
+                    left: "1.1.0".to_string(),
+                    right: "1.2.0".to_string(),
+                },
+                FieldDiff {
+                    field: "onnx_uri".to_string(),
+                    left: "opsml-root:/".to_string(),
+                    right: "null".to_string(),
+                },
+            ]
+        );
+
+        let table = build_diff_table(&diffs, "1.1.0", "1.2.0", utils::TableStyle::Markdown);
+        assert!(table.contains("| field "));
+        assert!(table.contains("model_type"));
+    }
+}
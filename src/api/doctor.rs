@@ -0,0 +1,251 @@
+/// Copyright (c) Shipt, Inc.
+/// This source code is licensed under the MIT license found in the
+/// LICENSE file in the root directory of this source tree.
+use crate::api::config;
+use crate::api::utils;
+use owo_colors::OwoColorize;
+use reqwest::{Method, StatusCode, Url};
+use std::env;
+
+/// Result of a single environment check
+#[derive(Debug, PartialEq)]
+pub struct Check {
+    pub name: &'static str,
+    pub passed: bool,
+    pub detail: String,
+    pub hint: Option<&'static str>,
+}
+
+impl Check {
+    fn pass(name: &'static str, detail: String) -> Check {
+        Check {
+            name,
+            passed: true,
+            detail,
+            hint: None,
+        }
+    }
+
+    fn fail(name: &'static str, detail: String, hint: &'static str) -> Check {
+        Check {
+            name,
+            passed: false,
+            detail,
+            hint: Some(hint),
+        }
+    }
+
+    /// Formats the check as a checklist line, followed by its hint when it failed
+    fn render(&self) -> String {
+        let status = if self.passed {
+            "PASS".green().to_string()
+        } else {
+            "FAIL".red().to_string()
+        };
+        let line = format!("[{}] {}: {}", status, self.name, self.detail);
+
+        match self.hint {
+            Some(hint) => format!("{}\n       {}", line, hint),
+            None => line,
+        }
+    }
+}
+
+/// Checks that the config file can be loaded and applied
+///
+/// # Arguments
+///
+/// * `profile` - Optional profile name
+///
+fn check_config(profile: Option<&str>) -> Check {
+    let path = config::config_path().map_or("none".to_string(), |path| path.display().to_string());
+
+    match config::apply_config(profile) {
+        Ok(()) => Check::pass("config", path),
+        Err(e) => Check::fail(
+            "config",
+            format!("{:#}", e),
+            "Fix the config file, or run `opsml-cli config list` to check its values",
+        ),
+    }
+}
+
+/// Checks that the tracking uri is set and is a valid url
+///
+/// # Returns
+/// * `(Check, Option<Url>)` - Check result, and the parsed uri when valid
+///
+fn check_tracking_uri() -> (Check, Option<Url>) {
+    let Ok(uri) = env::var("OPSML_TRACKING_URI") else {
+        return (
+            Check::fail(
+                "tracking uri",
+                "not set".to_string(),
+                "Set OPSML_TRACKING_URI or OPSML_TRACKING_URI_FILE, or run `opsml-cli config set tracking_uri <url>`",
+            ),
+            None,
+        );
+    };
+
+    let uri = utils::normalize_uri(&uri, &utils::default_scheme());
+    match Url::parse(&uri) {
+        Ok(url) => (
+            Check::pass("tracking uri", utils::display_uri(&url, &uri)),
+            Some(url),
+        ),
+        Err(e) => (
+            Check::fail(
+                "tracking uri",
+                format!("{} is not a valid url ({})", uri, e),
+                "Use a full url such as http://localhost:8888",
+            ),
+            None,
+        ),
+    }
+}
+
+/// Pings the server's healthcheck, checking it is reachable and accepts the configured auth
+///
+/// # Arguments
+///
+/// * `url` - Tracking uri
+///
+/// # Returns
+/// * `Vec<Check>` - Reachability check, and an auth check when the server is reachable
+///
+async fn check_server(url: &Url) -> Vec<Check> {
+    let healthcheck = utils::join_url(url.as_str(), utils::OpsmlPaths::Healthcheck.route());
+    let auth = config::auth_method(url);
+
+    let response = match utils::api_request(Method::GET, &healthcheck) {
+        Ok(request) => request.send().await.map_err(anyhow::Error::from),
+        Err(e) => Err(e),
+    };
+    let status = match response {
+        Ok(response) => response.status(),
+        Err(e) => {
+            return vec![Check::fail(
+                "server",
+                format!("{} is unreachable ({:#})", utils::redact_url(url), e),
+                "Check the server is running and the tracking uri host and port are correct",
+            )]
+        }
+    };
+
+    let mut checks = vec![Check::pass("server", format!("reachable ({})", status))];
+
+    if auth == "none" {
+        checks.push(Check::pass("auth", "not configured".to_string()));
+    } else if status == StatusCode::UNAUTHORIZED || status == StatusCode::FORBIDDEN {
+        checks.push(Check::fail(
+            "auth",
+            format!("{} rejected ({})", auth, status),
+            "Check the credentials or token in the tracking uri",
+        ));
+    } else {
+        checks.push(Check::pass("auth", auth.to_string()));
+    }
+
+    checks
+}
+
+/// Runs every environment check
+///
+/// Later checks are skipped when the tracking uri is missing or invalid
+///
+/// # Arguments
+///
+/// * `profile` - Optional profile name
+///
+pub async fn run_checks(profile: Option<&str>) -> Vec<Check> {
+    let mut checks = vec![check_config(profile)];
+
+    let (uri_check, url) = check_tracking_uri();
+    checks.push(uri_check);
+
+    if let Some(url) = url {
+        checks.extend(check_server(&url).await);
+    }
+
+    checks
+}
+
+/// Checks the environment and prints a checklist with hints for failed checks
+///
+/// # Arguments
+///
+/// * `profile` - Optional profile name
+///
+#[tokio::main]
+pub async fn run_doctor(profile: Option<&str>) -> Result<(), anyhow::Error> {
+    let checks = run_checks(profile).await;
+    for check in checks.iter() {
+        println!("{}", check.render());
+    }
+
+    let failed = checks.iter().filter(|check| !check.passed).count();
+    if failed > 0 {
+        return Err(anyhow::Error::msg(format!(
+            "{} of {} checks failed",
+            failed,
+            checks.len()
+        )));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::test_utils::TestEnv;
+
+    #[tokio::test]
+    async fn test_run_checks_all_pass() {
+        let _env = TestEnv::lock();
+        let mut server = mockito::Server::new();
+        let url = server.url();
+        env::set_var("OPSML_TRACKING_URI", &url);
+        env::set_var("OPSML_CONFIG_PATH", "./src/api/test_utils/missing.toml");
+
+        let mock_healthcheck = server
+            .mock("GET", "/opsml/healthcheck")
+            .with_status(200)
+            .create();
+
+        let checks = run_checks(None).await;
+        env::remove_var("OPSML_CONFIG_PATH");
+
+        mock_healthcheck.assert();
+        assert!(checks.iter().all(|check| check.passed), "{:?}", checks);
+        assert_eq!(
+            checks.iter().map(|check| check.name).collect::<Vec<_>>(),
+            vec!["config", "tracking uri", "server", "auth"]
+        );
+        assert_eq!(checks[1].detail, url);
+    }
+
+    #[tokio::test]
+    async fn test_run_checks_missing_uri() {
+        let _env = TestEnv::lock();
+        env::remove_var("OPSML_TRACKING_URI");
+        env::set_var("OPSML_CONFIG_PATH", "./src/api/test_utils/missing.toml");
+
+        let checks = run_checks(None).await;
+
+        // the server is not checked without a tracking uri
+        assert_eq!(checks.len(), 2);
+        assert!(checks[0].passed);
+        assert_eq!(
+            checks[1],
+            Check::fail(
+                "tracking uri",
+                "not set".to_string(),
+                "Set OPSML_TRACKING_URI or OPSML_TRACKING_URI_FILE, or run `opsml-cli config set tracking_uri <url>`",
+            )
+        );
+        assert!(checks[1]
+            .render()
+            .contains("opsml-cli config set tracking_uri"));
+    }
+}
@@ -0,0 +1,58 @@
+// Copyright (c) Shipt, Inc.
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+/// Units used by `format_bytes`, in steps of 1000
+const BYTE_UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+
+/// Formats a byte count for display, e.g. `12.3 MB` or `1.4 GB`
+///
+/// Units are decimal (1 KB = 1000 bytes), matching the MBps column of the download report.
+/// Counts under 1 KB are shown exactly, and counts past 1000 TB stay in TB
+///
+/// # Arguments
+///
+/// * `bytes` - Number of bytes
+///
+/// # Returns
+///  String - Size with one decimal place and a unit
+///
+pub fn format_bytes(bytes: u64) -> String {
+    if bytes < 1000 {
+        return format!("{} B", bytes);
+    }
+
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    // round before comparing, so 999_950 bytes shows as 1.0 MB rather than 1000.0 KB
+    while (size * 10.0).round() / 10.0 >= 1000.0 && unit < BYTE_UNITS.len() - 1 {
+        size /= 1000.0;
+        unit += 1;
+    }
+
+    format!("{:.1} {}", size, BYTE_UNITS[unit])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_bytes() {
+        for (bytes, formatted) in [
+            (0, "0 B"),
+            (999, "999 B"),
+            (1_000, "1.0 KB"),
+            (1_536, "1.5 KB"),
+            (999_949, "999.9 KB"),
+            (999_950, "1.0 MB"),
+            (12_345_678, "12.3 MB"),
+            (1_400_000_000, "1.4 GB"),
+            (2_500_000_000_000, "2.5 TB"),
+            (7_000_000_000_000_000, "7000.0 TB"),
+            (u64::MAX, "18446744.1 TB"),
+        ] {
+            assert_eq!(format_bytes(bytes), formatted, "{}", bytes);
+        }
+    }
+}
@@ -0,0 +1,253 @@
+/// Copyright (c) Shipt, Inc.
+/// This source code is licensed under the MIT license found in the
+/// LICENSE file in the root directory of this source tree.
+use anyhow::{Context, Result};
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr, TcpStream, ToSocketAddrs};
+use std::sync::mpsc::{self, TryRecvError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+const APP_SCRIPT: &str = r#"
+from opsml.app.main import run_app
+run_app(port=args.port, login=args.login, host=args.host, workers=args.workers)
+"#;
+const OPSML_MODULE: &str = "opsml";
+const STARTUP_POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+#[pyclass]
+struct AppArgs {
+    #[pyo3(get)]
+    port: i32,
+    #[pyo3(get)]
+    login: bool,
+    #[pyo3(get)]
+    host: String,
+    #[pyo3(get)]
+    workers: u16,
+}
+
+/// Checks that a port is within 1-65535
+///
+/// # Arguments
+///
+/// * `port` - Port to check
+///
+fn validate_port(port: i32) -> Result<u16, anyhow::Error> {
+    u16::try_from(port)
+        .ok()
+        .filter(|port| *port > 0)
+        .with_context(|| format!("Invalid port {}. Ports must be between 1 and 65535", port))
+}
+
+/// Resolves the address used to check that the server is listening
+///
+/// A server bound to all interfaces is reached through the loopback address
+///
+/// # Arguments
+///
+/// * `host` - Host the server binds
+/// * `port` - Port the server listens on
+///
+fn probe_addr(host: &str, port: u16) -> Result<SocketAddr, anyhow::Error> {
+    match host.parse::<IpAddr>() {
+        Ok(ip) if ip.is_unspecified() => Ok(SocketAddr::from((Ipv4Addr::LOCALHOST, port))),
+        Ok(ip) => Ok(SocketAddr::from((ip, port))),
+        Err(_) => (host, port)
+            .to_socket_addrs()
+            .with_context(|| format!("Failed to resolve host {}", host))?
+            .next()
+            .with_context(|| format!("Failed to resolve host {}", host)),
+    }
+}
+
+/// Converts a python exception into an error, keeping the python traceback
+///
+/// # Arguments
+///
+/// * `py` - Python GIL token
+/// * `err` - Python exception
+///
+fn python_error(py: Python, err: PyErr) -> anyhow::Error {
+    let traceback = err
+        .traceback(py)
+        .and_then(|traceback| traceback.format().ok())
+        .unwrap_or_default();
+
+    anyhow::Error::msg(format!("{}{}", traceback, err))
+}
+
+/// Checks that a python module can be imported before running the app
+///
+/// # Arguments
+///
+/// * `module` - Name of the python module
+///
+fn check_module(module: &str) -> Result<(), anyhow::Error> {
+    Python::with_gil(|py| match py.import(module) {
+        Ok(_) => Ok(()),
+        Err(err) => {
+            let version = py.version_info();
+            Err(anyhow::Error::msg(format!(
+                "{} could not be imported with python {}.{}.{} ({}). Install it with `pip install {}`",
+                module, version.major, version.minor, version.patch, err, module
+            )))
+        }
+    })
+}
+
+/// Runs a python script with the app arguments bound to `args`
+///
+/// # Arguments
+///
+/// * `script` - Python source to run
+/// * `args` - App arguments exposed to the script
+///
+fn run_script(script: &str, args: AppArgs) -> Result<(), anyhow::Error> {
+    Python::with_gil(|py| {
+        let run = || -> PyResult<()> {
+            let locals = PyDict::new(py);
+            locals.set_item("args", PyCell::new(py, args)?)?;
+            py.run(script, None, Some(locals))
+        };
+
+        run().map_err(|err| python_error(py, err))
+    })
+}
+
+/// Launches the opsml server and waits for it to bind its port
+///
+/// The server runs on a background thread. The returned error stops the CLI, which also stops the
+/// server
+///
+/// # Arguments
+///
+/// * `args` - App arguments
+/// * `startup_timeout` - Seconds the server has to bind its port
+///
+fn run_app_with_timeout(args: AppArgs, startup_timeout: u64) -> Result<(), anyhow::Error> {
+    let port = args.port;
+    let addr = probe_addr(&args.host, validate_port(port)?)?;
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let _ = sender.send(run_script(APP_SCRIPT, args));
+    });
+
+    let deadline = Instant::now() + Duration::from_secs(startup_timeout);
+    loop {
+        match receiver.try_recv() {
+            Ok(result) => {
+                return result.and(Err(anyhow::Error::msg(format!(
+                    "opsml app exited before listening on port {}",
+                    port
+                ))))
+            }
+            Err(TryRecvError::Disconnected) => {
+                return Err(anyhow::Error::msg("opsml app stopped unexpectedly"))
+            }
+            Err(TryRecvError::Empty) => {}
+        }
+
+        if TcpStream::connect_timeout(&addr, STARTUP_POLL_INTERVAL).is_ok() {
+            break;
+        }
+
+        if Instant::now() >= deadline {
+            return Err(anyhow::Error::msg(format!(
+                "opsml app did not listen on port {} within {}s",
+                port, startup_timeout
+            )));
+        }
+        thread::sleep(STARTUP_POLL_INTERVAL);
+    }
+
+    receiver
+        .recv()
+        .unwrap_or_else(|_| Err(anyhow::Error::msg("opsml app stopped unexpectedly")))
+}
+
+/// Launches the opsml server
+///
+/// # Arguments
+///
+/// * `port` - Port the server listens on
+/// * `login` - Whether to use login credentials
+/// * `host` - Host the server binds
+/// * `workers` - Number of uvicorn workers
+/// * `startup_timeout` - Seconds the server has to bind its port. Waits indefinitely when not set
+///
+pub fn launch_app(
+    port: i32,
+    login: bool,
+    host: &str,
+    workers: u16,
+    startup_timeout: Option<u64>,
+) -> Result<(), anyhow::Error> {
+    validate_port(port)?;
+    check_module(OPSML_MODULE)?;
+    let args = AppArgs {
+        port,
+        login,
+        host: host.to_string(),
+        workers,
+    };
+
+    match startup_timeout {
+        Some(timeout) => run_app_with_timeout(args, timeout),
+        None => run_script(APP_SCRIPT, args),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run_script_import_error() {
+        let args = AppArgs {
+            port: 8888,
+            login: false,
+            host: "0.0.0.0".to_string(),
+            workers: 1,
+        };
+
+        let err = run_script("import opsml_cli_missing_module", args).unwrap_err();
+        assert!(err.to_string().contains("ModuleNotFoundError"));
+    }
+
+    #[test]
+    fn test_check_module() {
+        check_module("json").unwrap();
+
+        let err = check_module("opsml_cli_missing_module").unwrap_err();
+        let message = err.to_string();
+        assert!(message.contains("pip install opsml_cli_missing_module"));
+        assert!(message.contains("could not be imported with python 3."));
+    }
+
+    #[test]
+    fn test_validate_port() {
+        assert_eq!(validate_port(1).unwrap(), 1);
+        assert_eq!(validate_port(65535).unwrap(), 65535);
+        for port in [0, -1, 65536] {
+            let err = validate_port(port).unwrap_err();
+            assert!(err.to_string().contains("between 1 and 65535"));
+        }
+
+        let err = launch_app(0, false, "0.0.0.0", 1, None).unwrap_err();
+        assert!(err.to_string().contains("Invalid port 0"));
+    }
+
+    #[test]
+    fn test_probe_addr() {
+        assert_eq!(
+            probe_addr("0.0.0.0", 8888).unwrap(),
+            SocketAddr::from((Ipv4Addr::LOCALHOST, 8888))
+        );
+        assert_eq!(
+            probe_addr("10.0.0.1", 8888).unwrap(),
+            SocketAddr::from(([10, 0, 0, 1], 8888))
+        );
+    }
+}
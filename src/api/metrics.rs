@@ -1,14 +1,317 @@
 /// Copyright (c) Shipt, Inc.
 /// This source code is licensed under the MIT license found in the
 /// LICENSE file in the root directory of this source tree.
+use crate::api::output;
 use crate::api::route_helper::RouteHelper;
 use crate::api::types;
 use crate::api::utils;
+use anyhow::Context;
+use clap::ValueEnum;
+use futures_util::StreamExt;
+use indexmap::IndexMap;
 use owo_colors::OwoColorize;
-use tabled::settings::style::Style;
+use reqwest::header::CONTENT_TYPE;
+use reqwest::Response;
+use serde_json::Value;
+use std::cmp::Ordering;
+use std::path::Path;
 use tabled::{settings::Alignment, Table};
 
-struct MetricGetter {}
+const NO_METRICS_FOUND: &str = "No metrics found";
+const NDJSON_CONTENT_TYPES: [&str; 2] = ["application/x-ndjson", "application/jsonl"];
+
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum MetricSortKey {
+    /// Metric name, alphabetically
+    Name,
+    /// Metric value, largest first
+    Value,
+}
+
+/// Sorting and truncation applied to metric table rows
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct MetricSelection {
+    pub sort: Option<MetricSortKey>,
+    pub ascending: bool,
+    pub top: Option<usize>,
+}
+
+impl MetricSelection {
+    pub fn is_default(&self) -> bool {
+        *self == MetricSelection::default()
+    }
+}
+
+struct MetricGetter {
+    pub table_style: utils::TableStyle,
+    pub output: output::OutputOptions,
+    pub color: bool,
+    pub selection: MetricSelection,
+    pub pivot: bool,
+}
+
+/// Coerces a metric value to a number, parsing numeric strings
+///
+/// # Arguments
+///
+/// * `value` - Metric value
+///
+/// # Returns
+/// * `Option<f64>` - Numeric value, or None when the value is not numeric
+///
+fn numeric_value(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(number) => number.as_f64(),
+        Value::String(string) => string.trim().parse::<f64>().ok().filter(|v| !v.is_nan()),
+        _ => None,
+    }
+}
+
+/// Orders metric values numerically, placing non-numeric values after numeric ones
+///
+/// # Arguments
+///
+/// * `a` - First metric value
+/// * `b` - Second metric value
+///
+fn compare_values(a: &Value, b: &Value) -> Ordering {
+    match (numeric_value(a), numeric_value(b)) {
+        (Some(a), Some(b)) => a.total_cmp(&b),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => a.to_string().cmp(&b.to_string()),
+    }
+}
+
+/// Sorts metric rows and keeps the first `top` rows
+///
+/// Sorting is stable, so rows that compare equal keep the order returned by the server.
+/// Non-numeric values always sort last
+///
+/// # Arguments
+///
+/// * `rows` - Metric rows
+/// * `selection` - Sort key, direction and row limit
+///
+fn select_metric_rows(rows: &mut Vec<types::MetricTable>, selection: &MetricSelection) {
+    match selection.sort {
+        // names always sort alphabetically; `ascending` only flips the largest-first value sort
+        Some(MetricSortKey::Name) => {
+            rows.sort_by(|a, b| a.metric.cmp(&b.metric));
+        }
+        Some(MetricSortKey::Value) => {
+            rows.sort_by(
+                |a, b| match (numeric_value(&a.value), numeric_value(&b.value)) {
+                    (Some(_), Some(_)) if !selection.ascending => {
+                        compare_values(&b.value, &a.value)
+                    }
+                    _ => compare_values(&a.value, &b.value),
+                },
+            );
+        }
+        None => {}
+    }
+
+    if let Some(top) = selection.top {
+        rows.truncate(top);
+    }
+}
+
+/// Converts a metric into a table row
+///
+/// # Arguments
+///
+/// * `metric` - Metric returned by the server
+///
+fn metric_row(metric: &types::Metric) -> types::MetricTable {
+    let step = if let Some(step) = &metric.step {
+        step.to_string()
+    } else {
+        "None".to_string()
+    };
+
+    let timestamp = if let Some(timestamp) = &metric.timestamp {
+        timestamp.to_string()
+    } else {
+        "None".to_string()
+    };
+
+    types::MetricTable {
+        metric: metric.name.clone(),
+        value: metric.value.clone(),
+        step,
+        timestamp,
+    }
+}
+
+/// Checks whether the server streamed metrics as newline-delimited JSON
+///
+/// # Arguments
+///
+/// * `response` - Metric response
+///
+fn is_ndjson(response: &Response) -> bool {
+    response
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| {
+            NDJSON_CONTENT_TYPES
+                .iter()
+                .any(|content_type| value.starts_with(content_type))
+        })
+}
+
+/// Parses one line of a metric NDJSON stream. Blank lines are skipped
+///
+/// # Arguments
+///
+/// * `line` - Line without its trailing newline
+///
+fn parse_ndjson_metric(line: &[u8]) -> Result<Option<types::Metric>, anyhow::Error> {
+    let line = std::str::from_utf8(line).with_context(|| "Metric stream is not valid UTF-8")?;
+    if line.trim().is_empty() {
+        return Ok(None);
+    }
+
+    serde_json::from_str(line)
+        .map(Some)
+        .with_context(|| format!("Failed to parse metric line: {}", line))
+}
+
+/// Reads metrics streamed as NDJSON, one metric per line
+///
+/// Lines are parsed as they arrive so the full response is never held in memory
+///
+/// # Arguments
+///
+/// * `response` - Metric response
+///
+/// # Returns
+/// * `Result<Vec<types::Metric>, anyhow::Error>` - Metrics in stream order
+///
+async fn read_ndjson_metrics(response: Response) -> Result<Vec<types::Metric>, anyhow::Error> {
+    let mut response_stream = response.bytes_stream();
+    let mut buffer: Vec<u8> = Vec::new();
+    let mut metrics = Vec::new();
+
+    while let Some(item) = response_stream.next().await {
+        let chunk = item.with_context(|| "failed to read metric stream")?;
+        buffer.extend_from_slice(&chunk);
+
+        // keep any partial line for the next chunk
+        while let Some(newline) = buffer.iter().position(|byte| *byte == b'\n') {
+            let line: Vec<u8> = buffer.drain(..=newline).collect();
+            metrics.extend(parse_ndjson_metric(&line[..newline])?);
+        }
+    }
+    metrics.extend(parse_ndjson_metric(&buffer)?);
+
+    Ok(metrics)
+}
+
+/// Formats the change from a champion value to a challenger value (e.g. `+0.05`)
+///
+/// # Arguments
+///
+/// * `champion` - Champion metric value
+/// * `challenger` - Challenger metric value
+///
+/// # Returns
+/// * `Option<String>` - Signed delta, or None when either value is not numeric
+///
+fn format_delta(champion: &Value, challenger: &Value) -> Option<String> {
+    let delta = challenger.as_f64()? - champion.as_f64()?;
+    let formatted = format!("{:+.4}", delta);
+
+    Some(
+        formatted
+            .trim_end_matches('0')
+            .trim_end_matches('.')
+            .to_string(),
+    )
+}
+
+/// Reads champion/challenger pairs from a CSV file
+///
+/// Each row is `challenger_uid,champion_uid,metric_name,lower_is_better`. A header row
+/// with those names is optional
+///
+/// # Arguments
+///
+/// * `path` - Path to the CSV file
+///
+/// # Returns
+/// * `Result<Vec<types::ComparePair>, anyhow::Error>` - Pairs in file order
+///
+fn read_compare_pairs(path: &Path) -> Result<Vec<types::ComparePair>, anyhow::Error> {
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .trim(csv::Trim::All)
+        .comment(Some(b'#'))
+        .from_path(path)
+        .with_context(|| format!("Failed to open pairs file {:?}", path))?;
+
+    let mut pairs = Vec::new();
+    for (index, record) in reader.records().enumerate() {
+        let record = record.with_context(|| format!("Failed to read pairs file {:?}", path))?;
+        if index == 0 && record.get(0) == Some("challenger_uid") {
+            continue;
+        }
+
+        let pair: types::ComparePair = record.deserialize(None).with_context(|| {
+            format!(
+                "Invalid row {} in {:?}. Expected challenger_uid,champion_uid,metric_name,lower_is_better",
+                index + 1,
+                path
+            )
+        })?;
+        pairs.push(pair);
+    }
+
+    if pairs.is_empty() {
+        return Err(anyhow::Error::msg(format!("No pairs found in {:?}", path)));
+    }
+
+    Ok(pairs)
+}
+
+/// Merges reports for the same challenger so each challenger renders as one table
+///
+/// # Arguments
+///
+/// * `compare_reports` - Reports in request order
+///
+/// # Returns
+/// * `Vec<types::CompareMetricResponse>` - One report per challenger, in first-seen order
+///
+fn merge_compare_reports(
+    compare_reports: Vec<types::CompareMetricResponse>,
+) -> Vec<types::CompareMetricResponse> {
+    let mut merged: Vec<types::CompareMetricResponse> = Vec::new();
+
+    for compare_report in compare_reports {
+        let existing = merged.iter_mut().find(|report| {
+            report.challenger_name == compare_report.challenger_name
+                && report.challenger_version == compare_report.challenger_version
+        });
+
+        match existing {
+            Some(existing) => {
+                for (metric, battle_reports) in compare_report.report {
+                    existing
+                        .report
+                        .entry(metric)
+                        .or_default()
+                        .extend(battle_reports);
+                }
+            }
+            None => merged.push(compare_report),
+        }
+    }
+
+    merged
+}
 
 impl MetricGetter {
     /// Parse metric response
@@ -26,45 +329,127 @@ impl MetricGetter {
         let metrics: types::ListMetricResponse =
             serde_json::from_str(response).expect("Failed to load response to MetricResponse JSON");
 
-        let mut metric_table: Vec<types::MetricTable> = Vec::new();
+        let metric_table: Vec<types::MetricTable> = metrics
+            .metrics
+            .values()
+            .flat_map(|metric_array| metric_array.iter().map(metric_row))
+            .collect();
 
-        for (_, metric_array) in metrics.metrics.iter() {
-            for metric in metric_array.iter() {
-                let step = if metric.step.is_some() {
-                    metric.step.as_ref().unwrap().to_string()
-                } else {
-                    "None".to_string()
-                };
+        self.render_metric_rows(metric_table)
+    }
 
-                let timestamp = if metric.timestamp.is_some() {
-                    metric.timestamp.as_ref().unwrap().to_string()
-                } else {
-                    "None".to_string()
-                };
-
-                metric_table.push(types::MetricTable {
-                    metric: metric.name.clone(),
-                    value: metric.value.clone(),
-                    step,
-                    timestamp,
-                });
+    /// Renders metric rows as a table, applying the sort, row limit and pivot options
+    ///
+    /// # Arguments
+    ///
+    /// * `metric_table` - Metric rows, one per (metric, step)
+    ///
+    /// # Returns
+    ///  String - Table of metrics
+    ///
+    fn render_metric_rows(&self, mut metric_table: Vec<types::MetricTable>) -> String {
+        select_metric_rows(&mut metric_table, &self.selection);
+
+        if self.pivot {
+            return self.build_pivot_metric_table(metric_table);
+        }
+
+        let mut metric_table = Table::new(metric_table);
+        metric_table.with(Alignment::center());
+
+        self.table_style.render(&mut metric_table)
+    }
+
+    /// Builds a table with a row per metric and a column per step
+    ///
+    /// Steps are ordered numerically. Metrics keep the order of the selected rows, or are
+    /// ordered by name when no sort is given. Steps a metric was not logged at are shown as "-"
+    ///
+    /// # Arguments
+    ///
+    /// * `rows` - Metric rows, one per (metric, step)
+    ///
+    /// # Returns
+    ///  String - Pivoted table of metrics
+    ///
+    fn build_pivot_metric_table(&self, mut rows: Vec<types::MetricTable>) -> String {
+        if self.selection.sort.is_none() {
+            rows.sort_by(|a, b| a.metric.cmp(&b.metric));
+        }
+
+        let mut steps: Vec<&str> = Vec::new();
+        let mut metrics: Vec<&str> = Vec::new();
+        for row in rows.iter() {
+            if !steps.contains(&row.step.as_str()) {
+                steps.push(&row.step);
+            }
+            if !metrics.contains(&row.metric.as_str()) {
+                metrics.push(&row.metric);
             }
         }
+        steps.sort_by(|a, b| compare_values(&Value::from(*a), &Value::from(*b)));
+
+        let mut builder = tabled::builder::Builder::default();
+        let mut header = vec!["Metric".to_string()];
+        header.extend(steps.iter().map(|step| format!("Step {}", step)));
+        builder.set_header(header);
 
-        let metric_table = Table::new(metric_table)
-            .with(Alignment::center())
-            .with(Style::sharp())
-            .to_string();
+        for metric in metrics.iter() {
+            let mut record = vec![metric.to_string()];
+            for step in steps.iter() {
+                let value = rows
+                    .iter()
+                    .find(|row| row.metric == *metric && row.step == *step)
+                    .map_or("-".to_string(), |row| row.value.to_string());
+                record.push(value);
+            }
+            builder.push_record(record);
+        }
+
+        let mut table = builder.build();
+        table.with(Alignment::center());
 
-        metric_table
+        self.table_style.render(&mut table)
     }
 
-    fn parse_compare_metric_response(&self, response: &str) -> String {
-        // Parses response and creates a table
+    /// Renders comparison reports, grouped by challenger when there is more than one
+    ///
+    /// # Arguments
+    ///
+    /// * `compare_reports` - Report for each challenger
+    ///
+    /// # Returns
+    ///  String - Table per challenger
+    ///
+    fn render_compare_reports(&self, compare_reports: &[types::CompareMetricResponse]) -> String {
+        if let [compare_report] = compare_reports {
+            return self.build_compare_metric_table(compare_report);
+        }
 
-        let compare_report: types::CompareMetricResponse = serde_json::from_str(response)
-            .expect("Failed to load response to CompareMetricResponse JSON");
+        compare_reports
+            .iter()
+            .map(|compare_report| {
+                format!(
+                    "Challenger {} ({})\n{}",
+                    compare_report.challenger_name.bold(),
+                    compare_report.challenger_version,
+                    self.build_compare_metric_table(compare_report)
+                )
+            })
+            .collect::<Vec<String>>()
+            .join("\n\n")
+    }
 
+    /// Builds a table comparing a challenger against its champions
+    ///
+    /// # Arguments
+    ///
+    /// * `compare_report` - Comparison report for a challenger
+    ///
+    /// # Returns
+    ///  String - Table of metrics
+    ///
+    fn build_compare_metric_table(&self, compare_report: &types::CompareMetricResponse) -> String {
         let mut builder = tabled::builder::Builder::default();
         builder.set_header(vec![
             "Champion Name",
@@ -75,7 +460,7 @@ impl MetricGetter {
             "Challenger Win",
         ]);
 
-        let battle_reports = compare_report.report;
+        let battle_reports = &compare_report.report;
         for (_, reports) in battle_reports.iter() {
             for report in reports.iter() {
                 // champion and challenger should have metrics to render in table
@@ -84,18 +469,41 @@ impl MetricGetter {
                 } else {
                     let challenger_metric = report.challenger_metric.as_ref().unwrap();
                     let champion_metric = report.champion_metric.as_ref().unwrap();
+
+                    let champion_value = champion_metric.value.to_string();
+                    let mut challenger_value = challenger_metric.value.to_string();
+                    if let Some(delta) =
+                        format_delta(&champion_metric.value, &challenger_metric.value)
+                    {
+                        challenger_value = format!("{} ({})", challenger_value, delta);
+                    }
+
+                    // the server decides the winner, which already accounts for lower_is_better
+                    let tied = champion_metric.value == challenger_metric.value;
+                    let (champion_value, challenger_value) = match (self.color, tied) {
+                        (true, false) if report.challenger_win => (
+                            champion_value.red().to_string(),
+                            challenger_value.green().to_string(),
+                        ),
+                        (true, false) => (
+                            champion_value.green().to_string(),
+                            challenger_value.red().to_string(),
+                        ),
+                        _ => (champion_value, challenger_value),
+                    };
+
                     let mut record = vec![
                         report.champion_name.clone(),
                         report.champion_version.clone(),
                         champion_metric.name.clone(),
-                        champion_metric.value.to_string(),
-                        challenger_metric.value.to_string(),
+                        champion_value,
+                        challenger_value,
                     ];
 
-                    if report.challenger_win {
-                        record.append(&mut vec!["true".green().to_string()]);
-                    } else {
-                        record.append(&mut vec!["false".red().to_string()]);
+                    match (self.color, report.challenger_win) {
+                        (true, true) => record.push("true".green().to_string()),
+                        (true, false) => record.push("false".red().to_string()),
+                        (false, win) => record.push(win.to_string()),
                     };
                     // insert values
                     builder.push_record(record);
@@ -104,43 +512,174 @@ impl MetricGetter {
         }
 
         let mut table = builder.build();
-        let compare_metric_table = table
-            .with(Alignment::center())
-            .with(Style::sharp())
-            .to_string();
+        table.with(Alignment::center());
+
+        self.table_style.render(&mut table)
+    }
+
+    /// Sends a metric request for a model
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Name of the model
+    /// * `repository` - Repository (team) of the model
+    /// * `version` - Version of the model
+    /// * `uid` - Unique identifier of the model
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Response, anyhow::Error>` - Successful response, with the body unread
+    async fn send_metric_request(
+        &self,
+        name: Option<&str>,
+        repository: Option<&str>,
+        version: Option<&str>,
+        uid: Option<&str>,
+    ) -> Result<Response, anyhow::Error> {
+        let (name, repository, version, uid) = (
+            utils::trim_arg(name),
+            utils::trim_arg(repository),
+            utils::trim_arg(version),
+            utils::trim_arg(uid),
+        );
+        utils::check_args(name, repository, version, uid).await?;
+        let model_metric_request = types::CardRequest {
+            name,
+            repository,
+            version,
+            uid,
+        };
 
-        compare_metric_table
+        let response = RouteHelper::make_post_request(
+            &utils::OpsmlPaths::Metric.as_str(),
+            &model_metric_request,
+        )
+        .await?;
+
+        if response.status().is_success() {
+            Ok(response)
+        } else {
+            Err(RouteHelper::response_error(response, "Request failed").await)
+        }
     }
 
     /// Get model metrics
     pub async fn get_model_metrics(
         &self,
         name: Option<&str>,
+        repository: Option<&str>,
         version: Option<&str>,
         uid: Option<&str>,
     ) -> Result<(), anyhow::Error> {
-        let model_metric_request = types::CardRequest { name, version, uid };
+        if (!self.selection.is_default() || self.pivot)
+            && (self.output.raw || self.output.is_json())
+        {
+            return Err(anyhow::Error::msg(
+                "--sort, --top and --pivot only apply to table output",
+            ));
+        }
+
+        let response = self
+            .send_metric_request(name, repository, version, uid)
+            .await?;
+
+        if !self.output.raw && is_ndjson(&response) {
+            let metrics = read_ndjson_metrics(response).await?;
+            return self.print_streamed_metrics(metrics);
+        }
+
+        let body = response.text().await?;
+
+        if self.output.raw {
+            output::print(body)?;
+            return Ok(());
+        }
+
+        // some routes return 202/204 with an empty body when there is nothing to report
+        if body.trim().is_empty() {
+            output::print(NO_METRICS_FOUND)?;
+            return Ok(());
+        }
+
+        if self.output.is_json() {
+            let metrics: types::ListMetricResponse = serde_json::from_str(&body)
+                .with_context(|| "Failed to load response to MetricResponse JSON")?;
+            output::print(self.output.render_json(&metrics)?)?;
+            return Ok(());
+        }
+
+        let metric_table = self.parse_metric_response(&body);
+        output::print("\nModel Metrics")?;
+        output::print(metric_table)?;
+        Ok(())
+    }
+
+    /// Prints metrics read from an NDJSON stream
+    ///
+    /// # Arguments
+    ///
+    /// * `metrics` - Metrics in stream order
+    ///
+    fn print_streamed_metrics(&self, metrics: Vec<types::Metric>) -> Result<(), anyhow::Error> {
+        if metrics.is_empty() {
+            return output::print(NO_METRICS_FOUND);
+        }
+
+        if self.output.is_json() {
+            let mut grouped: IndexMap<String, Vec<types::Metric>> = IndexMap::new();
+            for metric in metrics {
+                grouped.entry(metric.name.clone()).or_default().push(metric);
+            }
+            let metrics = types::ListMetricResponse { metrics: grouped };
+            return output::print(self.output.render_json(&metrics)?);
+        }
+
+        let metric_table = self.render_metric_rows(metrics.iter().map(metric_row).collect());
+        output::print("\nModel Metrics")?;
+        output::print(metric_table)
+    }
+
+    /// Requests a comparison of one challenger against the champions
+    ///
+    /// # Arguments
+    ///
+    /// * `metric_name` - Name of the metric
+    /// * `lower_is_better` - Whether a lower value is better for the metric
+    /// * `challenger_uid` - Unique identifier of the challenger model
+    /// * `champion_uid` - Unique identifier of the champion model
+    ///
+    /// # Returns
+    ///
+    /// * `Result<String, anyhow::Error>` - Response body exactly as returned by the server
+    async fn request_compare_body(
+        &self,
+        metric_name: &Vec<String>,
+        lower_is_better: &Vec<bool>,
+        challenger_uid: &str,
+        champion_uid: &Vec<String>,
+    ) -> Result<String, anyhow::Error> {
+        // set up repair request
+        let compare_metric_request = types::CompareMetricRequest {
+            metric_name,
+            lower_is_better,
+            challenger_uid,
+            champion_uid,
+        };
 
         let response = RouteHelper::make_post_request(
-            &utils::OpsmlPaths::Metric.as_str(),
-            &model_metric_request,
+            &utils::OpsmlPaths::CompareMetric.as_str(),
+            &compare_metric_request,
         )
         .await?;
 
         if response.status().is_success() {
-            let metric_table = self.parse_metric_response(&response.text().await?);
-            println!("\nModel Metrics");
-            println!("{}", metric_table);
-            Ok(())
+            Ok(response.text().await?)
         } else {
-            Err(anyhow::Error::msg(format!(
-                "Request failed {:?}",
-                response.error_for_status_ref()
-            )))
+            Err(RouteHelper::response_error(response, "Request failed").await)
         }
     }
 
-    /// Compare model metrics
+    /// Requests and parses a comparison of one challenger against the champions
     ///
     /// # Arguments
     ///
@@ -149,40 +688,165 @@ impl MetricGetter {
     /// * `challenger_uid` - Unique identifier of the challenger model
     /// * `champion_uid` - Unique identifier of the champion model
     ///
-    /// # Returns
+    /// # Returns
+    ///
+    /// * `Result<Option<types::CompareMetricResponse>, anyhow::Error>` - Comparison report, or None when the server has nothing to report
+    async fn request_compare_metrics(
+        &self,
+        metric_name: &Vec<String>,
+        lower_is_better: &Vec<bool>,
+        challenger_uid: &str,
+        champion_uid: &Vec<String>,
+    ) -> Result<Option<types::CompareMetricResponse>, anyhow::Error> {
+        let body = self
+            .request_compare_body(metric_name, lower_is_better, challenger_uid, champion_uid)
+            .await?;
+
+        // some routes return 202/204 with an empty body when there is nothing to report
+        if body.trim().is_empty() {
+            return Ok(None);
+        }
+
+        let compare_report: types::CompareMetricResponse = serde_json::from_str(&body)
+            .with_context(|| "Failed to load response to CompareMetricResponse JSON")?;
+        Ok(Some(compare_report))
+    }
+
+    /// Compare model metrics
+    ///
+    /// Each challenger is compared against the champions in a separate request
+    ///
+    /// # Arguments
+    ///
+    /// * `metric_name` - Name of the metric
+    /// * `lower_is_better` - Whether a lower value is better for the metric
+    /// * `challenger_uid` - Unique identifiers of the challenger models
+    /// * `champion_uid` - Unique identifier of the champion model
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), anyhow::Error>` - Result of the request
+    pub async fn compare_model_metrics(
+        &self,
+        metric_name: &Vec<String>,
+        lower_is_better: &Vec<bool>,
+        challenger_uid: &[String],
+        champion_uid: &Vec<String>,
+    ) -> Result<(), anyhow::Error> {
+        if self.output.raw {
+            for challenger in challenger_uid.iter() {
+                let body = self
+                    .request_compare_body(metric_name, lower_is_better, challenger, champion_uid)
+                    .await
+                    .with_context(|| format!("Failed to compare challenger {}", challenger))?;
+                output::print(body)?;
+            }
+            return Ok(());
+        }
+
+        let mut compare_reports = Vec::new();
+
+        for challenger in challenger_uid.iter() {
+            let compare_report = self
+                .request_compare_metrics(metric_name, lower_is_better, challenger, champion_uid)
+                .await
+                .with_context(|| format!("Failed to compare challenger {}", challenger))?;
+            compare_reports.extend(compare_report);
+        }
+
+        self.print_compare_reports(&compare_reports)
+    }
+
+    /// Requests one comparison per champion/challenger pair
+    ///
+    /// # Arguments
+    ///
+    /// * `pairs` - Pairs to compare
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Vec<types::CompareMetricResponse>, anyhow::Error>` - Reports merged by challenger
+    async fn request_pair_reports(
+        &self,
+        pairs: &[types::ComparePair],
+    ) -> Result<Vec<types::CompareMetricResponse>, anyhow::Error> {
+        let mut compare_reports = Vec::new();
+
+        for pair in pairs.iter() {
+            let compare_report = self
+                .request_compare_metrics(
+                    &vec![pair.metric_name.clone()],
+                    &vec![pair.lower_is_better],
+                    &pair.challenger_uid,
+                    &vec![pair.champion_uid.clone()],
+                )
+                .await
+                .with_context(|| {
+                    format!(
+                        "Failed to compare challenger {} against champion {}",
+                        pair.challenger_uid, pair.champion_uid
+                    )
+                })?;
+            compare_reports.extend(compare_report);
+        }
+
+        Ok(merge_compare_reports(compare_reports))
+    }
+
+    /// Compare model metrics for each row of a pairs file
+    ///
+    /// # Arguments
+    ///
+    /// * `pairs` - Pairs to compare
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), anyhow::Error>` - Result of the requests
+    pub async fn compare_pairs(&self, pairs: &[types::ComparePair]) -> Result<(), anyhow::Error> {
+        if self.output.raw {
+            for pair in pairs.iter() {
+                let body = self
+                    .request_compare_body(
+                        &vec![pair.metric_name.clone()],
+                        &vec![pair.lower_is_better],
+                        &pair.challenger_uid,
+                        &vec![pair.champion_uid.clone()],
+                    )
+                    .await
+                    .with_context(|| {
+                        format!("Failed to compare challenger {}", pair.challenger_uid)
+                    })?;
+                output::print(body)?;
+            }
+            return Ok(());
+        }
+
+        let compare_reports = self.request_pair_reports(pairs).await?;
+        self.print_compare_reports(&compare_reports)
+    }
+
+    /// Prints comparison reports in the configured output format
+    ///
+    /// # Arguments
+    ///
+    /// * `compare_reports` - Report for each challenger
     ///
-    /// * `Result<(), anyhow::Error>` - Result of the request
-    pub async fn compare_model_metrics(
+    fn print_compare_reports(
         &self,
-        metric_name: &Vec<String>,
-        lower_is_better: &Vec<bool>,
-        challenger_uid: &str,
-        champion_uid: &Vec<String>,
+        compare_reports: &[types::CompareMetricResponse],
     ) -> Result<(), anyhow::Error> {
-        // set up repair request
-        let compare_metric_request = types::CompareMetricRequest {
-            metric_name,
-            lower_is_better,
-            challenger_uid,
-            champion_uid,
-        };
-
-        let response = RouteHelper::make_post_request(
-            &utils::OpsmlPaths::CompareMetric.as_str(),
-            &compare_metric_request,
-        )
-        .await?;
+        if self.output.is_json() {
+            output::print(self.output.render_json(&compare_reports)?)?;
+            return Ok(());
+        }
 
-        if response.status().is_success() {
-            let metric_table = self.parse_compare_metric_response(&response.text().await?);
-            println!("{}", metric_table);
-            Ok(())
-        } else {
-            Err(anyhow::Error::msg(format!(
-                "Request failed {:?}",
-                response.error_for_status_ref()
-            )))
+        if compare_reports.is_empty() {
+            output::print(NO_METRICS_FOUND)?;
+            return Ok(());
         }
+
+        output::print(self.render_compare_reports(compare_reports))?;
+        Ok(())
     }
 }
 
@@ -191,37 +855,72 @@ impl MetricGetter {
 /// # Arguments
 ///
 /// * `name` - Name of the model
+/// * `repository` - Repository (team) of the model
 /// * `version` - Version of the model
 /// * `uid` - Unique identifier of the model
 /// * `url` - URL of the OpsML server
+/// * `table_style` - Style used to render the table
+/// * `output` - Output format options
+/// * `selection` - Sorting and row limit applied to the metric table
+/// * `pivot` - Render one row per metric with a column per step
 #[tokio::main]
+#[allow(clippy::too_many_arguments)]
 pub async fn get_model_metrics(
     name: Option<&str>,
+    repository: Option<&str>,
     version: Option<&str>,
     uid: Option<&str>,
+    table_style: utils::TableStyle,
+    output: output::OutputOptions,
+    selection: MetricSelection,
+    pivot: bool,
 ) -> Result<(), anyhow::Error> {
-    let metric_getter = MetricGetter {};
-    metric_getter.get_model_metrics(name, version, uid).await
+    let metric_getter = MetricGetter {
+        table_style,
+        output,
+        color: utils::color_enabled(),
+        selection,
+        pivot,
+    };
+    utils::with_shared_client(metric_getter.get_model_metrics(name, repository, version, uid)).await
 }
 
 #[tokio::main]
 pub async fn compare_model_metrics(
     metric_name: &Vec<String>,
     lower_is_better: &Vec<bool>,
-    challenger_uid: &str,
+    challenger_uid: &[String],
     champion_uid: &Vec<String>,
+    pairs: Option<&str>,
+    table_style: utils::TableStyle,
+    output: output::OutputOptions,
 ) -> Result<(), anyhow::Error> {
     // set up repair request
-    let compare_mertic = MetricGetter {};
+    let compare_mertic = MetricGetter {
+        table_style,
+        output,
+        color: utils::color_enabled(),
+        selection: MetricSelection::default(),
+        pivot: false,
+    };
 
-    compare_mertic
-        .compare_model_metrics(metric_name, lower_is_better, challenger_uid, champion_uid)
-        .await
+    utils::with_shared_client(async {
+        if let Some(pairs) = pairs {
+            let pairs = read_compare_pairs(Path::new(pairs))?;
+            return compare_mertic.compare_pairs(&pairs).await;
+        }
+
+        compare_mertic
+            .compare_model_metrics(metric_name, lower_is_better, challenger_uid, champion_uid)
+            .await
+    })
+    .await
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::api::test_utils::TestEnv;
 
     use std::collections::HashMap;
     use std::env;
@@ -230,6 +929,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_get_metrics() {
+        let _env = TestEnv::lock();
         let mut server = mockito::Server::new();
         let url = server.url();
         let path = "./src/api/test_utils/list_metric.json";
@@ -254,7 +954,13 @@ mod tests {
         };
         vec.push(metric2);
 
-        let metric_getter = MetricGetter {};
+        let metric_getter = MetricGetter {
+            table_style: utils::TableStyle::Sharp,
+            output: output::OutputOptions::default(),
+            color: true,
+            selection: MetricSelection::default(),
+            pivot: false,
+        };
 
         // Create a mock server
         let mock_get_metrics = server
@@ -264,11 +970,11 @@ mod tests {
             .create();
 
         metric_getter
-            .get_model_metrics(Some("fake"), Some("1.0.0"), None)
+            .get_model_metrics(Some("fake"), None, Some("1.0.0"), None)
             .await
             .unwrap();
 
-        let mut metrics = HashMap::new();
+        let mut metrics = IndexMap::new();
         metrics.insert("test".to_string(), vec);
 
         let mock_response = types::ListMetricResponse { metrics };
@@ -293,6 +999,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_compare_metrics() {
+        let _env = TestEnv::lock();
         let mut server = mockito::Server::new();
         let url = server.url();
         let path = "./src/api/test_utils/compare_metric.json";
@@ -307,12 +1014,18 @@ mod tests {
             .with_body(metric_data)
             .create();
 
-        let metric_compare = MetricGetter {};
+        let metric_compare = MetricGetter {
+            table_style: utils::TableStyle::Sharp,
+            output: output::OutputOptions::default(),
+            color: true,
+            selection: MetricSelection::default(),
+            pivot: false,
+        };
         metric_compare
             .compare_model_metrics(
                 &vec!["mae".to_string(), "mape".to_string()],
                 &vec![false, true],
-                "uid",
+                &["uid".to_string()],
                 &vec!["uid".to_string(), "uid".to_string()],
             )
             .await
@@ -320,6 +1033,631 @@ mod tests {
         mock_compare_metrics.assert();
     }
 
+    #[test]
+    fn test_build_compare_metric_table_no_color() {
+        let path = "./src/api/test_utils/compare_metric.json";
+        let metric_data = fs::read_to_string(path).expect("Unable to read file");
+        let compare_report: types::CompareMetricResponse =
+            serde_json::from_str(&metric_data).unwrap();
+
+        let metric_compare = MetricGetter {
+            table_style: utils::TableStyle::Markdown,
+            output: output::OutputOptions::default(),
+            color: false,
+            selection: MetricSelection::default(),
+            pivot: false,
+        };
+
+        assert_eq!(
+            metric_compare.build_compare_metric_table(&compare_report),
+            concat!(
+                "|      Champion Name      | Champion Version | Metric | Champion Value | Challenger Value | Challenger Win |\n",
+                "|-------------------------|------------------|--------|----------------|------------------|----------------|\n",
+                "| hootie-and-the-blowfish |      1.0.1       |  mape  |      10.0      |      5 (-5)      |      true      |\n",
+                "| hootie-and-the-blowfish |      1.0.2       |  mape  |       2        |      5 (+3)      |     false      |",
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn test_compare_multiple_challengers() {
+        let _env = TestEnv::lock();
+        let mut server = mockito::Server::new();
+        let url = server.url();
+        env::set_var("OPSML_TRACKING_URI", url);
+
+        let path = "./src/api/test_utils/compare_metric.json";
+        let metric_data = fs::read_to_string(path).expect("Unable to read file");
+
+        let mut mocks = Vec::new();
+        for challenger in ["uid-a", "uid-b"] {
+            mocks.push(
+                server
+                    .mock("POST", "/opsml/models/compare_metrics")
+                    .match_body(mockito::Matcher::Json(serde_json::json!({
+                        "metric_name": ["mape"],
+                        "lower_is_better": [true],
+                        "challenger_uid": challenger,
+                        "champion_uid": ["champion"]
+                    })))
+                    .with_status(201)
+                    .with_body(&metric_data)
+                    .create(),
+            );
+        }
+
+        let metric_compare = MetricGetter {
+            table_style: utils::TableStyle::Sharp,
+            output: output::OutputOptions::default(),
+            color: false,
+            selection: MetricSelection::default(),
+            pivot: false,
+        };
+        metric_compare
+            .compare_model_metrics(
+                &vec!["mape".to_string()],
+                &vec![true],
+                &["uid-a".to_string(), "uid-b".to_string()],
+                &vec!["champion".to_string()],
+            )
+            .await
+            .unwrap();
+
+        for mock in mocks.iter() {
+            mock.assert();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_compare_pairs_csv() {
+        let _env = TestEnv::lock();
+        let mut server = mockito::Server::new();
+        let url = server.url();
+        env::set_var("OPSML_TRACKING_URI", url);
+
+        let path = "./src/api/test_utils/compare_metric.json";
+        let metric_data = fs::read_to_string(path).expect("Unable to read file");
+
+        let pairs_path = format!("./src/api/test_utils/{}.csv", uuid::Uuid::new_v4());
+        fs::write(
+            &pairs_path,
+            concat!(
+                "challenger_uid,champion_uid,metric_name,lower_is_better\n",
+                "uid-a, champion-1, mape, true\n",
+                "uid-a,champion-2,r2,false\n",
+            ),
+        )
+        .unwrap();
+        let pairs = read_compare_pairs(Path::new(&pairs_path)).unwrap();
+        fs::remove_file(&pairs_path).unwrap();
+
+        assert_eq!(pairs.len(), 2);
+        assert_eq!(
+            pairs[0],
+            types::ComparePair {
+                challenger_uid: "uid-a".to_string(),
+                champion_uid: "champion-1".to_string(),
+                metric_name: "mape".to_string(),
+                lower_is_better: true,
+            }
+        );
+
+        let mut mocks = Vec::new();
+        for (champion, metric, lower_is_better) in
+            [("champion-1", "mape", true), ("champion-2", "r2", false)]
+        {
+            mocks.push(
+                server
+                    .mock("POST", "/opsml/models/compare_metrics")
+                    .match_body(mockito::Matcher::Json(serde_json::json!({
+                        "metric_name": [metric],
+                        "lower_is_better": [lower_is_better],
+                        "challenger_uid": "uid-a",
+                        "champion_uid": [champion]
+                    })))
+                    .with_status(201)
+                    .with_body(&metric_data)
+                    .create(),
+            );
+        }
+
+        let metric_compare = MetricGetter {
+            table_style: utils::TableStyle::Sharp,
+            output: output::OutputOptions::default(),
+            color: false,
+            selection: MetricSelection::default(),
+            pivot: false,
+        };
+        let compare_reports = metric_compare.request_pair_reports(&pairs).await.unwrap();
+
+        for mock in mocks.iter() {
+            mock.assert();
+        }
+
+        // both rows compare the same challenger, so they are combined into one report
+        assert_eq!(compare_reports.len(), 1);
+        assert_eq!(compare_reports[0].report["test"].len(), 4);
+    }
+
+    #[test]
+    fn test_read_compare_pairs_invalid_row() {
+        let pairs_path = format!("./src/api/test_utils/{}.csv", uuid::Uuid::new_v4());
+        fs::write(&pairs_path, "uid-a,champion-1,mape,maybe\n").unwrap();
+        let error = read_compare_pairs(Path::new(&pairs_path)).unwrap_err();
+        fs::remove_file(&pairs_path).unwrap();
+
+        assert!(error.to_string().contains("Invalid row 1"));
+    }
+
+    #[test]
+    fn test_render_compare_reports() {
+        let path = "./src/api/test_utils/compare_metric.json";
+        let metric_data = fs::read_to_string(path).expect("Unable to read file");
+
+        let mut challenger_b: types::CompareMetricResponse =
+            serde_json::from_str(&metric_data).unwrap();
+        challenger_b.challenger_name = "challenger-b".to_string();
+        challenger_b.challenger_version = "2.0.0".to_string();
+        challenger_b.report.get_mut("test").unwrap().truncate(1);
+
+        let compare_reports = vec![serde_json::from_str(&metric_data).unwrap(), challenger_b];
+
+        let metric_compare = MetricGetter {
+            table_style: utils::TableStyle::Markdown,
+            output: output::OutputOptions::default(),
+            color: false,
+            selection: MetricSelection::default(),
+            pivot: false,
+        };
+
+        let rendered = metric_compare.render_compare_reports(&compare_reports);
+        let sections: Vec<&str> = rendered.split("\n\n").collect();
+
+        assert_eq!(sections.len(), 2);
+        assert!(sections[0].contains("hootie-and-the-blowfish"));
+        assert!(sections[0].contains("(1.0.0)"));
+        assert_eq!(sections[0].lines().count(), 5);
+        assert!(sections[1].contains("challenger-b"));
+        assert!(sections[1].contains("(2.0.0)"));
+        assert_eq!(sections[1].lines().count(), 4);
+
+        // a single challenger renders just its table
+        assert_eq!(
+            metric_compare.render_compare_reports(&compare_reports[..1]),
+            metric_compare.build_compare_metric_table(&compare_reports[0])
+        );
+    }
+
+    #[test]
+    fn test_format_delta() {
+        assert_eq!(
+            format_delta(&Value::from(0.8), &Value::from(0.85)).unwrap(),
+            "+0.05"
+        );
+        assert_eq!(
+            format_delta(&Value::from(10), &Value::from(7.5)).unwrap(),
+            "-2.5"
+        );
+        assert_eq!(
+            format_delta(&Value::from(1), &Value::from(1)).unwrap(),
+            "+0"
+        );
+        assert!(format_delta(&Value::from("a"), &Value::from(1)).is_none());
+    }
+
+    #[test]
+    fn test_select_metric_rows() {
+        let rows = || {
+            [
+                ("mae", Value::from(5)),
+                ("rmse", Value::from("7.5")),
+                ("note", Value::from("n/a")),
+                ("mape", Value::from(10.0)),
+                ("r2", Value::from(5.0)),
+            ]
+            .into_iter()
+            .map(|(metric, value)| types::MetricTable {
+                metric: metric.to_string(),
+                value,
+                step: "None".to_string(),
+                timestamp: "None".to_string(),
+            })
+            .collect::<Vec<_>>()
+        };
+        let names = |rows: &[types::MetricTable]| {
+            rows.iter()
+                .map(|row| row.metric.as_str())
+                .collect::<Vec<_>>()
+                .join(",")
+        };
+
+        // largest first, ties keep server order, non-numeric values last
+        let mut selected = rows();
+        select_metric_rows(
+            &mut selected,
+            &MetricSelection {
+                sort: Some(MetricSortKey::Value),
+                ..Default::default()
+            },
+        );
+        assert_eq!(names(&selected), "mape,rmse,mae,r2,note");
+
+        let mut selected = rows();
+        select_metric_rows(
+            &mut selected,
+            &MetricSelection {
+                sort: Some(MetricSortKey::Value),
+                ascending: true,
+                top: Some(3),
+            },
+        );
+        assert_eq!(names(&selected), "mae,r2,rmse");
+
+        let mut selected = rows();
+        select_metric_rows(
+            &mut selected,
+            &MetricSelection {
+                sort: Some(MetricSortKey::Name),
+                ascending: true,
+                top: Some(2),
+            },
+        );
+        assert_eq!(names(&selected), "mae,mape");
+
+        // names sort A to Z without --ascending
+        let mut selected = rows();
+        select_metric_rows(
+            &mut selected,
+            &MetricSelection {
+                sort: Some(MetricSortKey::Name),
+                ..Default::default()
+            },
+        );
+        assert_eq!(names(&selected), "mae,mape,note,r2,rmse");
+
+        // top without sort keeps server order
+        let mut selected = rows();
+        select_metric_rows(
+            &mut selected,
+            &MetricSelection {
+                top: Some(10),
+                ..Default::default()
+            },
+        );
+        assert_eq!(names(&selected), "mae,rmse,note,mape,r2");
+    }
+
+    #[test]
+    fn test_parse_metric_response_keeps_server_order() {
+        // names are out of alphabetical order so a hashed map would reorder them
+        let response = r#"{"metrics": {
+            "rmse": [{"name": "rmse", "value": 5, "step": null, "timestamp": null}],
+            "mae": [{"name": "mae", "value": 5, "step": null, "timestamp": null}],
+            "r2": [{"name": "r2", "value": 5, "step": null, "timestamp": null}],
+            "auc": [{"name": "auc", "value": 1, "step": null, "timestamp": null}]
+        }}"#;
+        let render = |selection: MetricSelection| {
+            let metric_getter = MetricGetter {
+                table_style: utils::TableStyle::Markdown,
+                output: output::OutputOptions::default(),
+                color: false,
+                selection,
+                pivot: false,
+            };
+            let table = metric_getter.parse_metric_response(response);
+            table
+                .lines()
+                .skip(2)
+                .map(|line| line.split('|').nth(1).unwrap().trim().to_string())
+                .collect::<Vec<_>>()
+                .join(",")
+        };
+
+        // ties keep server order
+        for _ in 0..5 {
+            assert_eq!(
+                render(MetricSelection {
+                    sort: Some(MetricSortKey::Value),
+                    ..Default::default()
+                }),
+                "rmse,mae,r2,auc"
+            );
+        }
+
+        // top without sort keeps server order
+        assert_eq!(
+            render(MetricSelection {
+                top: Some(2),
+                ..Default::default()
+            }),
+            "rmse,mae"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_read_ndjson_metrics() {
+        let _env = TestEnv::lock();
+        let mut server = mockito::Server::new();
+        let url = server.url();
+        env::set_var("OPSML_TRACKING_URI", url);
+
+        let metrics = vec![
+            types::Metric {
+                name: "mae".to_string(),
+                value: Value::from(1.5),
+                step: Some(Value::from(0)),
+                timestamp: None,
+            },
+            types::Metric {
+                name: "mae".to_string(),
+                value: Value::from(1.25),
+                step: Some(Value::from(1)),
+                timestamp: None,
+            },
+            types::Metric {
+                name: "rmse".to_string(),
+                value: Value::from(3.0),
+                step: Some(Value::from(0)),
+                timestamp: Some(Value::from(1700000000)),
+            },
+        ];
+        let ndjson: String = metrics
+            .iter()
+            .map(|metric| format!("{}\n", serde_json::to_string(metric).unwrap()))
+            .collect();
+
+        // chunks split lines mid-way, and the last line has no trailing newline
+        let mock_get_metrics = server
+            .mock("POST", "/opsml/models/metrics")
+            .with_status(200)
+            .with_header("content-type", "application/x-ndjson")
+            .with_chunked_body(move |w| {
+                let body = ndjson.trim_end().as_bytes();
+                for chunk in body.chunks(7) {
+                    w.write_all(chunk)?;
+                    w.flush()?;
+                }
+                Ok(())
+            })
+            .create();
+
+        let metric_getter = MetricGetter {
+            table_style: utils::TableStyle::Markdown,
+            output: output::OutputOptions::default(),
+            color: false,
+            selection: MetricSelection {
+                sort: Some(MetricSortKey::Name),
+                ascending: true,
+                top: None,
+            },
+            pivot: false,
+        };
+
+        let response = metric_getter
+            .send_metric_request(Some("fake"), None, Some("1.0.0"), None)
+            .await
+            .unwrap();
+        assert!(is_ndjson(&response));
+        let streamed = read_ndjson_metrics(response).await.unwrap();
+        mock_get_metrics.assert();
+        assert_eq!(streamed.len(), 3);
+
+        let mut grouped: IndexMap<String, Vec<types::Metric>> = IndexMap::new();
+        for metric in metrics {
+            grouped.entry(metric.name.clone()).or_default().push(metric);
+        }
+        let buffered =
+            serde_json::to_string(&types::ListMetricResponse { metrics: grouped }).unwrap();
+
+        assert_eq!(
+            metric_getter.render_metric_rows(streamed.iter().map(metric_row).collect()),
+            metric_getter.parse_metric_response(&buffered)
+        );
+    }
+
+    #[test]
+    fn test_pivot_metric_table() {
+        let metric_getter = MetricGetter {
+            table_style: utils::TableStyle::Markdown,
+            output: output::OutputOptions::default(),
+            color: false,
+            selection: MetricSelection::default(),
+            pivot: true,
+        };
+
+        // rmse was not logged at step 1
+        let response = serde_json::json!({
+            "metrics": {
+                "rmse": [
+                    {"name": "rmse", "value": 3.5, "step": 2, "timestamp": null},
+                    {"name": "rmse", "value": 3.0, "step": 0, "timestamp": null},
+                ],
+                "mae": [
+                    {"name": "mae", "value": 1.5, "step": 0, "timestamp": null},
+                    {"name": "mae", "value": 1.25, "step": 1, "timestamp": null},
+                    {"name": "mae", "value": 1.0, "step": 2, "timestamp": null},
+                ],
+            }
+        });
+
+        assert_eq!(
+            metric_getter.parse_metric_response(&response.to_string()),
+            [
+                "| Metric | Step 0 | Step 1 | Step 2 |",
+                "|--------|--------|--------|--------|",
+                "|  mae   |  1.5   |  1.25  |  1.0   |",
+                "|  rmse  |  3.0   |   -    |  3.5   |",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_metrics_empty_response() {
+        let _env = TestEnv::lock();
+        let mut server = mockito::Server::new();
+        let url = server.url();
+        env::set_var("OPSML_TRACKING_URI", url);
+
+        let mock_get_metrics = server
+            .mock("POST", "/opsml/models/metrics")
+            .with_status(204)
+            .create();
+
+        let metric_getter = MetricGetter {
+            table_style: utils::TableStyle::Sharp,
+            output: output::OutputOptions::default(),
+            color: true,
+            selection: MetricSelection::default(),
+            pivot: false,
+        };
+        output::capture();
+        metric_getter
+            .get_model_metrics(Some("fake"), None, Some("1.0.0"), None)
+            .await
+            .unwrap();
+
+        mock_get_metrics.assert();
+        assert_eq!(output::take_captured(), "No metrics found\n");
+    }
+
+    #[tokio::test]
+    async fn test_get_metrics_repository() {
+        let _env = TestEnv::lock();
+        let mut server = mockito::Server::new();
+        let url = server.url();
+        env::set_var("OPSML_TRACKING_URI", url);
+
+        let mock_get_metrics = server
+            .mock("POST", "/opsml/models/metrics")
+            .match_body(mockito::Matcher::Json(serde_json::json!({
+                "name": "fake",
+                "repository": "team-a",
+                "version": "1.0.0",
+                "uid": null
+            })))
+            .with_status(204)
+            .create();
+
+        let metric_getter = MetricGetter {
+            table_style: utils::TableStyle::Sharp,
+            output: output::OutputOptions::default(),
+            color: true,
+            selection: MetricSelection::default(),
+            pivot: false,
+        };
+        metric_getter
+            .get_model_metrics(Some("fake"), Some("team-a"), Some("1.0.0"), None)
+            .await
+            .unwrap();
+
+        mock_get_metrics.assert();
+    }
+
+    #[tokio::test]
+    async fn test_compare_metrics_empty_response() {
+        let _env = TestEnv::lock();
+        let mut server = mockito::Server::new();
+        let url = server.url();
+        env::set_var("OPSML_TRACKING_URI", url);
+
+        let mock_compare_metrics = server
+            .mock("POST", "/opsml/models/compare_metrics")
+            .with_status(204)
+            .create();
+
+        let metric_compare = MetricGetter {
+            table_style: utils::TableStyle::Sharp,
+            output: output::OutputOptions::default(),
+            color: true,
+            selection: MetricSelection::default(),
+            pivot: false,
+        };
+        output::capture();
+        metric_compare
+            .compare_model_metrics(
+                &vec!["mae".to_string()],
+                &vec![true],
+                &["uid".to_string()],
+                &vec!["uid".to_string()],
+            )
+            .await
+            .unwrap();
+
+        mock_compare_metrics.assert();
+        assert_eq!(output::take_captured(), "No metrics found\n");
+    }
+
+    #[tokio::test]
+    async fn test_metrics_raw() {
+        let _env = TestEnv::lock();
+        let mut server = mockito::Server::new();
+        let url = server.url();
+        env::set_var("OPSML_TRACKING_URI", url);
+
+        // not valid JSON, so any parsing would fail
+        let body = "{\"metrics\": {\"mae\": [{\"name\": \"mae\", \"value\": 5}]}, trailing";
+        let mock_get_metrics = server
+            .mock("POST", "/opsml/models/metrics")
+            .with_status(200)
+            .with_body(body)
+            .expect(2)
+            .create();
+        let mock_compare_metrics = server
+            .mock("POST", "/opsml/models/compare_metrics")
+            .with_status(200)
+            .with_body(body)
+            .expect(2)
+            .create();
+
+        let metric_getter = MetricGetter {
+            table_style: utils::TableStyle::Sharp,
+            output: output::OutputOptions {
+                raw: true,
+                ..Default::default()
+            },
+            color: true,
+            selection: MetricSelection::default(),
+            pivot: false,
+        };
+
+        let metrics = metric_getter
+            .send_metric_request(Some("fake"), None, Some("1.0.0"), None)
+            .await
+            .unwrap()
+            .text()
+            .await
+            .unwrap();
+        assert_eq!(metrics, body);
+        metric_getter
+            .get_model_metrics(Some("fake"), None, Some("1.0.0"), None)
+            .await
+            .unwrap();
+
+        let compare = metric_getter
+            .request_compare_body(
+                &vec!["mae".to_string()],
+                &vec![true],
+                "uid",
+                &vec!["uid".to_string()],
+            )
+            .await
+            .unwrap();
+        assert_eq!(compare, body);
+        metric_getter
+            .compare_model_metrics(
+                &vec!["mae".to_string()],
+                &vec![true],
+                &["uid".to_string()],
+                &vec!["uid".to_string()],
+            )
+            .await
+            .unwrap();
+
+        mock_get_metrics.assert();
+        mock_compare_metrics.assert();
+    }
+
     #[test]
     fn test_parse_compare_metric_response() {
         let challenger_metric = types::Metric {
@@ -1,11 +1,25 @@
 /// Copyright (c) Shipt, Inc.
 /// This source code is licensed under the MIT license found in the
 /// LICENSE file in the root directory of this source tree.
+pub mod cache;
 pub mod cards;
+pub mod checksum;
 pub mod cli;
 pub mod commands;
+pub mod config;
+pub mod diff;
+pub mod doctor;
+pub mod format;
+#[cfg(feature = "python")]
+pub mod launch_app;
 pub mod metrics;
 pub mod model;
+pub mod output;
 pub mod route_helper;
+pub mod schema;
+pub mod sync;
+#[cfg(test)]
+mod test_utils;
 pub mod types;
 pub mod utils;
+pub mod validate;
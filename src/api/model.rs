@@ -1,20 +1,82 @@
 /// Copyright (c) Shipt, Inc.
 /// This source code is licensed under the MIT license found in the
 /// LICENSE file in the root directory of this source tree.
+use crate::api::cards::CardLister;
+use crate::api::checksum;
+use crate::api::commands::DownloadModelArgs;
+use crate::api::format;
+use crate::api::output;
 use crate::api::route_helper::RouteHelper;
 use crate::api::types;
 use crate::api::utils;
 use anyhow::{Context, Result};
+use clap::ValueEnum;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use futures::future::join_all;
+use futures::stream::{self, StreamExt};
+use globset::{Glob, GlobSet, GlobSetBuilder};
 use owo_colors::OwoColorize;
+use reqwest::StatusCode;
+use semver::Version;
 use serde_json;
+use std::collections::HashMap;
 use std::path::PathBuf;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use std::{fs, path::Path};
+use tabled::{settings::Alignment, Table};
 use tokio;
+use tokio::io::AsyncWrite;
 
 const MODEL_METADATA_FILE: &str = "model-metadata.json";
+const MODEL_METADATA_YAML_FILE: &str = "model-metadata.yaml";
+const MANIFEST_FILE: &str = "manifest.json";
+const CHECKSUMS_FILE: &str = "SHA256SUMS";
 const NO_ONNX_URI: &str = "No onnx model uri found but onnx flag set to true";
+pub const DEFAULT_BATCH_SIZE: usize = 50;
+/// Concurrency picked by `--auto-concurrency` for files averaging under 1 MB, 100 MB and 1 GB
+const AUTO_CONCURRENCY: [(u64, usize); 3] = [(1_000_000, 16), (100_000_000, 8), (1_000_000_000, 4)];
+/// Concurrency picked by `--auto-concurrency` for larger files
+const AUTO_CONCURRENCY_LARGE: usize = 2;
+/// Concurrency picked by `--auto-concurrency` when the server reports no file sizes
+const AUTO_CONCURRENCY_UNKNOWN: usize = 4;
+pub const DEFAULT_MAX_DOWNLOADS: usize = 20;
 const NO_QUANTIZE_URI: &str = "No quantize model uri found but quantize flag set to true";
 
+/// File format model metadata is written in
+#[derive(Clone, Copy, Debug, Default, PartialEq, ValueEnum)]
+pub enum MetadataFormat {
+    #[default]
+    Json,
+    Yaml,
+}
+
+impl MetadataFormat {
+    /// Name of the metadata file, e.g. `model-metadata.json`
+    pub fn filename(&self) -> &'static str {
+        match self {
+            MetadataFormat::Json => MODEL_METADATA_FILE,
+            MetadataFormat::Yaml => MODEL_METADATA_YAML_FILE,
+        }
+    }
+
+    /// Serializes metadata in this format
+    ///
+    /// # Arguments
+    ///
+    /// * `metadata` - Metadata to serialize
+    ///
+    pub fn serialize(&self, metadata: &types::ModelMetadata) -> Result<String, anyhow::Error> {
+        match self {
+            MetadataFormat::Json => {
+                serde_json::to_string(metadata).with_context(|| "Failed to serialize metadata")
+            }
+            MetadataFormat::Yaml => serde_yaml::to_string(metadata)
+                .with_context(|| "Failed to serialize metadata to yaml"),
+        }
+    }
+}
+
 pub struct ModelDownloader<'a> {
     pub name: Option<&'a str>,
     pub version: Option<&'a str>,
@@ -25,31 +87,381 @@ pub struct ModelDownloader<'a> {
     pub onnx: &'a bool,
     pub quantize: &'a bool,
     pub preprocessor: &'a bool,
+    pub manifest: &'a bool,
+    pub archive: Option<&'a str>,
+    pub file_filter: FileFilter,
+    pub check_space: &'a bool,
+    pub report: Option<utils::TableStyle>,
+    pub batch_size: usize,
+    pub metadata_format: MetadataFormat,
+    pub write_checksums: bool,
+    pub resume: bool,
+    pub auto_concurrency: bool,
+    pub write_metadata: bool,
+}
+
+/// Outcome of downloading one model out of several
+pub struct ModelDownloadResult {
+    pub model: String,
+    pub outcome: Result<u64, anyhow::Error>,
+    pub duration: Duration,
+}
+
+/// Remote file to download and its path relative to the download directory
+struct ModelFile {
+    remote_path: String,
+    path: PathBuf,
+}
+
+/// Glob filter applied to model files before they are downloaded
+///
+/// Patterns are matched against the file path relative to the model directory.
+/// A file is downloaded when it matches an include pattern (or no include patterns
+/// are set) and matches no exclude pattern, so exclude wins on conflicts.
+/// When `only` names are set, a file must also be one of the named files.
+#[derive(Clone, Debug, Default)]
+pub struct FileFilter {
+    include: Option<GlobSet>,
+    exclude: Option<GlobSet>,
+    only: Vec<String>,
+}
+
+impl FileFilter {
+    /// Builds a filter from include and exclude glob patterns
+    ///
+    /// # Arguments
+    ///
+    /// * `include` - Patterns of files to download
+    /// * `exclude` - Patterns of files to skip
+    ///
+    /// # Returns
+    /// * `Result<FileFilter, String>` - File filter
+    ///
+    pub fn new(include: &[String], exclude: &[String]) -> Result<Self, anyhow::Error> {
+        Ok(FileFilter {
+            include: FileFilter::build_globset(include)?,
+            exclude: FileFilter::build_globset(exclude)?,
+            only: Vec::new(),
+        })
+    }
+
+    /// Restricts the filter to the named files
+    ///
+    /// # Arguments
+    ///
+    /// * `only` - File names, or paths relative to the model directory
+    ///
+    pub fn with_only(self, only: &[String]) -> Self {
+        FileFilter {
+            only: only.to_vec(),
+            ..self
+        }
+    }
+
+    /// Names passed to `with_only` that match none of the given files
+    ///
+    /// # Arguments
+    ///
+    /// * `paths` - Paths of the files relative to the model directory
+    ///
+    fn unmatched_only(&self, paths: &[&Path]) -> Vec<&str> {
+        self.only
+            .iter()
+            .filter(|name| !paths.iter().any(|path| path.ends_with(name)))
+            .map(|name| name.as_str())
+            .collect()
+    }
+
+    fn build_globset(patterns: &[String]) -> Result<Option<GlobSet>, anyhow::Error> {
+        if patterns.is_empty() {
+            return Ok(None);
+        }
+
+        let mut builder = GlobSetBuilder::new();
+        for pattern in patterns.iter() {
+            builder.add(
+                Glob::new(pattern).with_context(|| format!("Invalid glob pattern {}", pattern))?,
+            );
+        }
+
+        Ok(Some(
+            builder
+                .build()
+                .with_context(|| "Failed to build glob patterns")?,
+        ))
+    }
+
+    /// Whether a file should be downloaded
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path of the file relative to the model directory
+    ///
+    fn is_match(&self, path: &Path) -> bool {
+        let included = self
+            .include
+            .as_ref()
+            .is_none_or(|globs| globs.is_match(path));
+        let excluded = self
+            .exclude
+            .as_ref()
+            .is_some_and(|globs| globs.is_match(path));
+        let named = self.only.is_empty() || self.only.iter().any(|name| path.ends_with(name));
+
+        included && !excluded && named
+    }
+}
+
+/// Gzipped tarball that downloaded files are written into
+///
+/// Files are downloaded into a staging directory next to the archive, and each batch is
+/// appended and removed from the staging directory once it completes, so at most one batch
+/// is held on disk at a time. An archive dropped before `finish` removes both the partial
+/// tarball and the staging directory, so a failed download leaves nothing behind
+struct ModelArchive {
+    builder: Option<tar::Builder<GzEncoder<fs::File>>>,
+    path: PathBuf,
+    staging_dir: PathBuf,
+}
+
+impl ModelArchive {
+    /// Creates an archive at the given path
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path of the tar.gz archive
+    ///
+    fn create(path: &Path) -> Result<Self, anyhow::Error> {
+        utils::create_dir_path(path)?;
+        let file = fs::File::create(path)
+            .with_context(|| format!("Unable to create archive {:?}", path))?;
+
+        let mut staging_dir = path.as_os_str().to_owned();
+        staging_dir.push(".partial");
+
+        Ok(ModelArchive {
+            builder: Some(tar::Builder::new(GzEncoder::new(
+                file,
+                Compression::default(),
+            ))),
+            path: path.to_path_buf(),
+            staging_dir: PathBuf::from(staging_dir),
+        })
+    }
+
+    /// Builder of an archive that has not been finished
+    fn builder(&mut self) -> &mut tar::Builder<GzEncoder<fs::File>> {
+        self.builder.as_mut().expect("archive is already finished")
+    }
+
+    /// Appends a downloaded file to the archive and removes it from the staging directory
+    ///
+    /// # Arguments
+    ///
+    /// * `lpath` - Path of the downloaded file
+    /// * `name` - Path of the file within the archive
+    ///
+    fn append_file(&mut self, lpath: &Path, name: &Path) -> Result<(), anyhow::Error> {
+        self.builder()
+            .append_path_with_name(lpath, name)
+            .with_context(|| format!("Unable to add {:?} to archive", name))?;
+        fs::remove_file(lpath).with_context(|| format!("Unable to remove {:?}", lpath))?;
+        Ok(())
+    }
+
+    /// Appends in-memory contents to the archive
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Path of the file within the archive
+    /// * `contents` - File contents
+    ///
+    fn append_bytes(&mut self, name: &str, contents: &[u8]) -> Result<(), anyhow::Error> {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(contents.len() as u64);
+        header.set_mode(0o644);
+        header.set_mtime(
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .with_context(|| "Failed to get current time")?
+                .as_secs(),
+        );
+
+        self.builder()
+            .append_data(&mut header, name, contents)
+            .with_context(|| format!("Unable to add {} to archive", name))?;
+        Ok(())
+    }
+
+    /// Writes the archive footer, flushes compression and removes the staging directory
+    fn finish(mut self) -> Result<(), anyhow::Error> {
+        self.builder
+            .take()
+            .expect("archive is already finished")
+            .into_inner()
+            .and_then(|encoder| encoder.finish())
+            .with_context(|| "Unable to write archive")?;
+
+        if self.staging_dir.exists() {
+            fs::remove_dir_all(&self.staging_dir)
+                .with_context(|| "Unable to remove archive staging directory")?;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for ModelArchive {
+    fn drop(&mut self) {
+        // finished archives have given up their builder
+        if let Some(builder) = self.builder.take() {
+            // close the file before removing it
+            drop(builder);
+            let _ = fs::remove_file(&self.path);
+            let _ = fs::remove_dir_all(&self.staging_dir);
+        }
+    }
 }
 
 impl ModelDownloader<'_> {
-    /// Saves metadata to json
+    /// Saves metadata to the write directory in the configured metadata format
     ///
     /// # Arguments
     ///
     /// * `metadata` - metadata to save
-    /// * `path` - path to save to
     ///
     /// # Returns
-    /// * `Result<(), String>` - Result of file download
+    /// * `Result<PathBuf, String>` - Path the metadata was written to
     ///
-    async fn save_metadata_to_json(
+    async fn save_metadata(
         &self,
         metadata: &types::ModelMetadata,
-        path: &Path,
+    ) -> Result<PathBuf, anyhow::Error> {
+        let path = Path::new(&self.write_dir).join(self.metadata_format.filename());
+        let contents = self.metadata_format.serialize(metadata)?;
+        utils::create_dir_path(&path)?;
+        fs::write(&path, contents).with_context(|| "Unable to write metadata file")?;
+        Ok(path)
+    }
+
+    /// Resolves the uid of the downloaded model
+    ///
+    /// Metadata does not include the uid, so a model requested by name and version is
+    /// looked up in the model registry
+    ///
+    /// # Arguments
+    ///
+    /// * `model_metadata` - Model metadata used to resolve name and version
+    ///
+    /// # Returns
+    /// * `Result<Option<String>, String>` - Uid of the model, None when no card matches
+    ///
+    async fn resolve_uid(
+        &self,
+        model_metadata: &types::ModelMetadata,
+    ) -> Result<Option<String>, anyhow::Error> {
+        if let Some(uid) = self.uid {
+            return Ok(Some(uid.to_string()));
+        }
+
+        let card_lister = CardLister {
+            registry_type: "model",
+            name: Some(&model_metadata.model_name),
+            repository: Some(&model_metadata.model_repository),
+            version: Some(&model_metadata.model_version),
+            uid: None,
+            limit: None,
+            tags: HashMap::new(),
+            max_date: None,
+            min_date: None,
+            name_contains: None,
+            ignore_release_candidates: &false,
+            all_versions: &false,
+            table_style: utils::TableStyle::default(),
+            output: output::OutputOptions::default(),
+            group_by: None,
+            columns: None,
+            template: None,
+        };
+        let cards = card_lister
+            .get_card_response()
+            .await
+            .with_context(|| "Failed to resolve model uid")?;
+
+        Ok(cards.cards.into_iter().next().map(|card| card.uid))
+    }
+
+    /// Writes a manifest of downloaded files to the write directory
+    ///
+    /// # Arguments
+    ///
+    /// * `model_metadata` - Model metadata used to resolve name and version
+    /// * `uid` - Resolved uid of the model
+    /// * `files` - Files that were downloaded
+    ///
+    /// # Returns
+    /// * `Result<(), String>` - Result of writing the manifest
+    ///
+    fn save_manifest(
+        &self,
+        model_metadata: &types::ModelMetadata,
+        uid: Option<String>,
+        files: Vec<types::ManifestFile>,
+        archive: Option<&mut ModelArchive>,
     ) -> Result<(), anyhow::Error> {
-        let json_string =
-            serde_json::to_string(metadata).with_context(|| "Failed to serialize metadata")?;
-        fs::File::create(path).with_context(|| "Unable to create metadata file")?;
-        fs::write(path, json_string).with_context(|| "Unable to write metadata file")?;
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .with_context(|| "Failed to get current time")?
+            .as_secs();
+
+        let manifest = types::DownloadManifest {
+            name: model_metadata.model_name.clone(),
+            version: model_metadata.model_version.clone(),
+            uid,
+            timestamp,
+            files,
+        };
+
+        let json_string = serde_json::to_string_pretty(&manifest)
+            .with_context(|| "Failed to serialize manifest")?;
+
+        if let Some(archive) = archive {
+            return archive.append_bytes(MANIFEST_FILE, json_string.as_bytes());
+        }
+
+        let path = Path::new(self.write_dir).join(MANIFEST_FILE);
+        utils::create_dir_path(&path)?;
+        fs::write(&path, json_string).with_context(|| "Unable to write manifest file")?;
         Ok(())
     }
 
+    /// Writes a `sha256sum`-compatible SHA256SUMS file of downloaded files to the write directory
+    ///
+    /// Each line is `<sha256>  <path>`, with paths relative to the write directory
+    ///
+    /// # Arguments
+    ///
+    /// * `files` - Files that were downloaded
+    ///
+    fn save_checksums(&self, files: &[types::ManifestFile]) -> Result<(), anyhow::Error> {
+        let mut contents = String::new();
+        for file in files.iter() {
+            let local_path = Path::new(&file.local_path);
+            let relative_path = local_path
+                .strip_prefix(self.write_dir)
+                .unwrap_or(local_path);
+            let relative_path = relative_path
+                .components()
+                .map(|component| component.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            contents.push_str(&format!("{}  {}\n", file.sha256, relative_path));
+        }
+
+        let path = Path::new(self.write_dir).join(CHECKSUMS_FILE);
+        utils::create_dir_path(&path)?;
+        fs::write(&path, contents).with_context(|| "Unable to write checksums file")
+    }
+
     /// Main function for downloading model metadata
     ///
     /// # Arguments
@@ -60,32 +472,179 @@ impl ModelDownloader<'_> {
     /// * `Result<types::ModelMetadata, String>` - Result of model metadata download
     ///
     async fn get_model_metadata(&self) -> Result<types::ModelMetadata, anyhow::Error> {
-        let save_path = Path::new(&self.write_dir).join(MODEL_METADATA_FILE);
+        let model_metadata = self.fetch_model_metadata().await?;
+        self.save_metadata(&model_metadata).await?;
+
+        Ok(model_metadata)
+    }
+
+    /// Checks args and requests model metadata without writing it to disk
+    ///
+    /// # Returns
+    /// * `Result<types::ModelMetadata, String>` - Model metadata
+    ///
+    pub async fn request_metadata(&self) -> Result<types::ModelMetadata, anyhow::Error> {
+        utils::check_args(self.name, self.repository, self.version, self.uid).await?;
+        self.fetch_model_metadata().await
+    }
+
+    /// Describes the model being downloaded for error messages
+    fn describe(&self) -> String {
+        match self.uid {
+            Some(uid) => format!("uid {}", uid),
+            None => format!(
+                "{} version {}",
+                self.name.unwrap_or_default(),
+                self.version.unwrap_or("latest")
+            ),
+        }
+    }
 
+    /// Requests model metadata from the server without writing it to disk
+    ///
+    /// # Returns
+    /// * `Result<types::ModelMetadata, String>` - Model metadata
+    ///
+    async fn fetch_model_metadata(&self) -> Result<types::ModelMetadata, anyhow::Error> {
         let model_metadata_request = types::ModelMetadataRequest {
-            name: self.name,
-            repository: self.repository,
-            version: self.version,
-            uid: self.uid,
+            name: utils::trim_arg(self.name),
+            repository: utils::trim_arg(self.repository),
+            version: utils::trim_arg(self.version),
+            uid: utils::trim_arg(self.uid),
             ignore_release_candidates: self.ignore_release_candidates,
         };
 
-        let response = RouteHelper::make_post_request(
-            &utils::OpsmlPaths::MetadataDownload.as_str(),
-            &model_metadata_request,
-        )
-        .await?;
+        let spinner = utils::spinner("fetching metadata...");
+        let mut attempt = 0;
+        let mut retry_start: Option<Instant> = None;
+
+        // retry connection failures, rate limiting and transient gateway errors, and 404s
+        // from registries that have not caught up with a registration when asked to
+        let retry_not_found = utils::retry_on_404();
+        let response = loop {
+            let result = RouteHelper::make_post_request(
+                &utils::OpsmlPaths::MetadataDownload.as_str(),
+                &model_metadata_request,
+            )
+            .await;
+
+            if let Some(start) = retry_start.take() {
+                utils::charge_retry(start.elapsed());
+            }
+
+            let retryable = match &result {
+                Ok(response) => match response.status() {
+                    StatusCode::NOT_FOUND => retry_not_found,
+                    status => utils::is_retryable_status(status),
+                },
+                Err(_) => true,
+            };
+
+            if !retryable || attempt >= utils::max_retries() || utils::retry_budget_exhausted() {
+                break result;
+            }
+
+            attempt += 1;
+            retry_start = Some(Instant::now());
+            spinner.set_message(format!(
+                "fetching metadata... retry {}/{}",
+                attempt,
+                utils::max_retries()
+            ));
+
+            utils::wait_before_retry(result.as_ref().ok(), utils::RETRY_DELAY * attempt).await;
+        };
+        spinner.finish_and_clear();
+        let response = response?;
+
+        if !response.status().is_success() {
+            return Err(
+                RouteHelper::response_error(response, "Failed to fetch model metadata").await,
+            );
+        }
 
         let loaded_response = RouteHelper::load_stream_response(response).await?;
-        let model_metadata: types::ModelMetadata = serde_json::from_str(&loaded_response)
-            .with_context(|| "Failed to parse model Metadata")?;
+        parse_model_metadata(&loaded_response, utils::strict())
+    }
 
-        // create save path for metadata
-        utils::create_dir_path(&save_path)?;
-        self.save_metadata_to_json(&model_metadata, &save_path)
-            .await?;
+    /// Selects a single field from model metadata
+    ///
+    /// # Arguments
+    ///
+    /// * `model_metadata` - Model metadata
+    /// * `field` - Field name. Nested fields are selected with dotted paths (e.g. data_schema.data_type)
+    ///
+    /// # Returns
+    /// * `Result<String, String>` - Field value. Strings are returned unquoted, other values as JSON
+    ///
+    fn get_metadata_field(
+        &self,
+        model_metadata: &types::ModelMetadata,
+        field: &str,
+    ) -> Result<String, anyhow::Error> {
+        let mut value =
+            serde_json::to_value(model_metadata).with_context(|| "Failed to serialize metadata")?;
 
-        Ok(model_metadata)
+        for key in field.split('.') {
+            value = match value {
+                serde_json::Value::Object(mut map) => match map.remove(key) {
+                    Some(nested) => nested,
+                    None => {
+                        let available: Vec<String> = map.keys().cloned().collect();
+                        return Err(anyhow::Error::msg(format!(
+                            "Unknown metadata field: {}. Available fields are: {}",
+                            field,
+                            available.join(", ")
+                        )));
+                    }
+                },
+                _ => {
+                    return Err(anyhow::Error::msg(format!(
+                        "Unknown metadata field: {}. {} is not an object",
+                        field, key
+                    )))
+                }
+            };
+        }
+
+        match value {
+            serde_json::Value::String(val) => Ok(val),
+            val => Ok(val.to_string()),
+        }
+    }
+
+    /// Fetches model metadata and prints a single field
+    ///
+    /// # Arguments
+    ///
+    /// * `field` - Field name or dotted path
+    ///
+    async fn print_metadata_field(&self, field: &str) -> Result<String, anyhow::Error> {
+        let model_metadata = self.request_metadata().await?;
+        let value = self.get_metadata_field(&model_metadata, field)?;
+        println!("{}", value);
+
+        Ok(value)
+    }
+
+    /// Fetches model metadata and prints it as JSON without writing to disk
+    ///
+    /// # Arguments
+    ///
+    /// * `output` - Output options controlling compact or pretty JSON
+    ///
+    /// # Returns
+    /// * `Result<String, String>` - Printed metadata
+    ///
+    async fn print_metadata(
+        &self,
+        output: &output::OutputOptions,
+    ) -> Result<String, anyhow::Error> {
+        let model_metadata = self.request_metadata().await?;
+        let json_string = output.render_json(&model_metadata)?;
+        println!("{}", json_string);
+
+        Ok(json_string)
     }
 
     /// Sets model uri (onnx or trained model) depending on boolean
@@ -96,12 +655,12 @@ impl ModelDownloader<'_> {
     /// * `model_metadata` - Model metadata
     ///
     /// # Returns
-    /// * `&Path` - Remote path to file
+    /// * `String` - Remote path to file
     ///
     fn get_model_uri(
         &self,
         model_metadata: &types::ModelMetadata,
-    ) -> Result<PathBuf, anyhow::Error> {
+    ) -> Result<String, anyhow::Error> {
         let uri = if self.onnx == &true {
             if self.quantize == &true {
                 model_metadata
@@ -118,9 +677,7 @@ impl ModelDownloader<'_> {
             model_metadata.model_uri.clone()
         };
 
-        let filepath = std::path::Path::new(&uri);
-
-        Ok(filepath.to_owned())
+        Ok(uri)
     }
 
     /// Gets processor uri
@@ -130,25 +687,15 @@ impl ModelDownloader<'_> {
     /// * `model_metadata` - Model metadata
     ///
     /// # Returns
-    /// * `Option<&Path>` - File path to processor or None
+    /// * `Option<String>` - Remote path to processor or None
     ///
-    fn get_preprocessor_uri(&self, model_metadata: &types::ModelMetadata) -> Option<PathBuf> {
-        let uri = if model_metadata.preprocessor_uri.is_some() {
-            Some(
-                std::path::Path::new(&model_metadata.preprocessor_uri.as_ref().unwrap()).to_owned(),
-            )
-        } else if model_metadata.tokenizer_uri.is_some() {
-            Some(std::path::Path::new(&model_metadata.tokenizer_uri.as_ref().unwrap()).to_owned())
-        } else if model_metadata.feature_extractor_uri.is_some() {
-            Some(
-                std::path::Path::new(&model_metadata.feature_extractor_uri.as_ref().unwrap())
-                    .to_owned(),
-            )
-        } else {
-            None
-        };
-
-        uri.to_owned()
+    fn get_preprocessor_uri(&self, model_metadata: &types::ModelMetadata) -> Option<String> {
+        model_metadata
+            .preprocessor_uri
+            .as_ref()
+            .or(model_metadata.tokenizer_uri.as_ref())
+            .or(model_metadata.feature_extractor_uri.as_ref())
+            .cloned()
     }
 
     /// Downloads metadata
@@ -159,366 +706,4094 @@ impl ModelDownloader<'_> {
     ///
     async fn get_metadata(&self) -> Result<types::ModelMetadata, anyhow::Error> {
         // check args first
-        utils::check_args(self.name, self.repository, self.version, self.uid)
-            .await
-            .unwrap();
+        utils::check_args(self.name, self.repository, self.version, self.uid).await?;
         let model_metadata = self.get_model_metadata().await?;
 
         Ok(model_metadata)
     }
 
-    /// Downloads files associated with a model
+    /// Lists the files under a remote path that pass the file filter
     ///
     /// # Arguments
     ///
-    /// * `rpath` - Remote path to file
+    /// * `rpath` - Remote path to file. Remote paths are always `/`-separated
     ///
     /// # Returns
-    /// * `Result<(), String>` - Result of file download
-    async fn download_files(&self, rpath: &Path) -> Result<(), anyhow::Error> {
+    /// * `Result<Vec<ModelFile>, String>` - Files to download
+    async fn list_model_files(&self, rpath: &str) -> Result<Vec<ModelFile>, anyhow::Error> {
         let rpath_files = RouteHelper::list_files(rpath).await?;
+        let mut model_files = Vec::new();
 
-        // iterate over each file and download
-        for file in rpath_files.files.iter() {
-            let base_path = rpath;
-
-            // check if rpath is a directory
-            let lpath = if rpath.extension().is_none() {
-                // if rpath is a directory, append filename to rpath
-                let path_to_file = Path::new(file)
-                    .strip_prefix(base_path)
-                    .with_context(|| "Failed to create file path")?;
-                Path::new(self.write_dir).join(path_to_file)
-            } else {
-                Path::new(self.write_dir).join(
-                    Path::new(file)
-                        .file_name()
-                        .with_context(|| "Failed to create file path")?,
-                )
-            };
-
-            utils::create_dir_path(&lpath)?;
-            RouteHelper::download_file(&lpath, file).await?;
+        for file in rpath_files.files.into_iter() {
+            let path = utils::remote_to_local_path(rpath, &file)?;
+            if self.file_filter.is_match(&path) {
+                model_files.push(ModelFile {
+                    remote_path: file,
+                    path,
+                });
+            }
         }
 
-        Ok(())
+        Ok(model_files)
     }
 
-    /// Downloads a model file
-    /// Will also download any associated preprocessor files
-    /// Preprocessors can be tokenizer, feature extractor, or preprocessor
-    async fn download_model(&self) -> Result<(), anyhow::Error> {
-        let model_metadata = self.get_metadata().await?;
-
-        // Get preprocessor
-        if self.preprocessor == &true {
-            let preprocessor_rpath = self.get_preprocessor_uri(&model_metadata);
-
-            if preprocessor_rpath.is_some() {
-                let preprocessor_rpath = preprocessor_rpath.unwrap();
-                self.download_files(&preprocessor_rpath).await?;
-            }
-        }
+    /// Gets the size the server reports for each file
+    ///
+    /// # Arguments
+    ///
+    /// * `model_files` - Files to look up
+    ///
+    /// # Returns
+    /// * `Result<Vec<Option<u64>>, String>` - Size of each file, None when the server does not report it
+    ///
+    async fn file_sizes(
+        &self,
+        model_files: &[ModelFile],
+    ) -> Result<Vec<Option<u64>>, anyhow::Error> {
+        join_all(
+            model_files
+                .iter()
+                .map(|file| RouteHelper::head_file(&file.remote_path)),
+        )
+        .await
+        .into_iter()
+        .map(|file_meta| Ok(file_meta?.size))
+        .collect()
+    }
 
-        let model_rpath = self.get_model_uri(&model_metadata)?;
+    /// Checks that the target filesystem has room for all files before downloading
+    ///
+    /// Files whose size the server does not report are not counted
+    ///
+    /// # Arguments
+    ///
+    /// * `file_sizes` - Size of each file that will be downloaded
+    ///
+    fn check_disk_space(&self, file_sizes: &[Option<u64>]) -> Result<(), anyhow::Error> {
+        let required = file_sizes.iter().map(|size| size.unwrap_or(0)).sum();
 
-        // Get model
-        self.download_files(&model_rpath).await?;
+        let target = Path::new(self.archive.unwrap_or(self.write_dir));
+        utils::check_available_space(required, utils::available_space(target)?, target)
+    }
 
-        Ok(())
+    /// Downloads a single file, timing the download
+    ///
+    /// # Arguments
+    ///
+    /// * `file` - File to download
+    /// * `lpath` - Local path to write the file to
+    ///
+    /// # Returns
+    /// * `Result<(types::DownloadedFile, Duration), String>` - Downloaded file and download duration
+    async fn download_timed(
+        &self,
+        file: &ModelFile,
+        lpath: &Path,
+    ) -> Result<(types::DownloadedFile, Duration), anyhow::Error> {
+        let start = Instant::now();
+        let downloaded_file = if self.resume {
+            ModelDownloader::resume_file(file, lpath).await?
+        } else {
+            RouteHelper::download_file(lpath, &file.remote_path).await?
+        };
+        Ok((downloaded_file, start.elapsed()))
     }
-}
 
-/// Downloads model metadata
-///
-/// * `name` - Name of model
-/// * `repository` - repository associated with model
-/// * `version` - Version of model
+    /// Picks up a file left by an interrupted download
+    ///
+    /// A file whose size matches the server's is kept, and a shorter one is resumed from
+    /// its last byte. Anything else, including files the server reports no size for, is
+    /// downloaded again
+    ///
+    /// # Arguments
+    ///
+    /// * `file` - File to download
+    /// * `lpath` - Local path of the file
+    ///
+    /// # Returns
+    /// * `Result<types::DownloadedFile, String>` - Size and checksum of the complete file
+    async fn resume_file(
+        file: &ModelFile,
+        lpath: &Path,
+    ) -> Result<types::DownloadedFile, anyhow::Error> {
+        let Ok(local) = std::fs::metadata(lpath) else {
+            return RouteHelper::download_file(lpath, &file.remote_path).await;
+        };
+
+        match RouteHelper::head_file(&file.remote_path).await?.size {
+            Some(size) if size == local.len() => {
+                if !utils::quiet() {
+                    println!("Skipping complete file: {:?}", lpath);
+                }
+                let mut hasher = checksum::DownloadHasher::new(utils::checksum_algo());
+                hasher.update_file(lpath)?;
+                let (checksum, sha256) = hasher.finalize();
+                Ok(types::DownloadedFile {
+                    size,
+                    checksum,
+                    sha256,
+                })
+            }
+            Some(size) if size > local.len() => {
+                RouteHelper::resume_file(lpath, &file.remote_path).await
+            }
+            _ => RouteHelper::download_file(lpath, &file.remote_path).await,
+        }
+    }
+
+    /// Downloads files associated with a model
+    ///
+    /// Files are downloaded concurrently in batches of `batch_size`, waiting for each
+    /// batch to finish before starting the next
+    ///
+    /// # Arguments
+    ///
+    /// * `model_files` - Files to download
+    /// * `archive` - Optional archive to write files into instead of the write directory
+    /// * `batch_size` - Number of files to download at once
+    ///
+    /// # Returns
+    /// * `Result<Vec<types::ManifestFile>, String>` - Downloaded files
+    async fn download_files(
+        &self,
+        model_files: &[ModelFile],
+        mut archive: Option<&mut ModelArchive>,
+        batch_size: usize,
+    ) -> Result<Vec<types::ManifestFile>, anyhow::Error> {
+        let mut downloaded = Vec::new();
+
+        for batch in model_files.chunks(batch_size.max(1)) {
+            let lpaths: Vec<PathBuf> = batch
+                .iter()
+                .map(|file| match archive.as_deref() {
+                    Some(archive) => archive.staging_dir.join(&file.path),
+                    None => Path::new(self.write_dir).join(&file.path),
+                })
+                .collect();
+
+            for lpath in lpaths.iter() {
+                utils::create_dir_path(lpath)?;
+            }
+
+            let results = join_all(
+                batch
+                    .iter()
+                    .zip(lpaths.iter())
+                    .map(|(file, lpath)| self.download_timed(file, lpath)),
+            )
+            .await;
+
+            for ((file, lpath), result) in batch.iter().zip(lpaths.iter()).zip(results) {
+                let (downloaded_file, duration) = result?;
+
+                let local_path = match archive.as_deref_mut() {
+                    Some(archive) => {
+                        archive.append_file(lpath, &file.path)?;
+                        file.path.to_string_lossy().to_string()
+                    }
+                    None => lpath.to_string_lossy().to_string(),
+                };
+
+                downloaded.push(types::ManifestFile {
+                    remote_path: file.remote_path.clone(),
+                    local_path,
+                    size: downloaded_file.size,
+                    checksum: downloaded_file.checksum,
+                    sha256: downloaded_file.sha256,
+                    duration,
+                });
+            }
+        }
+
+        Ok(downloaded)
+    }
+
+    /// Finds the remote path of a single named model file
+    ///
+    /// # Arguments
+    ///
+    /// * `model_metadata` - Model metadata
+    /// * `file_name` - File name, or path relative to the model directory
+    ///
+    /// # Returns
+    /// * `Result<String, String>` - Remote path of the matching file
+    ///
+    async fn find_model_file(
+        &self,
+        model_metadata: &types::ModelMetadata,
+        file_name: &str,
+    ) -> Result<String, anyhow::Error> {
+        let mut rpaths = vec![self.get_model_uri(model_metadata)?];
+        if self.preprocessor == &true {
+            rpaths.extend(self.get_preprocessor_uri(model_metadata));
+        }
+
+        let mut files = Vec::new();
+        for rpath in rpaths.iter() {
+            files.extend(RouteHelper::list_files(rpath).await?.files);
+        }
+
+        let mut matches: Vec<String> = files
+            .iter()
+            .filter(|file| Path::new(file).ends_with(file_name))
+            .cloned()
+            .collect();
+
+        match matches.len() {
+            0 => Err(anyhow::Error::msg(format!(
+                "No file named {} found. Available files are: {}",
+                file_name,
+                files.join(", ")
+            ))),
+            1 => Ok(matches.remove(0)),
+            _ => Err(anyhow::Error::msg(format!(
+                "Multiple files match {}: {}",
+                file_name,
+                matches.join(", ")
+            ))),
+        }
+    }
+
+    /// Downloads a single named model file to a writer instead of the write directory
+    ///
+    /// # Arguments
+    ///
+    /// * `file_name` - File name, or path relative to the model directory
+    /// * `writer` - Writer to copy the file to
+    ///
+    /// # Returns
+    /// * `Result<types::DownloadedFile, String>` - Size and checksum of the file
+    ///
+    pub async fn stream_model_file<W: AsyncWrite + Unpin>(
+        &self,
+        file_name: &str,
+        writer: &mut W,
+    ) -> Result<types::DownloadedFile, anyhow::Error> {
+        let model_metadata = self.request_metadata().await?;
+        let rpath = self.find_model_file(&model_metadata, file_name).await?;
+        RouteHelper::download_file_to_writer(&rpath, writer).await
+    }
+
+    /// Downloads a model file
+    /// Will also download any associated preprocessor files
+    /// Preprocessors can be tokenizer, feature extractor, or preprocessor
+    /// When an archive path is set, all files are written into a tar.gz archive instead
+    pub async fn download_model(&self) -> Result<(), anyhow::Error> {
+        let model_metadata = self.request_metadata().await?;
+        self.download_model_with_metadata(&model_metadata).await?;
+        Ok(())
+    }
+
+    /// Resolves the urls the model would be downloaded from, without downloading anything
+    ///
+    /// Files are not listed, so each url points at the resolved model or preprocessor uri
+    ///
+    /// # Returns
+    /// * `Result<Vec<String>, String>` - Preprocessor url (when requested), then model url
+    ///
+    pub async fn resolve_download_urls(&self) -> Result<Vec<String>, anyhow::Error> {
+        let model_metadata = self.request_metadata().await?;
+
+        let mut rpaths = Vec::new();
+        if self.preprocessor == &true {
+            rpaths.extend(self.get_preprocessor_uri(&model_metadata));
+        }
+        rpaths.push(self.get_model_uri(&model_metadata)?);
+
+        Ok(rpaths
+            .iter()
+            .map(|rpath| RouteHelper::download_url(rpath))
+            .collect())
+    }
+
+    /// Lists the files the model would be downloaded as, without downloading anything
+    ///
+    /// # Returns
+    /// * `Result<Vec<types::ModelFileInfo>, String>` - Local path, remote path and size of each file
+    ///
+    pub async fn list_files(&self) -> Result<Vec<types::ModelFileInfo>, anyhow::Error> {
+        let model_metadata = self.request_metadata().await?;
+        let model_files = self.resolve_model_files(&model_metadata).await?;
+        let sizes = self.file_sizes(&model_files).await?;
+
+        Ok(model_files
+            .into_iter()
+            .zip(sizes)
+            .map(|(file, size)| types::ModelFileInfo {
+                path: file.path.to_string_lossy().to_string(),
+                remote_path: file.remote_path,
+                size,
+            })
+            .collect())
+    }
+
+    /// Downloads a model and summarizes what was downloaded
+    ///
+    /// # Returns
+    /// * `Result<types::DownloadSummary, String>` - Resolved model, downloaded files and duration
+    ///
+    pub async fn download_model_summary(&self) -> Result<types::DownloadSummary, anyhow::Error> {
+        let start = Instant::now();
+        let model_metadata = self.request_metadata().await?;
+        let files = self.download_model_with_metadata(&model_metadata).await?;
+        let uid = self.resolve_uid(&model_metadata).await?;
+
+        Ok(types::DownloadSummary {
+            name: model_metadata.model_name,
+            version: model_metadata.model_version,
+            uid,
+            write_dir: self.archive.unwrap_or(self.write_dir).to_string(),
+            total_bytes: files.iter().map(|file| file.size).sum(),
+            files,
+            duration_secs: start.elapsed().as_secs_f64(),
+        })
+    }
+
+    /// Lists versions of the model newer than `since_version`
+    ///
+    /// # Arguments
+    ///
+    /// * `since_version` - Semver version to compare against
+    ///
+    /// # Returns
+    /// * `Result<Vec<String>, String>` - Newer versions, oldest first
+    ///
+    async fn list_versions_since(&self, since_version: &str) -> Result<Vec<String>, anyhow::Error> {
+        let since_version = Version::parse(since_version)
+            .with_context(|| format!("Invalid --since-version {}", since_version))?;
+
+        let card_lister = CardLister {
+            registry_type: "model",
+            name: self.name,
+            repository: self.repository,
+            version: None,
+            uid: None,
+            limit: None,
+            tags: HashMap::new(),
+            max_date: None,
+            min_date: None,
+            name_contains: None,
+            ignore_release_candidates: self.ignore_release_candidates,
+            all_versions: &true,
+            table_style: utils::TableStyle::default(),
+            output: output::OutputOptions::default(),
+            group_by: None,
+            columns: None,
+            template: None,
+        };
+        let cards = card_lister.get_card_response().await?;
+
+        Ok(newer_versions(&cards.cards, &since_version))
+    }
+
+    /// Lists the model cards with every given tag
+    ///
+    /// # Arguments
+    ///
+    /// * `tags` - Tag names and values
+    ///
+    /// # Returns
+    /// * `Result<Vec<types::Card>, String>` - Matching cards, in server order
+    ///
+    async fn list_tagged_cards(
+        &self,
+        tags: &[(String, String)],
+    ) -> Result<Vec<types::Card>, anyhow::Error> {
+        let card_lister = CardLister {
+            registry_type: "model",
+            name: self.name,
+            repository: self.repository,
+            version: None,
+            uid: None,
+            limit: None,
+            tags: tags.iter().cloned().collect(),
+            max_date: None,
+            min_date: None,
+            name_contains: None,
+            ignore_release_candidates: self.ignore_release_candidates,
+            all_versions: &false,
+            table_style: utils::TableStyle::default(),
+            output: output::OutputOptions::default(),
+            group_by: None,
+            columns: None,
+            template: None,
+        };
+
+        Ok(card_lister.get_card_response().await?.cards)
+    }
+
+    /// Downloads the model cards with every given tag
+    ///
+    /// A single match, or the first match when `first` is set, is written to the write
+    /// directory. Otherwise each match is written to a subdirectory named after its uid
+    ///
+    /// # Arguments
+    ///
+    /// * `tags` - Tag names and values
+    /// * `first` - Only download the first matching card
+    /// * `max_downloads` - Maximum number of cards to download. 0 for no limit
+    ///
+    pub async fn download_tagged(
+        &self,
+        tags: &[(String, String)],
+        first: bool,
+        max_downloads: usize,
+    ) -> Result<(), anyhow::Error> {
+        let mut cards = self.list_tagged_cards(tags).await?;
+        let described_tags = tags
+            .iter()
+            .map(|(key, value)| format!("{}={}", key, value))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        if cards.is_empty() {
+            return Err(anyhow::Error::msg(format!(
+                "No model cards found with tags {}",
+                described_tags
+            )));
+        }
+
+        if first {
+            cards.truncate(1);
+        }
+        check_max_downloads(cards.len(), max_downloads)?;
+
+        println!(
+            "{}",
+            output::styled(format!(
+                "\nDownloading {} model cards tagged {}",
+                cards.len().to_string().bold().green(),
+                described_tags.bold()
+            ))
+        );
+
+        for card in cards.iter() {
+            let write_dir = match cards.len() {
+                1 => self.write_dir.to_string(),
+                _ => uid_write_dir(self.write_dir, &card.uid)?,
+            };
+            let downloader = ModelDownloader {
+                name: None,
+                version: None,
+                repository: None,
+                uid: Some(&card.uid),
+                write_dir: &write_dir,
+                ignore_release_candidates: self.ignore_release_candidates,
+                onnx: self.onnx,
+                quantize: self.quantize,
+                preprocessor: self.preprocessor,
+                manifest: self.manifest,
+                archive: None,
+                file_filter: self.file_filter.clone(),
+                check_space: self.check_space,
+                report: self.report,
+                batch_size: self.batch_size,
+                metadata_format: self.metadata_format,
+                write_checksums: self.write_checksums,
+                resume: self.resume,
+                auto_concurrency: self.auto_concurrency,
+                write_metadata: self.write_metadata,
+            };
+
+            downloader.download_model().await.with_context(|| {
+                format!(
+                    "Failed to download model {} version {}",
+                    card.name, card.version
+                )
+            })?;
+        }
+
+        Ok(())
+    }
+
+    /// Downloads every version of the model newer than `since_version`
+    ///
+    /// Each version is written to a subdirectory of the write directory named after the version
+    ///
+    /// # Arguments
+    ///
+    /// * `since_version` - Semver version to compare against
+    /// * `max_downloads` - Maximum number of versions to download. 0 for no limit
+    ///
+    pub async fn download_versions_since(
+        &self,
+        since_version: &str,
+        max_downloads: usize,
+    ) -> Result<(), anyhow::Error> {
+        let versions = self.list_versions_since(since_version).await?;
+        check_max_downloads(versions.len(), max_downloads)?;
+
+        if versions.is_empty() {
+            println!(
+                "No versions of {} newer than {}",
+                self.name.unwrap_or_default(),
+                since_version
+            );
+            return Ok(());
+        }
+
+        println!(
+            "{}",
+            output::styled(format!(
+                "\nDownloading {} versions newer than {}",
+                versions.len().to_string().bold().green(),
+                since_version.bold()
+            ))
+        );
+
+        for version in versions.iter() {
+            let write_dir = Path::new(self.write_dir)
+                .join(version)
+                .to_string_lossy()
+                .to_string();
+            let downloader = ModelDownloader {
+                name: self.name,
+                version: Some(version),
+                repository: self.repository,
+                uid: None,
+                write_dir: &write_dir,
+                ignore_release_candidates: self.ignore_release_candidates,
+                onnx: self.onnx,
+                quantize: self.quantize,
+                preprocessor: self.preprocessor,
+                manifest: self.manifest,
+                archive: None,
+                file_filter: self.file_filter.clone(),
+                check_space: self.check_space,
+                report: self.report,
+                batch_size: self.batch_size,
+                metadata_format: self.metadata_format,
+                write_checksums: self.write_checksums,
+                resume: self.resume,
+                auto_concurrency: self.auto_concurrency,
+                write_metadata: self.write_metadata,
+            };
+
+            downloader
+                .download_model()
+                .await
+                .with_context(|| format!("Failed to download model {}", downloader.describe()))?;
+        }
+
+        Ok(())
+    }
+
+    /// Downloads a model, recording the outcome instead of returning an error
+    ///
+    /// # Returns
+    /// * `ModelDownloadResult` - Bytes downloaded or the failure, and the time taken
+    ///
+    async fn download_model_timed(&self) -> ModelDownloadResult {
+        let start = Instant::now();
+        let outcome = async {
+            let model_metadata = self.request_metadata().await?;
+            let files = self.download_model_with_metadata(&model_metadata).await?;
+            Ok(files.iter().map(|file| file.size).sum())
+        }
+        .await;
+
+        ModelDownloadResult {
+            model: self.describe(),
+            outcome,
+            duration: start.elapsed(),
+        }
+    }
+
+    /// Lists the remote files that make up a model, after filtering
+    ///
+    /// # Arguments
+    ///
+    /// * `model_metadata` - Model metadata
+    ///
+    /// # Returns
+    /// * `Result<Vec<ModelFile>, String>` - Preprocessor files (when requested), then model files
+    ///
+    async fn resolve_model_files(
+        &self,
+        model_metadata: &types::ModelMetadata,
+    ) -> Result<Vec<ModelFile>, anyhow::Error> {
+        let mut rpaths = Vec::new();
+        if self.preprocessor == &true {
+            rpaths.extend(self.get_preprocessor_uri(model_metadata));
+        }
+        rpaths.push(self.get_model_uri(model_metadata)?);
+
+        let mut model_files = Vec::new();
+        for rpath in rpaths.iter() {
+            model_files.extend(self.list_model_files(rpath).await?);
+        }
+
+        let paths: Vec<&Path> = model_files.iter().map(|file| file.path.as_path()).collect();
+        let unmatched = self.file_filter.unmatched_only(&paths);
+        if !unmatched.is_empty() {
+            return Err(anyhow::Error::msg(format!(
+                "No file named {} found. Available files are: {}",
+                unmatched.join(", "),
+                paths
+                    .iter()
+                    .map(|path| path.to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )));
+        }
+
+        Ok(model_files)
+    }
+
+    /// Checks a local copy of a model file against the server
+    ///
+    /// # Arguments
+    ///
+    /// * `file` - Remote file
+    ///
+    /// # Returns
+    /// * `Result<Option<String>, String>` - Description of the discrepancy, or None when the file matches
+    ///
+    async fn verify_file(&self, file: &ModelFile) -> Result<Option<String>, anyhow::Error> {
+        let lpath = Path::new(self.write_dir).join(&file.path);
+        let local_size = match fs::metadata(&lpath) {
+            Ok(metadata) if metadata.is_file() => metadata.len(),
+            _ => return Ok(Some(format!("{}: missing", file.path.display()))),
+        };
+
+        let file_meta = RouteHelper::head_file(&file.remote_path).await?;
+
+        if let Some(size) = file_meta.size {
+            if size != local_size {
+                return Ok(Some(format!(
+                    "{}: size mismatch (local {} bytes, server {} bytes)",
+                    file.path.display(),
+                    local_size,
+                    size
+                )));
+            }
+        }
+
+        if let Some(expected) = file_meta.checksum {
+            let checksum = checksum::file_checksum(&lpath, expected.algo)?;
+            if expected.verify(&checksum).is_err() {
+                return Ok(Some(format!(
+                    "{}: checksum mismatch (local {}:{}, server {}:{})",
+                    file.path.display(),
+                    expected.algo.as_str(),
+                    checksum,
+                    expected.algo.as_str(),
+                    expected.digest
+                )));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Checks every model file in `write_dir` against the server without downloading
+    ///
+    /// Files are compared by size and, when the server reports one, checksum
+    ///
+    /// # Returns
+    /// * `Result<Vec<String>, String>` - One entry per missing or mismatched file
+    ///
+    async fn verify_model_files(&self) -> Result<Vec<String>, anyhow::Error> {
+        let model_metadata = self.request_metadata().await?;
+        let model_files = self.resolve_model_files(&model_metadata).await?;
+
+        let results = join_all(model_files.iter().map(|file| self.verify_file(file))).await;
+
+        let mut discrepancies = Vec::new();
+        for result in results.into_iter() {
+            discrepancies.extend(result?);
+        }
+
+        Ok(discrepancies)
+    }
+
+    /// Verifies a previously downloaded model, failing on any discrepancy
+    pub async fn verify_model(&self) -> Result<(), anyhow::Error> {
+        let discrepancies = self.verify_model_files().await?;
+
+        if !discrepancies.is_empty() {
+            return Err(anyhow::Error::msg(format!(
+                "{} file(s) in {} do not match the server:\n  - {}",
+                discrepancies.len(),
+                self.write_dir,
+                discrepancies.join("\n  - ")
+            )));
+        }
+
+        println!(
+            "{}",
+            output::styled(format!(
+                "All model files in {} match the server",
+                self.write_dir.green()
+            ))
+        );
+        Ok(())
+    }
+
+    /// Writes metadata and downloads model files using previously fetched metadata
+    ///
+    /// # Arguments
+    ///
+    /// * `model_metadata` - Model metadata
+    ///
+    /// # Returns
+    /// * `Result<Vec<types::ManifestFile>, String>` - Downloaded files
+    ///
+    pub async fn download_model_with_metadata(
+        &self,
+        model_metadata: &types::ModelMetadata,
+    ) -> Result<Vec<types::ManifestFile>, anyhow::Error> {
+        let model_files = self.resolve_model_files(model_metadata).await?;
+
+        // a single HEAD request per file serves both the space check and the concurrency pick
+        let file_sizes = if self.check_space == &true || self.auto_concurrency {
+            self.file_sizes(&model_files).await?
+        } else {
+            Vec::new()
+        };
+
+        if self.check_space == &true {
+            self.check_disk_space(&file_sizes)?;
+        }
+
+        let mut archive = match self.archive {
+            Some(path) => Some(ModelArchive::create(Path::new(path))?),
+            None => None,
+        };
+
+        if self.write_metadata {
+            match archive.as_mut() {
+                Some(archive) => {
+                    let contents = self.metadata_format.serialize(model_metadata)?;
+                    archive.append_bytes(self.metadata_format.filename(), contents.as_bytes())?;
+                }
+                None => {
+                    self.save_metadata(model_metadata).await?;
+                }
+            }
+        }
+
+        let batch_size = if self.auto_concurrency {
+            let batch_size = auto_batch_size(&file_sizes);
+            if !utils::quiet() {
+                println!(
+                    "Downloading {} files, {} at a time",
+                    model_files.len(),
+                    batch_size
+                );
+            }
+            batch_size
+        } else {
+            self.batch_size
+        };
+        let downloaded = self
+            .download_files(&model_files, archive.as_mut(), batch_size)
+            .await?;
+
+        if let Some(table_style) = self.report {
+            println!("\nDownload Report");
+            println!("{}", build_download_report(&downloaded, table_style));
+        }
+
+        if self.write_checksums {
+            self.save_checksums(&downloaded)?;
+        }
+
+        if self.manifest == &true {
+            let uid = self.resolve_uid(model_metadata).await?;
+            self.save_manifest(model_metadata, uid, downloaded.clone(), archive.as_mut())?;
+        }
+
+        if let Some(archive) = archive {
+            archive.finish()?;
+        }
+
+        Ok(downloaded)
+    }
+}
+
+/// Picks how many files to download at once from the sizes of the files
+///
+/// Many small files are downloaded with high concurrency to hide per-request latency,
+/// while large files get low concurrency so they do not compete for bandwidth
+///
+/// # Arguments
+///
+/// * `sizes` - Size of each file, None when the server does not report it
+///
+/// # Returns
+/// * `usize` - Number of files to download at once, at most the number of files
+///
+fn auto_batch_size(sizes: &[Option<u64>]) -> usize {
+    let known: Vec<u64> = sizes.iter().flatten().copied().collect();
+
+    let concurrency = if known.is_empty() {
+        AUTO_CONCURRENCY_UNKNOWN
+    } else {
+        let average = known.iter().sum::<u64>() / known.len() as u64;
+        AUTO_CONCURRENCY
+            .iter()
+            .find(|(limit, _)| average < *limit)
+            .map_or(AUTO_CONCURRENCY_LARGE, |(_, concurrency)| *concurrency)
+    };
+
+    concurrency.min(sizes.len()).max(1)
+}
+
+/// Guards batch downloads against filters that match more models than intended
+///
+/// # Arguments
+///
+/// * `count` - Number of models the filters resolved to
+/// * `max_downloads` - Maximum number of models to download. 0 for no limit
+///
+pub(crate) fn check_max_downloads(count: usize, max_downloads: usize) -> Result<(), anyhow::Error> {
+    if max_downloads > 0 && count > max_downloads {
+        return Err(anyhow::Error::msg(format!(
+            "Filters matched {} models, more than --max-downloads {}. Narrow the filters, or pass a higher --max-downloads (0 for no limit)",
+            count, max_downloads
+        )));
+    }
+    Ok(())
+}
+
+/// Builds a table of size, duration and throughput for each downloaded file
+///
+/// # Arguments
+///
+/// * `files` - Downloaded files
+/// * `table_style` - Style used to render the table
+///
+/// # Returns
+///  String - Table with one row per file
+///
+fn build_download_report(files: &[types::ManifestFile], table_style: utils::TableStyle) -> String {
+    let report: Vec<types::DownloadReportTable> = files
+        .iter()
+        .map(|file| {
+            let seconds = file.duration.as_secs_f64();
+            let mbps = if seconds > 0.0 {
+                format!("{:.2}", file.size as f64 / 1_000_000.0 / seconds)
+            } else {
+                "-".to_string()
+            };
+
+            types::DownloadReportTable {
+                file: file.local_path.clone(),
+                size: format::format_bytes(file.size),
+                seconds: format!("{:.2}", seconds),
+                mbps,
+            }
+        })
+        .collect();
+
+    let mut table = Table::new(report);
+    table.with(Alignment::center());
+
+    table_style.render(&mut table)
+}
+
+/// Builds a table of the files a model would be downloaded as
+///
+/// # Arguments
+///
+/// * `files` - Files to list
+/// * `table_style` - Style used to render the table
+///
+/// # Returns
+///  String - Table with one row per file
+///
+fn build_file_table(files: &[types::ModelFileInfo], table_style: utils::TableStyle) -> String {
+    let rows: Vec<types::ModelFileTable> = files
+        .iter()
+        .map(|file| types::ModelFileTable {
+            file: file.path.clone(),
+            remote_path: file.remote_path.clone(),
+            size: file
+                .size
+                .map(format::format_bytes)
+                .unwrap_or_else(|| "-".to_string()),
+        })
+        .collect();
+
+    let mut table = Table::new(rows);
+    table.with(Alignment::center());
+
+    table_style.render(&mut table)
+}
+
+/// Selects the versions greater than `since_version`
+///
+/// Versions that are not valid semver are skipped with a warning
+///
+/// # Arguments
+///
+/// * `cards` - Model cards
+/// * `since_version` - Version to compare against
+///
+/// # Returns
+/// * `Vec<String>` - Distinct newer versions, oldest first
+///
+fn newer_versions(cards: &[types::Card], since_version: &Version) -> Vec<String> {
+    let mut versions: Vec<Version> = Vec::new();
+
+    for card in cards.iter() {
+        match Version::parse(&card.version) {
+            Ok(version) if version > *since_version => versions.push(version),
+            Ok(_) => {}
+            Err(_) => eprintln!(
+                "{}",
+                output::styled(
+                    format!(
+                        "Skipping {} version {}: not a semver version",
+                        card.name, card.version
+                    )
+                    .yellow()
+                )
+            ),
+        }
+    }
+
+    versions.sort();
+    versions.dedup();
+    versions.iter().map(|version| version.to_string()).collect()
+}
+
+/// Builds a table of the outcome of each model in a multi-model download
+///
+/// # Arguments
+///
+/// * `results` - Outcome of each model download
+/// * `table_style` - Style used to render the table
+///
+/// # Returns
+///  String - Table with one row per model
+///
+fn build_model_summary(results: &[ModelDownloadResult], table_style: utils::TableStyle) -> String {
+    let summary: Vec<types::ModelSummaryTable> = results
+        .iter()
+        .map(|result| {
+            let (status, bytes, error) = match &result.outcome {
+                Ok(bytes) => ("ok".green().to_string(), *bytes, String::new()),
+                Err(e) => ("failed".red().to_string(), 0, format!("{:#}", e)),
+            };
+
+            types::ModelSummaryTable {
+                model: result.model.clone(),
+                status,
+                size: format::format_bytes(bytes),
+                seconds: format!("{:.2}", result.duration.as_secs_f64()),
+                error,
+            }
+        })
+        .collect();
+
+    let mut table = Table::new(summary);
+    table.with(Alignment::center());
+
+    table_style.render(&mut table)
+}
+
+/// Parses model metadata returned by the server
+///
+/// In strict mode, fields that `types::ModelMetadata` does not model are an error
+/// rather than being silently dropped
+///
+/// # Arguments
+///
+/// * `body` - Metadata JSON
+/// * `strict` - Whether to fail on unknown fields
+///
+/// # Returns
+/// * `Result<types::ModelMetadata, String>` - Model metadata
+///
+fn parse_model_metadata(body: &str, strict: bool) -> Result<types::ModelMetadata, anyhow::Error> {
+    let mut unknown_fields = Vec::new();
+    let deserializer = &mut serde_json::Deserializer::from_str(body);
+    let model_metadata: types::ModelMetadata =
+        serde_ignored::deserialize(deserializer, |path| unknown_fields.push(path.to_string()))
+            .with_context(|| "Failed to parse model Metadata")?;
+
+    if strict && !unknown_fields.is_empty() {
+        return Err(anyhow::Error::msg(format!(
+            "Unknown model metadata fields: {}",
+            unknown_fields.join(", ")
+        )));
+    }
+
+    Ok(model_metadata)
+}
+
+/// Fetches metadata for several models concurrently
+///
+/// Every request is made before any result is inspected, so all invalid models are
+/// reported together rather than one at a time
+///
+/// # Arguments
+///
+/// * `downloaders` - Models to fetch metadata for
+///
+/// # Returns
+/// * `Result<Vec<types::ModelMetadata>, String>` - Metadata in the same order as `downloaders`
+///
+pub async fn prefetch_metadata(
+    downloaders: &[ModelDownloader<'_>],
+) -> Result<Vec<types::ModelMetadata>, anyhow::Error> {
+    let results = join_all(
+        downloaders
+            .iter()
+            .map(|downloader| downloader.request_metadata()),
+    )
+    .await;
+
+    let mut metadata = Vec::new();
+    let mut errors = Vec::new();
+    for (downloader, result) in downloaders.iter().zip(results) {
+        match result {
+            Ok(model_metadata) => metadata.push(model_metadata),
+            Err(e) => errors.push(format!("{}: {:#}", downloader.describe(), e)),
+        }
+    }
+
+    if !errors.is_empty() {
+        return Err(anyhow::Error::msg(format!(
+            "Failed to fetch metadata for {} of {} models:\n  - {}",
+            errors.len(),
+            downloaders.len(),
+            errors.join("\n  - ")
+        )));
+    }
+
+    Ok(metadata)
+}
+
+/// Joins a single directory name returned by the server onto `write_dir`
+///
+/// # Arguments
+///
+/// * `write_dir` - Directory models are written to
+/// * `name` - Directory name, such as a model uid, name or version
+/// * `field` - Card field `name` came from, used in the error message
+///
+/// # Returns
+/// * `Result<PathBuf, String>` - Directory to write the model to
+///
+pub(crate) fn join_dir_name(
+    write_dir: &Path,
+    name: &str,
+    field: &str,
+) -> Result<PathBuf, anyhow::Error> {
+    // a single path component can never point outside of write_dir
+    if name.is_empty() || name == "." || name == ".." || name.contains(['/', '\\']) {
+        return Err(anyhow::Error::msg(format!(
+            "Invalid model {} {:?}: {}s cannot be used as a directory name",
+            field, name, field
+        )));
+    }
+
+    Ok(write_dir.join(name))
+}
+
+/// Builds the subdirectory of `write_dir` a model is written to, named after its uid
+///
+/// # Arguments
+///
+/// * `write_dir` - Directory models are written to
+/// * `uid` - Uid of the model
+///
+/// # Returns
+/// * `Result<String, String>` - Directory to write the model to
+///
+fn uid_write_dir(write_dir: &str, uid: &str) -> Result<String, anyhow::Error> {
+    Ok(join_dir_name(Path::new(write_dir), uid, "uid")?
+        .to_string_lossy()
+        .to_string())
+}
+
+/// Downloads several models concurrently
+///
+/// A failed model does not stop the others. Results are returned in the same order as `downloaders`
+///
+/// # Arguments
+///
+/// * `downloaders` - Models to download
+/// * `jobs` - Maximum number of models downloaded at once
+///
+/// # Returns
+/// * `Vec<ModelDownloadResult>` - Outcome of each model
+///
+async fn download_models_concurrently(
+    downloaders: &[ModelDownloader<'_>],
+    jobs: usize,
+) -> Vec<ModelDownloadResult> {
+    stream::iter(
+        downloaders
+            .iter()
+            .map(|downloader| downloader.download_model_timed()),
+    )
+    .buffered(jobs.max(1))
+    .collect()
+    .await
+}
+
+/// Downloads model metadata
+///
+/// * `name` - Name of model
+/// * `repository` - repository associated with model
+/// * `version` - Version of model
+/// * `uid` - uid of model
+/// * `url` - url of opsml server
+/// * `print` - Optional field to print instead of writing metadata to disk
+/// * `metadata_format` - Format to write metadata in
+#[tokio::main]
+#[allow(clippy::too_many_arguments)]
+pub async fn download_model_metadata(
+    name: Option<&str>,
+    version: Option<&str>,
+    repository: Option<&str>,
+    uid: Option<&str>,
+    write_dir: &str,
+    ignore_release_candidates: &bool,
+    print: Option<&str>,
+    metadata_format: MetadataFormat,
+) -> Result<(), anyhow::Error> {
+    // check args first
+
+    let model_downloader = ModelDownloader {
+        name,
+        version,
+        repository,
+        uid,
+        write_dir,
+        ignore_release_candidates,
+        onnx: &false,
+        quantize: &false,
+        preprocessor: &false,
+        manifest: &false,
+        archive: None,
+        file_filter: FileFilter::default(),
+        check_space: &false,
+        report: None,
+        batch_size: DEFAULT_BATCH_SIZE,
+        metadata_format,
+        write_checksums: false,
+        resume: false,
+        auto_concurrency: false,
+        write_metadata: true,
+    };
+
+    utils::with_shared_client(async {
+        match print {
+            Some(field) => model_downloader
+                .print_metadata_field(field)
+                .await
+                .map(|_| ()),
+            None => model_downloader.get_metadata().await.map(|_| ()),
+        }
+    })
+    .await
+}
+
+/// Prints model metadata to stdout
+///
+/// * `name` - Name of model
+/// * `version` - Version of model
+/// * `repository` - repository associated with model
+/// * `uid` - uid of model
+/// * `ignore_release_candidates` - Flag to ignore release candidates
+/// * `output` - Output options controlling compact or pretty JSON
+#[tokio::main]
+pub async fn model_metadata(
+    name: Option<&str>,
+    version: Option<&str>,
+    repository: Option<&str>,
+    uid: Option<&str>,
+    ignore_release_candidates: &bool,
+    output: output::OutputOptions,
+) -> Result<(), anyhow::Error> {
+    let model_downloader = ModelDownloader {
+        name,
+        version,
+        repository,
+        uid,
+        write_dir: "",
+        ignore_release_candidates,
+        onnx: &false,
+        quantize: &false,
+        preprocessor: &false,
+        manifest: &false,
+        archive: None,
+        file_filter: FileFilter::default(),
+        check_space: &false,
+        report: None,
+        batch_size: DEFAULT_BATCH_SIZE,
+        metadata_format: MetadataFormat::Json,
+        write_checksums: false,
+        resume: false,
+        auto_concurrency: false,
+        write_metadata: true,
+    };
+    utils::with_shared_client(model_downloader.print_metadata(&output)).await?;
+
+    Ok(())
+}
+
+/// Lists the files of a model with their sizes, without downloading them
+///
+/// # Arguments
+///
+/// * `name` - Name of model
+/// * `version` - Version of model
+/// * `repository` - repository associated with model
 /// * `uid` - uid of model
-/// * `url` - url of opsml server
+/// * `onnx` - Flag to list the onnx model files
+/// * `quantize` - Flag to list the quantized onnx model files
+/// * `preprocessor` - Flag to also list preprocessor files
+/// * `ignore_release_candidates` - Flag to ignore release candidates
+/// * `table_style` - Style used to render the table
+/// * `output` - Output format. JSON output prints a list of files instead of a table
+///
 #[tokio::main]
-pub async fn download_model_metadata(
+#[allow(clippy::too_many_arguments)]
+pub async fn list_files(
     name: Option<&str>,
     version: Option<&str>,
     repository: Option<&str>,
     uid: Option<&str>,
-    write_dir: &str,
+    onnx: &bool,
+    quantize: &bool,
+    preprocessor: &bool,
     ignore_release_candidates: &bool,
-) -> Result<types::ModelMetadata, anyhow::Error> {
-    // check args first
+    table_style: utils::TableStyle,
+    output: output::OutputOptions,
+) -> Result<(), anyhow::Error> {
+    let model_downloader = ModelDownloader {
+        name,
+        version,
+        repository,
+        uid,
+        write_dir: "",
+        ignore_release_candidates,
+        onnx,
+        quantize,
+        preprocessor,
+        manifest: &false,
+        archive: None,
+        file_filter: FileFilter::default(),
+        check_space: &false,
+        report: None,
+        batch_size: DEFAULT_BATCH_SIZE,
+        metadata_format: MetadataFormat::Json,
+        write_checksums: false,
+        resume: false,
+        auto_concurrency: false,
+        write_metadata: true,
+    };
+
+    let files = utils::with_shared_client(model_downloader.list_files()).await?;
+    if output.is_json() {
+        output::print(output.render_json_list(&files)?)
+    } else {
+        output::print(build_file_table(&files, table_style))
+    }
+}
+
+/// Downloads model file
+///
+/// # Arguments
+///
+/// * `args` - Download options given on the command line
+/// * `table_style` - Style used to render the download report
+/// * `output` - Output format. JSON output prints a summary of the download instead of progress lines
+///
+#[tokio::main]
+pub async fn download_model(
+    args: &DownloadModelArgs,
+    table_style: utils::TableStyle,
+    output: output::OutputOptions,
+) -> Result<(), anyhow::Error> {
+    let to_stdout = args.file.as_deref().filter(|_| args.to_stdout);
+    let model_downloader = ModelDownloader {
+        name: args.name.as_deref(),
+        version: args.version.as_deref(),
+        repository: args.repository.as_deref(),
+        uid: args.uid.as_deref(),
+        write_dir: &args.write_dir,
+        ignore_release_candidates: &args.ignore_release_candidates,
+        onnx: &args.onnx,
+        quantize: &args.quantize,
+        preprocessor: &args.preprocessor,
+        manifest: &args.manifest,
+        archive: args.archive.as_deref(),
+        file_filter: FileFilter::new(&args.include, &args.exclude)?.with_only(&args.only),
+        check_space: &args.check_space,
+        // the JSON summary already lists each file's size
+        report: (args.report && !output.is_json()).then_some(table_style),
+        batch_size: args.batch_size.into(),
+        metadata_format: MetadataFormat::Json,
+        write_checksums: args.write_checksums,
+        resume: args.resume,
+        auto_concurrency: args.auto_concurrency,
+        write_metadata: !args.no_metadata_file,
+    };
+
+    if output.is_json()
+        && (to_stdout.is_some()
+            || args.verify_only
+            || args.since_version.is_some()
+            || !args.tag.is_empty())
+    {
+        return Err(anyhow::Error::msg(
+            "--output json cannot be combined with --to-stdout, --verify-only, --since-version or --tag",
+        ));
+    }
+
+    // one client for the whole run, so the metadata request and downloads share connections
+    utils::with_shared_client(async {
+        if let Some(file_name) = to_stdout {
+            let mut stdout = tokio::io::stdout();
+            model_downloader
+                .stream_model_file(file_name, &mut stdout)
+                .await?;
+            return Ok(());
+        }
+
+        if args.verify_only {
+            return model_downloader.verify_model().await;
+        }
+
+        if args.resolve_only {
+            let urls = model_downloader.resolve_download_urls().await?;
+            return if output.is_json() {
+                output::print(output.render_json_list(&urls)?)
+            } else {
+                output::print(urls.join("\n"))
+            };
+        }
+
+        if let Some(since_version) = args.since_version.as_deref() {
+            return model_downloader
+                .download_versions_since(since_version, args.max_downloads)
+                .await;
+        }
+
+        if !args.tag.is_empty() {
+            return model_downloader
+                .download_tagged(&args.tag, args.first, args.max_downloads)
+                .await;
+        }
+
+        if output.is_json() {
+            let summary = model_downloader.download_model_summary().await?;
+            return output::print(output.render_json(&summary)?);
+        }
+
+        model_downloader.download_model().await
+    })
+    .await?;
+
+    // nothing was written for the hook to act on
+    if args.resolve_only {
+        return Ok(());
+    }
+
+    match args.post_hook.as_deref() {
+        Some(hook) => run_post_hook(hook, &args.write_dir, output.is_json()),
+        None => Ok(()),
+    }
+}
+
+/// Environment variable holding the write directory while a post-download hook runs
+const HOOK_DIR_VAR: &str = "OPSML_WRITE_DIR";
+
+/// Runs a shell command after a successful download
+///
+/// The write directory is passed in `OPSML_WRITE_DIR` rather than pasted into the command,
+/// so spaces or shell metacharacters in it cannot change what the hook runs
+///
+/// # Arguments
+///
+/// * `hook` - Shell command. `{dir}` is replaced with a quoted reference to `OPSML_WRITE_DIR`
+/// * `write_dir` - Directory the model was written to
+/// * `json` - Whether stdout carries JSON output. The hook's stdout is sent to stderr instead
+///
+fn run_post_hook(hook: &str, write_dir: &str, json: bool) -> Result<(), anyhow::Error> {
+    if !utils::quiet() {
+        println!("Running post-download hook: {} ({})", hook, write_dir);
+    }
+
+    let (shell, flag, dir_ref) = if cfg!(windows) {
+        ("cmd", "/C", format!("\"%{}%\"", HOOK_DIR_VAR))
+    } else {
+        ("sh", "-c", format!("\"${}\"", HOOK_DIR_VAR))
+    };
+    let command = hook.replace("{dir}", &dir_ref);
+    let mut process = std::process::Command::new(shell);
+    process.arg(flag).arg(&command).env(HOOK_DIR_VAR, write_dir);
+    if json {
+        process.stdout(std::io::stderr());
+    }
+    let status = process
+        .status()
+        .with_context(|| format!("Failed to run post-download hook: {}", hook))?;
+
+    if !status.success() {
+        return Err(anyhow::Error::msg(format!(
+            "Post-download hook {} exited with {}",
+            hook, status
+        )));
+    }
+
+    Ok(())
+}
+
+/// Downloads models by uid and prints a summary of each download
+///
+/// Each model is written to a subdirectory of `write_dir` named after its uid
+///
+/// # Arguments
+///
+/// * `uids` - Uids of the models to download
+/// * `write_dir` - directory to write to
+/// * `onnx` - Flag to download onnx models
+/// * `quantize` - Flag to download quantized models
+/// * `preprocessor` - Flag to download preprocessors
+/// * `jobs` - Maximum number of models downloaded at once
+/// * `table_style` - Style used to render the summary
+///
+#[tokio::main]
+pub async fn download_models_by_uid(
+    uids: &[String],
+    write_dir: &str,
+    onnx: &bool,
+    quantize: &bool,
+    preprocessor: &bool,
+    jobs: usize,
+    table_style: utils::TableStyle,
+) -> Result<(), anyhow::Error> {
+    let write_dirs = uids
+        .iter()
+        .map(|uid| uid_write_dir(write_dir, uid))
+        .collect::<Result<Vec<String>, _>>()?;
+    let downloaders: Vec<ModelDownloader> = uids
+        .iter()
+        .zip(write_dirs.iter())
+        .map(|(uid, write_dir)| ModelDownloader {
+            name: None,
+            version: None,
+            repository: None,
+            uid: Some(uid),
+            write_dir,
+            ignore_release_candidates: &false,
+            onnx,
+            quantize,
+            preprocessor,
+            manifest: &false,
+            archive: None,
+            file_filter: FileFilter::default(),
+            check_space: &true,
+            report: None,
+            batch_size: DEFAULT_BATCH_SIZE,
+            metadata_format: MetadataFormat::Json,
+            write_checksums: false,
+            resume: false,
+            auto_concurrency: false,
+            write_metadata: true,
+        })
+        .collect();
+
+    let results = utils::with_shared_client(async {
+        Ok(download_models_concurrently(&downloaders, jobs).await)
+    })
+    .await?;
+
+    println!("\nDownload Summary");
+    println!("{}", build_model_summary(&results, table_style));
+
+    let failed = results
+        .iter()
+        .filter(|result| result.outcome.is_err())
+        .count();
+    if failed > 0 {
+        return Err(anyhow::Error::msg(format!(
+            "Failed to download {} of {} models",
+            failed,
+            results.len()
+        )));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::test_utils::{TestDir, TestEnv};
+    use std::env;
+    use std::fs;
+    use std::fs::File;
+    use std::io::Write;
+    use tokio;
+    use uuid::Uuid;
+
+    /// Downloader with every option off, for tests to override with struct update syntax
+    fn test_downloader() -> ModelDownloader<'static> {
+        ModelDownloader {
+            name: None,
+            version: None,
+            repository: None,
+            uid: None,
+            write_dir: "",
+            ignore_release_candidates: &false,
+            onnx: &false,
+            quantize: &false,
+            preprocessor: &false,
+            manifest: &false,
+            archive: None,
+            file_filter: FileFilter::default(),
+            check_space: &false,
+            report: None,
+            batch_size: DEFAULT_BATCH_SIZE,
+            metadata_format: MetadataFormat::Json,
+            write_checksums: false,
+            resume: false,
+            auto_concurrency: false,
+            write_metadata: true,
+        }
+    }
+
+    /// Mocks the card lookup that resolves the uid of a model downloaded by name and version
+    fn mock_resolve_uid(
+        server: &mut mockito::Server,
+        model_metadata: &types::ModelMetadata,
+        uid: &str,
+    ) -> mockito::Mock {
+        let card = types::Card {
+            name: model_metadata.model_name.clone(),
+            repository: model_metadata.model_repository.clone(),
+            date: None,
+            contact: "fake_email".to_string(),
+            version: model_metadata.model_version.clone(),
+            uid: uid.to_string(),
+            tags: HashMap::new(),
+        };
+
+        server
+            .mock("POST", "/opsml/cards/list")
+            .match_body(mockito::Matcher::PartialJson(serde_json::json!({
+                "registry_type": "model",
+                "name": model_metadata.model_name,
+                "version": model_metadata.model_version,
+            })))
+            .with_status(201)
+            .with_body(
+                serde_json::to_string(&types::ListCardResponse { cards: vec![card] }).unwrap(),
+            )
+            .create()
+    }
+
+    #[tokio::test]
+    async fn test_download_model() {
+        let _env = TestEnv::lock();
+        let uid = &Uuid::new_v4().to_string();
+        // Populate files
+        let test_dir = format!("./src/api/test_utils/{}", uid);
+        std::fs::create_dir_all(&test_dir).unwrap();
+
+        // create fake model file
+        let model_path = Path::new(&test_dir).join("model.onnx");
+        let model_rpath = model_path.to_str().unwrap();
+        let mut file = File::create(&model_path).unwrap();
+        file.write_all(b"model").unwrap();
+
+        // load and write metadata
+        let metadata_path = Path::new(&test_dir).join("metadata.json");
+        let metadata = fs::read_to_string("./src/api/test_utils/metadata.json").unwrap();
+        let mut metadata_file = File::create(metadata_path).unwrap();
+        metadata_file.write_all(metadata.as_bytes()).unwrap();
+
+        let mut model_metadata: types::ModelMetadata = serde_json::from_str(&metadata).unwrap();
+        model_metadata.onnx_uri = Some(model_rpath.to_string());
+
+        // setup server
+        let mut download_server = mockito::Server::new();
+        let url = download_server.url();
+        env::set_var("OPSML_TRACKING_URI", url);
+
+        // get files
+        let files = types::ListFileResponse {
+            files: vec![model_rpath.to_string()],
+        };
+        let file_response = serde_json::to_string(&files).unwrap();
+
+        // directory to write to
+        let new_dir = format!("./src/api/test_utils/{}/{}", uid, "downloaded");
+
+        // mock metadata
+        let mock_metadata_path = download_server
+            .mock("POST", "/opsml/models/metadata")
+            .with_status(201)
+            .with_body(serde_json::to_string(&model_metadata).unwrap())
+            .create();
+
+        // mock list files
+        let artifact_path = format!("/opsml/files/list?path={}", model_rpath);
+        let mock_list_path = download_server
+            .mock("GET", artifact_path.as_str())
+            .with_status(201)
+            .with_body(&file_response)
+            .create();
+
+        // mock model
+        let get_path = format!("/opsml/files/download?path={}", model_rpath);
+        let mock_model_path = download_server
+            .mock("GET", get_path.as_str())
+            .with_status(201)
+            .with_body(&metadata)
+            .create();
+
+        let downloader = ModelDownloader {
+            name: Some("name"),
+            version: Some("version"),
+            repository: Some("repo"),
+            write_dir: &new_dir,
+            onnx: &true,
+            ..test_downloader()
+        };
+
+        let _ = downloader.get_metadata().await.unwrap();
+        mock_metadata_path.assert();
+
+        downloader.download_model().await.unwrap();
+
+        mock_list_path.assert();
+        mock_model_path.assert();
+
+        // clean up
+        fs::remove_dir_all(&test_dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_download_model_shares_client() {
+        let _env = TestEnv::lock();
+        let output_dir = TestDir::new();
+        let test_dir = output_dir.path();
+        let model_rpath = format!("{}/model.onnx", test_dir);
+        let metadata = fs::read_to_string("./src/api/test_utils/metadata.json").unwrap();
+        let mut model_metadata: types::ModelMetadata = serde_json::from_str(&metadata).unwrap();
+        model_metadata.onnx_uri = Some(model_rpath.clone());
+
+        let mut download_server = mockito::Server::new();
+        let url = download_server.url();
+        env::set_var("OPSML_TRACKING_URI", url);
+
+        let mock_metadata = download_server
+            .mock("POST", "/opsml/models/metadata")
+            .with_status(201)
+            .with_body(serde_json::to_string(&model_metadata).unwrap())
+            .create();
+        let mock_list = download_server
+            .mock(
+                "GET",
+                format!("/opsml/files/list?path={}", model_rpath).as_str(),
+            )
+            .with_status(201)
+            .with_body(
+                serde_json::to_string(&types::ListFileResponse {
+                    files: vec![model_rpath.clone()],
+                })
+                .unwrap(),
+            )
+            .create();
+        let mock_model = download_server
+            .mock(
+                "GET",
+                format!("/opsml/files/download?path={}", model_rpath).as_str(),
+            )
+            .with_status(200)
+            .with_body("model")
+            .create();
+
+        let new_dir = format!("{}/downloaded", test_dir);
+        let downloader = ModelDownloader {
+            name: Some("name"),
+            version: Some("version"),
+            write_dir: &new_dir,
+            onnx: &true,
+            check_space: &true,
+            ..test_downloader()
+        };
+
+        // metadata, head, list and download requests all go through the one shared client.
+        // Connection reuse itself is covered by test_shared_client_reuses_connections
+        utils::with_shared_client(downloader.download_model())
+            .await
+            .unwrap();
+
+        mock_metadata.assert();
+        mock_list.assert();
+        mock_model.assert();
+        assert_eq!(
+            fs::read_to_string(Path::new(&new_dir).join("model.onnx")).unwrap(),
+            "model"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_download_processor_model() {
+        let _env = TestEnv::lock();
+        let uid = &Uuid::new_v4().to_string();
+        // Populate files
+        let test_dir = format!("./src/api/test_utils/{}", uid);
+        std::fs::create_dir_all(&test_dir).unwrap();
+
+        // create fake model file
+        let model_path = Path::new(&test_dir).join("trained_model/model.onnx");
+        std::fs::create_dir_all(model_path.parent().unwrap()).unwrap();
+        let model_rpath = model_path.to_str().unwrap();
+        let model_parent = model_path.parent().unwrap().to_str().unwrap();
+        let mut file = File::create(&model_path).unwrap();
+        file.write_all(b"model").unwrap();
+
+        // create fake preprocessor file
+        let preprocessor_path =
+            Path::new(&test_dir).join("preprocessor/nested/preprocessor.joblib");
+        std::fs::create_dir_all(preprocessor_path.parent().unwrap()).unwrap();
+        let preprocessor_rpath = preprocessor_path.to_str().unwrap();
+        let preprocessor_parent = preprocessor_path
+            .parent()
+            .unwrap()
+            .parent()
+            .unwrap()
+            .to_str()
+            .unwrap();
+        let mut file = File::create(&preprocessor_path).unwrap();
+        file.write_all(b"preprocessor").unwrap();
+
+        // load and write metadata
+        let metadata_path = Path::new(&test_dir).join("metadata.json");
+        let metadata = fs::read_to_string("./src/api/test_utils/metadata.json").unwrap();
+        let mut metadata_file = File::create(metadata_path).unwrap();
+        metadata_file.write_all(metadata.as_bytes()).unwrap();
+
+        let mut model_metadata: types::ModelMetadata = serde_json::from_str(&metadata).unwrap();
+        model_metadata.onnx_uri = Some(model_parent.to_string());
+        model_metadata.preprocessor_uri = Some(preprocessor_parent.to_string());
+
+        // setup server
+        let mut download_server = mockito::Server::new();
+        let url = download_server.url();
+        env::set_var("OPSML_TRACKING_URI", url);
+
+        // get model files
+        let model_files = types::ListFileResponse {
+            files: vec![model_rpath.to_string()],
+        };
+
+        let model_file_response = serde_json::to_string(&model_files).unwrap();
+
+        // get preprocessor files
+        let preprocessor_files = types::ListFileResponse {
+            files: vec![preprocessor_rpath.to_string()],
+        };
+
+        let preprocessor_file_response = serde_json::to_string(&preprocessor_files).unwrap();
+
+        // directory to write to
+        let new_dir = format!("./src/api/test_utils/{}/{}", uid, "downloaded");
+
+        // mock metadata
+        let mock_metadata_path = download_server
+            .mock("POST", "/opsml/models/metadata")
+            .with_status(201)
+            .with_body(serde_json::to_string(&model_metadata).unwrap())
+            .create();
+
+        // mock list model files
+        let artifact_model_path = format!("/opsml/files/list?path={}", model_parent);
+        let model_list_path = download_server
+            .mock("GET", artifact_model_path.as_str())
+            .with_status(201)
+            .with_body(&model_file_response)
+            .create();
+
+        // mock list preprocessor files
+        let artifact_preprocessor_path = format!("/opsml/files/list?path={}", preprocessor_parent);
+        let preprocessor_list_path = download_server
+            .mock("GET", artifact_preprocessor_path.as_str())
+            .with_status(201)
+            .with_body(&preprocessor_file_response)
+            .create();
+
+        // mock model
+        let get_path = format!("/opsml/files/download?path={}", model_rpath);
+        let mock_model_path = download_server
+            .mock("GET", get_path.as_str())
+            .with_status(201)
+            .with_body(&metadata)
+            .create();
+
+        // mock model
+        let get_path = format!("/opsml/files/download?path={}", preprocessor_rpath);
+        let mock_preprocessor_path = download_server
+            .mock("GET", get_path.as_str())
+            .with_status(201)
+            .with_body(&metadata)
+            .create();
+
+        let downloader = ModelDownloader {
+            name: Some("name"),
+            version: Some("version"),
+            repository: Some("repo"),
+            write_dir: &new_dir,
+            onnx: &true,
+            preprocessor: &true,
+            ..test_downloader()
+        };
+
+        let _ = downloader.get_metadata().await.unwrap();
+        mock_metadata_path.assert();
+
+        downloader.download_model().await.unwrap();
+
+        model_list_path.assert();
+        preprocessor_list_path.assert();
+        mock_model_path.assert();
+        mock_preprocessor_path.assert();
+        assert!(Path::new(&test_dir)
+            .join("downloaded/nested/preprocessor.joblib")
+            .exists());
+        assert!(Path::new(&test_dir).join("downloaded/model.onnx").exists());
+
+        // clean up
+        fs::remove_dir_all(&test_dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_download_model_archive_failure_cleans_up() {
+        let _env = TestEnv::lock();
+        let test_dir = TestDir::new();
+        let model_rpath = format!("{}/trained_model", test_dir.path());
+        let weights_rpath = format!("{}/model.onnx", model_rpath);
+        let config_rpath = format!("{}/config.json", model_rpath);
+
+        let metadata = fs::read_to_string("./src/api/test_utils/metadata.json").unwrap();
+        let mut model_metadata: types::ModelMetadata = serde_json::from_str(&metadata).unwrap();
+        model_metadata.onnx_uri = Some(model_rpath.clone());
+
+        let mut download_server = mockito::Server::new();
+        let url = download_server.url();
+        env::set_var("OPSML_TRACKING_URI", url);
+
+        let mock_list_path = download_server
+            .mock(
+                "GET",
+                format!("/opsml/files/list?path={}", model_rpath).as_str(),
+            )
+            .with_status(201)
+            .with_body(
+                serde_json::to_string(&types::ListFileResponse {
+                    files: vec![weights_rpath.clone(), config_rpath.clone()],
+                })
+                .unwrap(),
+            )
+            .create();
+        let mock_weights_path = download_server
+            .mock(
+                "GET",
+                format!("/opsml/files/download?path={}", weights_rpath).as_str(),
+            )
+            .with_status(201)
+            .with_body("model")
+            .create();
+        let mock_config_path = download_server
+            .mock(
+                "GET",
+                format!("/opsml/files/download?path={}", config_rpath).as_str(),
+            )
+            .with_status(500)
+            .create();
+
+        let archive_path = format!("{}/model.tar.gz", test_dir.path());
+        let downloader = ModelDownloader {
+            name: Some("name"),
+            version: Some("version"),
+            write_dir: test_dir.path(),
+            onnx: &true,
+            archive: Some(&archive_path),
+            batch_size: 1,
+            ..test_downloader()
+        };
+
+        assert!(downloader
+            .download_model_with_metadata(&model_metadata)
+            .await
+            .is_err());
+
+        mock_list_path.assert();
+        mock_weights_path.assert();
+        mock_config_path.assert();
+
+        // neither the partial tarball nor the staged files are left behind
+        assert!(!Path::new(&archive_path).exists());
+        assert!(!Path::new(&format!("{}.partial", archive_path)).exists());
+    }
+
+    #[tokio::test]
+    async fn test_download_model_archive() {
+        let _env = TestEnv::lock();
+        let uid = &Uuid::new_v4().to_string();
+        let test_dir = format!("./src/api/test_utils/{}", uid);
+        std::fs::create_dir_all(&test_dir).unwrap();
+
+        // remote model directory with a nested file
+        let model_dir = Path::new(&test_dir).join("trained_model");
+        let model_rpath = model_dir.to_str().unwrap();
+        let weights_rpath = format!("{}/model.onnx", model_rpath);
+        let config_rpath = format!("{}/config/config.json", model_rpath);
+
+        let metadata = fs::read_to_string("./src/api/test_utils/metadata.json").unwrap();
+        let mut model_metadata: types::ModelMetadata = serde_json::from_str(&metadata).unwrap();
+        model_metadata.onnx_uri = Some(model_rpath.to_string());
+
+        let mut download_server = mockito::Server::new();
+        let url = download_server.url();
+        env::set_var("OPSML_TRACKING_URI", url);
+
+        let files = types::ListFileResponse {
+            files: vec![weights_rpath.clone(), config_rpath.clone()],
+        };
+
+        let new_dir = format!("{}/downloaded", test_dir);
+        let archive_path = format!("{}/model.tar.gz", test_dir);
+
+        let mock_metadata_path = download_server
+            .mock("POST", "/opsml/models/metadata")
+            .with_status(201)
+            .with_body(serde_json::to_string(&model_metadata).unwrap())
+            .create();
+
+        let mock_list_path = download_server
+            .mock(
+                "GET",
+                format!("/opsml/files/list?path={}", model_rpath).as_str(),
+            )
+            .with_status(201)
+            .with_body(serde_json::to_string(&files).unwrap())
+            .create();
+
+        let mock_weights_path = download_server
+            .mock(
+                "GET",
+                format!("/opsml/files/download?path={}", weights_rpath).as_str(),
+            )
+            .with_status(201)
+            .with_body("model")
+            .create();
+
+        let mock_config_path = download_server
+            .mock(
+                "GET",
+                format!("/opsml/files/download?path={}", config_rpath).as_str(),
+            )
+            .with_status(201)
+            .with_body("{}")
+            .create();
+
+        let mock_resolve_uid = mock_resolve_uid(&mut download_server, &model_metadata, uid);
+
+        let downloader = ModelDownloader {
+            name: Some("name"),
+            version: Some("version"),
+            repository: Some("repo"),
+            write_dir: &new_dir,
+            onnx: &true,
+            manifest: &true,
+            archive: Some(&archive_path),
+            ..test_downloader()
+        };
+
+        downloader.download_model().await.unwrap();
+
+        mock_metadata_path.assert();
+        mock_list_path.assert();
+        mock_weights_path.assert();
+        mock_config_path.assert();
+        mock_resolve_uid.assert();
+
+        // nothing written outside the archive
+        assert!(!Path::new(&new_dir).exists());
+        assert!(!Path::new(&format!("{}.partial", archive_path)).exists());
+
+        let archive_file = File::open(&archive_path).unwrap();
+        let mut archive = tar::Archive::new(flate2::read::GzDecoder::new(archive_file));
+        let mut entries: Vec<(String, String)> = archive
+            .entries()
+            .unwrap()
+            .map(|entry| {
+                let mut entry = entry.unwrap();
+                let name = entry.path().unwrap().to_string_lossy().to_string();
+                let mut contents = String::new();
+                std::io::Read::read_to_string(&mut entry, &mut contents).unwrap();
+                (name, contents)
+            })
+            .collect();
+        entries.sort();
+
+        let names: Vec<&str> = entries.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec![
+                "config/config.json",
+                "manifest.json",
+                "model-metadata.json",
+                "model.onnx"
+            ]
+        );
+        assert_eq!(entries[3].1, "model");
+
+        let archived_metadata: types::ModelMetadata = serde_json::from_str(&entries[2].1).unwrap();
+        assert_eq!(archived_metadata.model_name, model_metadata.model_name);
+
+        fs::remove_dir_all(&test_dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_download_model_manifest() {
+        let _env = TestEnv::lock();
+        let uid = &Uuid::new_v4().to_string();
+        // Populate files
+        let test_dir = format!("./src/api/test_utils/{}", uid);
+        std::fs::create_dir_all(&test_dir).unwrap();
+
+        // create fake model directory with two files
+        let model_dir = Path::new(&test_dir).join("trained_model");
+        let model_rpath = model_dir.to_str().unwrap();
+        let weights_rpath = format!("{}/model.onnx", model_rpath);
+        let config_rpath = format!("{}/config.json", model_rpath);
+
+        let metadata = fs::read_to_string("./src/api/test_utils/metadata.json").unwrap();
+        let mut model_metadata: types::ModelMetadata = serde_json::from_str(&metadata).unwrap();
+        model_metadata.onnx_uri = Some(model_rpath.to_string());
+
+        // setup server
+        let mut download_server = mockito::Server::new();
+        let url = download_server.url();
+        env::set_var("OPSML_TRACKING_URI", url);
+
+        let files = types::ListFileResponse {
+            files: vec![weights_rpath.clone(), config_rpath.clone()],
+        };
+
+        // directory to write to
+        let new_dir = format!("./src/api/test_utils/{}/{}", uid, "downloaded");
+
+        let mock_metadata_path = download_server
+            .mock("POST", "/opsml/models/metadata")
+            .with_status(201)
+            .with_body(serde_json::to_string(&model_metadata).unwrap())
+            .create();
+
+        let mock_list_path = download_server
+            .mock(
+                "GET",
+                format!("/opsml/files/list?path={}", model_rpath).as_str(),
+            )
+            .with_status(201)
+            .with_body(serde_json::to_string(&files).unwrap())
+            .create();
+
+        let mock_weights_path = download_server
+            .mock(
+                "GET",
+                format!("/opsml/files/download?path={}", weights_rpath).as_str(),
+            )
+            .with_status(201)
+            .with_body("model")
+            .create();
+
+        let mock_config_path = download_server
+            .mock(
+                "GET",
+                format!("/opsml/files/download?path={}", config_rpath).as_str(),
+            )
+            .with_status(201)
+            .with_body("{}")
+            .create();
+
+        // the model is requested by name and version, so its uid is looked up
+        let mock_resolve_uid = mock_resolve_uid(&mut download_server, &model_metadata, uid);
+
+        let downloader = ModelDownloader {
+            name: Some("name"),
+            version: Some("version"),
+            repository: Some("repo"),
+            write_dir: &new_dir,
+            onnx: &true,
+            manifest: &true,
+            ..test_downloader()
+        };
+
+        downloader.download_model().await.unwrap();
+
+        mock_metadata_path.assert();
+        mock_list_path.assert();
+        mock_weights_path.assert();
+        mock_config_path.assert();
+        mock_resolve_uid.assert();
+
+        let manifest: types::DownloadManifest = serde_json::from_str(
+            &fs::read_to_string(Path::new(&new_dir).join(MANIFEST_FILE)).unwrap(),
+        )
+        .unwrap();
+
+        assert_eq!(manifest.name, model_metadata.model_name);
+        assert_eq!(manifest.version, model_metadata.model_version);
+        assert_eq!(manifest.uid.as_deref(), Some(uid.as_str()));
+        assert_eq!(manifest.files.len(), 2);
+
+        assert_eq!(manifest.files[0].remote_path, weights_rpath);
+        assert_eq!(
+            manifest.files[0].local_path,
+            Path::new(&new_dir).join("model.onnx").to_str().unwrap()
+        );
+        assert_eq!(manifest.files[0].size, 5);
+        assert_eq!(
+            manifest.files[0].checksum,
+            "9372c470eeadd5ecd9c3c74c2b3cb633f8e2f2fad799250a0f70d652b6b825e4"
+        );
+
+        assert_eq!(manifest.files[1].remote_path, config_rpath);
+        assert_eq!(manifest.files[1].size, 2);
+        assert_eq!(
+            manifest.files[1].checksum,
+            "44136fa355b3678a1146ad16f7e8649e94fb4fc21fe77e8310c060f61caaff8a"
+        );
+
+        // clean up
+        fs::remove_dir_all(&test_dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_resolve_download_urls() {
+        let _env = TestEnv::lock();
+        let metadata = fs::read_to_string("./src/api/test_utils/metadata.json").unwrap();
+        let mut model_metadata: types::ModelMetadata = serde_json::from_str(&metadata).unwrap();
+        model_metadata.model_uri = "remote/trained_model".to_string();
+        model_metadata.tokenizer_uri = Some("remote/tokenizer".to_string());
+
+        let mut download_server = mockito::Server::new();
+        env::set_var("OPSML_TRACKING_URI", download_server.url());
+
+        let mock_metadata = download_server
+            .mock("POST", "/opsml/models/metadata")
+            .with_status(201)
+            .with_body(serde_json::to_string(&model_metadata).unwrap())
+            .create();
+        let mock_files = download_server
+            .mock(
+                "GET",
+                mockito::Matcher::Regex(r"^/opsml/files/".to_string()),
+            )
+            .expect(0)
+            .create();
+
+        let downloader = ModelDownloader {
+            uid: Some("uid"),
+            write_dir: "",
+            preprocessor: &true,
+            ..test_downloader()
+        };
+
+        let urls = downloader.resolve_download_urls().await.unwrap();
+        mock_metadata.assert();
+        mock_files.assert();
+
+        assert_eq!(
+            urls,
+            vec![
+                format!(
+                    "{}?path=remote/tokenizer",
+                    utils::OpsmlPaths::Download.as_str()
+                ),
+                format!(
+                    "{}?path=remote/trained_model",
+                    utils::OpsmlPaths::Download.as_str()
+                ),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_download_model_summary_json() {
+        let _env = TestEnv::lock();
+        let uid = &Uuid::new_v4().to_string();
+        let test_dir = format!("./src/api/test_utils/{}", uid);
+        let model_rpath = format!("{}/trained_model", test_dir);
+        let weights_rpath = format!("{}/model.onnx", model_rpath);
+        let config_rpath = format!("{}/config.json", model_rpath);
+        let new_dir = format!("{}/downloaded", test_dir);
+
+        let metadata = fs::read_to_string("./src/api/test_utils/metadata.json").unwrap();
+        let mut model_metadata: types::ModelMetadata = serde_json::from_str(&metadata).unwrap();
+        model_metadata.onnx_uri = Some(model_rpath.clone());
+
+        let mut download_server = mockito::Server::new();
+        env::set_var("OPSML_TRACKING_URI", download_server.url());
+
+        let mut mocks = vec![
+            download_server
+                .mock("POST", "/opsml/models/metadata")
+                .with_status(201)
+                .with_body(serde_json::to_string(&model_metadata).unwrap())
+                .create(),
+            download_server
+                .mock(
+                    "GET",
+                    format!("/opsml/files/list?path={}", model_rpath).as_str(),
+                )
+                .with_status(201)
+                .with_body(
+                    serde_json::to_string(&types::ListFileResponse {
+                        files: vec![weights_rpath.clone(), config_rpath.clone()],
+                    })
+                    .unwrap(),
+                )
+                .create(),
+        ];
+        for (rpath, body) in [(&weights_rpath, "model"), (&config_rpath, "{}")] {
+            mocks.push(
+                download_server
+                    .mock(
+                        "GET",
+                        format!("/opsml/files/download?path={}", rpath).as_str(),
+                    )
+                    .with_status(200)
+                    .with_body(body)
+                    .create(),
+            );
+        }
+
+        // the model is requested by name and version, so its uid is looked up
+        mocks.push(mock_resolve_uid(&mut download_server, &model_metadata, uid));
+
+        let downloader = ModelDownloader {
+            name: Some("name"),
+            version: Some("version"),
+            write_dir: &new_dir,
+            onnx: &true,
+            ..test_downloader()
+        };
+
+        let summary = downloader.download_model_summary().await.unwrap();
+        for mock in mocks.iter() {
+            mock.assert();
+        }
+
+        let json: serde_json::Value = serde_json::from_str(
+            &output::OutputOptions {
+                format: output::OutputFormat::Json,
+                ..Default::default()
+            }
+            .render_json(&summary)
+            .unwrap(),
+        )
+        .unwrap();
+
+        let mut keys: Vec<&str> = json
+            .as_object()
+            .unwrap()
+            .keys()
+            .map(|key| key.as_str())
+            .collect();
+        keys.sort();
+        assert_eq!(
+            keys,
+            vec![
+                "duration_secs",
+                "files",
+                "name",
+                "total_bytes",
+                "uid",
+                "version",
+                "write_dir"
+            ]
+        );
+        assert_eq!(json["name"], model_metadata.model_name.as_str());
+        assert_eq!(json["version"], model_metadata.model_version.as_str());
+        assert_eq!(json["uid"], uid.as_str());
+        assert_eq!(json["write_dir"], new_dir.as_str());
+        assert_eq!(json["total_bytes"], 7);
+        assert!(json["duration_secs"].is_f64());
+
+        let files = json["files"].as_array().unwrap();
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0]["remote_path"], weights_rpath.as_str());
+        assert_eq!(
+            files[0]["local_path"],
+            Path::new(&new_dir).join("model.onnx").to_str().unwrap()
+        );
+        assert_eq!(files[0]["size"], 5);
+        assert_eq!(files[1]["remote_path"], config_rpath.as_str());
+        assert_eq!(files[1]["size"], 2);
+
+        fs::remove_dir_all(&test_dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_download_model_write_checksums() {
+        let _env = TestEnv::lock();
+        use sha2::{Digest, Sha256};
+
+        let output_dir = TestDir::new();
+        let test_dir = output_dir.path();
+        let model_rpath = format!("{}/trained_model", test_dir);
+        let contents = [("model.onnx", "model"), ("tokenizer/vocab.txt", "a\nb\n")];
+
+        let metadata = fs::read_to_string("./src/api/test_utils/metadata.json").unwrap();
+        let mut model_metadata: types::ModelMetadata = serde_json::from_str(&metadata).unwrap();
+        model_metadata.onnx_uri = Some(model_rpath.clone());
+
+        let mut download_server = mockito::Server::new();
+        let url = download_server.url();
+        env::set_var("OPSML_TRACKING_URI", url);
+
+        let mock_metadata = download_server
+            .mock("POST", "/opsml/models/metadata")
+            .with_status(201)
+            .with_body(serde_json::to_string(&model_metadata).unwrap())
+            .create();
+        let mock_list = download_server
+            .mock(
+                "GET",
+                format!("/opsml/files/list?path={}", model_rpath).as_str(),
+            )
+            .with_status(201)
+            .with_body(
+                serde_json::to_string(&types::ListFileResponse {
+                    files: contents
+                        .iter()
+                        .map(|(path, _)| format!("{}/{}", model_rpath, path))
+                        .collect(),
+                })
+                .unwrap(),
+            )
+            .create();
+
+        // an md5 server checksum still produces sha256 sums
+        let mut mock_downloads = Vec::new();
+        for (path, body) in contents.iter() {
+            let mut mock = download_server
+                .mock(
+                    "GET",
+                    format!("/opsml/files/download?path={}/{}", model_rpath, path).as_str(),
+                )
+                .with_status(200)
+                .with_body(body);
+            if *path == "model.onnx" {
+                mock = mock.with_header("x-checksum", "md5:20f35e630daf44dbfa4c3f68f5399d8c");
+            }
+            mock_downloads.push(mock.create());
+        }
+
+        let new_dir = format!("{}/downloaded", test_dir);
+        let downloader = ModelDownloader {
+            name: Some("name"),
+            version: Some("version"),
+            repository: Some("repo"),
+            write_dir: &new_dir,
+            onnx: &true,
+            write_checksums: true,
+            ..test_downloader()
+        };
+
+        downloader.download_model().await.unwrap();
+
+        mock_metadata.assert();
+        mock_list.assert();
+        for mock in mock_downloads.iter() {
+            mock.assert();
+        }
+
+        let sums = fs::read_to_string(Path::new(&new_dir).join(CHECKSUMS_FILE)).unwrap();
+        let expected: Vec<String> = contents
+            .iter()
+            .map(|(path, _)| {
+                let downloaded = fs::read(Path::new(&new_dir).join(path)).unwrap();
+                format!("{:x}  {}", Sha256::digest(&downloaded), path)
+            })
+            .collect();
+        assert_eq!(sums.lines().collect::<Vec<_>>(), expected);
+    }
+
+    #[test]
+    fn test_run_post_hook() {
+        let output_dir = TestDir::new();
+        let test_dir = output_dir.path();
+        fs::create_dir_all(test_dir).unwrap();
+
+        // a failing hook fails the command
+        let error = run_post_hook("exit 3", test_dir, false).unwrap_err();
+        assert!(error.to_string().contains("exit 3 exited with"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_post_hook_write_dir() {
+        let output_dir = TestDir::new();
+        // spaces and shell metacharacters stay part of the directory name
+        let test_dir = format!("{}/with space;touch injected", output_dir.path());
+        fs::create_dir_all(&test_dir).unwrap();
+
+        run_post_hook("printf %s {dir} > {dir}/hook.txt", &test_dir, false).unwrap();
+        assert_eq!(
+            fs::read_to_string(Path::new(&test_dir).join("hook.txt")).unwrap(),
+            test_dir
+        );
+        assert!(!Path::new("injected").exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_post_hook_json_output() {
+        let output_dir = TestDir::new();
+        let test_dir = output_dir.path();
+        fs::create_dir_all(test_dir).unwrap();
+
+        // with JSON output the hook writes to stderr, leaving stdout to the JSON summary
+        run_post_hook("[ /dev/stdout -ef /dev/stderr ]", test_dir, true).unwrap();
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_run_post_hook_write_dir() {
+        let output_dir = TestDir::new();
+        let test_dir = format!("{}\\with space&echo injected", output_dir.path());
+        fs::create_dir_all(&test_dir).unwrap();
+
+        run_post_hook("echo done> {dir}\\hook.txt", &test_dir, false).unwrap();
+        assert_eq!(
+            fs::read_to_string(Path::new(&test_dir).join("hook.txt"))
+                .unwrap()
+                .trim(),
+            "done"
+        );
+    }
+
+    #[test]
+    fn test_get_metadata_field() {
+        let metadata = fs::read_to_string("./src/api/test_utils/metadata.json").unwrap();
+        let model_metadata: types::ModelMetadata = serde_json::from_str(&metadata).unwrap();
+
+        let downloader = ModelDownloader {
+            name: Some("name"),
+            version: Some("version"),
+            repository: Some("repo"),
+            write_dir: "",
+            ..test_downloader()
+        };
+
+        assert_eq!(
+            downloader
+                .get_metadata_field(&model_metadata, "model_uri")
+                .unwrap(),
+            "opsml-root:/"
+        );
+        assert_eq!(
+            downloader
+                .get_metadata_field(&model_metadata, "data_schema.data_type")
+                .unwrap(),
+            "numpy.ndarray"
+        );
+        assert_eq!(
+            downloader
+                .get_metadata_field(&model_metadata, "data_schema.input_features.inputs.shape")
+                .unwrap(),
+            "[1,11]"
+        );
+
+        let error = downloader
+            .get_metadata_field(&model_metadata, "not_a_field")
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("Unknown metadata field: not_a_field"));
+        assert!(error.contains("model_uri"));
+    }
+
+    #[tokio::test]
+    async fn test_save_metadata_yaml() {
+        let metadata = fs::read_to_string("./src/api/test_utils/metadata.json").unwrap();
+        let model_metadata: types::ModelMetadata = serde_json::from_str(&metadata).unwrap();
+        let output_dir = TestDir::new();
+        let write_dir = output_dir.path();
+
+        let downloader = ModelDownloader {
+            name: Some("name"),
+            version: Some("version"),
+            write_dir,
+            metadata_format: MetadataFormat::Yaml,
+            ..test_downloader()
+        };
+
+        let path = downloader.save_metadata(&model_metadata).await.unwrap();
+        assert_eq!(path, Path::new(write_dir).join("model-metadata.yaml"));
+        assert!(!Path::new(write_dir).join(MODEL_METADATA_FILE).exists());
+
+        let saved: types::ModelMetadata =
+            serde_yaml::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(saved, model_metadata);
+    }
+
+    #[test]
+    fn test_parse_model_metadata_strict() {
+        let metadata = fs::read_to_string("./src/api/test_utils/metadata.json").unwrap();
+        parse_model_metadata(&metadata, true).unwrap();
+
+        let mut extended: serde_json::Value = serde_json::from_str(&metadata).unwrap();
+        extended["model_owner"] = "ml-platform".into();
+        extended["data_schema"]["row_count"] = 10.into();
+        let extended = extended.to_string();
+
+        // unknown fields are dropped unless strict
+        assert_eq!(
+            parse_model_metadata(&extended, false).unwrap(),
+            parse_model_metadata(&metadata, false).unwrap()
+        );
+
+        let error = parse_model_metadata(&extended, true)
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("model_owner"));
+        assert!(error.contains("data_schema.row_count"));
+    }
+
+    #[tokio::test]
+    async fn test_print_metadata_field() {
+        let _env = TestEnv::lock();
+        let metadata = fs::read_to_string("./src/api/test_utils/metadata.json").unwrap();
+        let output_dir = TestDir::new();
+        let write_dir = output_dir.path();
+
+        let mut download_server = mockito::Server::new();
+        let url = download_server.url();
+        env::set_var("OPSML_TRACKING_URI", url);
+
+        let mock_metadata_path = download_server
+            .mock("POST", "/opsml/models/metadata")
+            .with_status(201)
+            .with_body(&metadata)
+            .create();
+
+        let downloader = ModelDownloader {
+            name: Some("name"),
+            version: Some("version"),
+            repository: Some("repo"),
+            write_dir,
+            ..test_downloader()
+        };
+
+        let value = downloader.print_metadata_field("onnx_uri").await.unwrap();
+        mock_metadata_path.assert();
+
+        assert_eq!(value, "opsml-root:/");
+        assert!(!Path::new(write_dir).exists());
+    }
+
+    #[tokio::test]
+    async fn test_print_metadata() {
+        let _env = TestEnv::lock();
+        let metadata = fs::read_to_string("./src/api/test_utils/metadata.json").unwrap();
+        let output_dir = TestDir::new();
+        let write_dir = output_dir.path();
+
+        let mut download_server = mockito::Server::new();
+        let url = download_server.url();
+        env::set_var("OPSML_TRACKING_URI", url);
+
+        let mock_metadata_path = download_server
+            .mock("POST", "/opsml/models/metadata")
+            .with_status(201)
+            .with_body(&metadata)
+            .create();
+
+        let downloader = ModelDownloader {
+            uid: Some("uid"),
+            write_dir,
+            ..test_downloader()
+        };
+
+        let printed = downloader
+            .print_metadata(&output::OutputOptions::default())
+            .await
+            .unwrap();
+        mock_metadata_path.assert();
+
+        let printed: types::ModelMetadata = serde_json::from_str(&printed).unwrap();
+        let expected: types::ModelMetadata = serde_json::from_str(&metadata).unwrap();
+        assert_eq!(printed.model_name, expected.model_name);
+        assert_eq!(printed.model_uri, expected.model_uri);
+
+        // nothing written to disk
+        assert!(!Path::new(write_dir).exists());
+        assert!(!Path::new(MODEL_METADATA_FILE).exists());
+    }
+
+    #[tokio::test]
+    async fn test_prefetch_metadata_concurrently() {
+        let _env = TestEnv::lock();
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        let metadata = fs::read_to_string("./src/api/test_utils/metadata.json").unwrap();
+
+        let mut download_server = mockito::Server::new();
+        let url = download_server.url();
+        env::set_var("OPSML_TRACKING_URI", url);
+
+        // each response waits until both requests have arrived. The wait is bounded, so
+        // sequential requests fail the overlap check instead of hanging
+        let arrived = Arc::new(AtomicUsize::new(0));
+        let overlapped = Arc::new(AtomicUsize::new(0));
+        let mut mocks = Vec::new();
+        for uid in ["uid-a", "uid-b"] {
+            let mut model_metadata: types::ModelMetadata = serde_json::from_str(&metadata).unwrap();
+            model_metadata.model_name = format!("model-{}", uid);
+            let body = serde_json::to_string(&model_metadata).unwrap();
+            let (arrived, overlapped) = (arrived.clone(), overlapped.clone());
+
+            mocks.push(
+                download_server
+                    .mock("POST", "/opsml/models/metadata")
+                    .match_body(mockito::Matcher::PartialJson(
+                        serde_json::json!({ "uid": uid }),
+                    ))
+                    .with_status(201)
+                    .with_chunked_body(move |w| {
+                        arrived.fetch_add(1, Ordering::SeqCst);
+                        let deadline =
+                            std::time::Instant::now() + std::time::Duration::from_secs(5);
+                        while arrived.load(Ordering::SeqCst) < 2
+                            && std::time::Instant::now() < deadline
+                        {
+                            std::thread::sleep(std::time::Duration::from_millis(5));
+                        }
+                        if arrived.load(Ordering::SeqCst) == 2 {
+                            overlapped.fetch_add(1, Ordering::SeqCst);
+                        }
+                        w.write_all(body.as_bytes())
+                    })
+                    .create(),
+            );
+        }
+
+        let downloaders: Vec<ModelDownloader> = ["uid-a", "uid-b"]
+            .iter()
+            .map(|uid| ModelDownloader {
+                uid: Some(uid),
+                write_dir: "",
+                ..test_downloader()
+            })
+            .collect();
+
+        let fetched = prefetch_metadata(&downloaders).await.unwrap();
+
+        for mock in mocks.iter() {
+            mock.assert();
+        }
+        assert_eq!(fetched[0].model_name, "model-uid-a");
+        assert_eq!(fetched[1].model_name, "model-uid-b");
+        // both requests were in flight at the same time
+        assert_eq!(overlapped.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_prefetch_metadata_reports_all_failures() {
+        let _env = TestEnv::lock();
+        let mut download_server = mockito::Server::new();
+        let url = download_server.url();
+        env::set_var("OPSML_TRACKING_URI", url);
+
+        let mock_metadata_path = download_server
+            .mock("POST", "/opsml/models/metadata")
+            .with_status(500)
+            .with_body("not found")
+            .expect(2)
+            .create();
+
+        let downloaders: Vec<ModelDownloader> = ["missing-a", "missing-b"]
+            .iter()
+            .map(|name| ModelDownloader {
+                name: Some(name),
+                version: Some("1.0.0"),
+                write_dir: "",
+                ..test_downloader()
+            })
+            .collect();
+
+        let error = prefetch_metadata(&downloaders)
+            .await
+            .unwrap_err()
+            .to_string();
+
+        mock_metadata_path.assert();
+        assert!(error.contains("2 of 2 models"));
+        assert!(error.contains("missing-a version 1.0.0"));
+        assert!(error.contains("missing-b version 1.0.0"));
+    }
+
+    #[tokio::test]
+    async fn test_stream_model_file() {
+        let _env = TestEnv::lock();
+        let metadata = fs::read_to_string("./src/api/test_utils/metadata.json").unwrap();
+        let output_dir = TestDir::new();
+        let write_dir = output_dir.path();
+
+        let model_rpath = "remote/trained_model";
+        let config_rpath = format!("{}/config.json", model_rpath);
+        let tokenizer_rpath = format!("{}/tokenizer/config.json", model_rpath);
+        let weights_rpath = format!("{}/model.onnx", model_rpath);
+
+        let mut model_metadata: types::ModelMetadata = serde_json::from_str(&metadata).unwrap();
+        model_metadata.model_uri = model_rpath.to_string();
+
+        let mut download_server = mockito::Server::new();
+        let url = download_server.url();
+        env::set_var("OPSML_TRACKING_URI", url);
+
+        let files = types::ListFileResponse {
+            files: vec![
+                config_rpath.clone(),
+                tokenizer_rpath.clone(),
+                weights_rpath.clone(),
+            ],
+        };
+
+        let mock_metadata_path = download_server
+            .mock("POST", "/opsml/models/metadata")
+            .with_status(201)
+            .with_body(serde_json::to_string(&model_metadata).unwrap())
+            .expect(2)
+            .create();
+
+        let mock_list_path = download_server
+            .mock(
+                "GET",
+                format!("/opsml/files/list?path={}", model_rpath).as_str(),
+            )
+            .with_status(201)
+            .with_body(serde_json::to_string(&files).unwrap())
+            .expect(2)
+            .create();
+
+        let mock_weights_path = download_server
+            .mock(
+                "GET",
+                format!("/opsml/files/download?path={}", weights_rpath).as_str(),
+            )
+            .with_status(200)
+            .with_body("model bytes")
+            .create();
+
+        let downloader = ModelDownloader {
+            uid: Some("uid"),
+            write_dir,
+            ..test_downloader()
+        };
+
+        let mut output: Vec<u8> = Vec::new();
+        let downloaded = downloader
+            .stream_model_file("model.onnx", &mut output)
+            .await
+            .unwrap();
+
+        assert_eq!(output, b"model bytes");
+        assert_eq!(downloaded.size, 11);
+        assert!(!Path::new(write_dir).exists());
+        mock_weights_path.assert();
+
+        // config.json matches the top-level and tokenizer files
+        let mut output: Vec<u8> = Vec::new();
+        let error = downloader
+            .stream_model_file("config.json", &mut output)
+            .await
+            .unwrap_err()
+            .to_string();
+
+        assert!(error.contains("Multiple files match config.json"));
+        assert!(output.is_empty());
+        mock_metadata_path.assert();
+        mock_list_path.assert();
+    }
+
+    #[test]
+    fn test_file_filter() {
+        let files = [
+            "model.onnx",
+            "model.bin",
+            "tokenizer/vocab.json",
+            "optim/state.bin",
+        ];
+        let matching = |filter: &FileFilter| -> Vec<&str> {
+            files
+                .iter()
+                .filter(|file| filter.is_match(Path::new(file)))
+                .copied()
+                .collect()
+        };
+
+        let patterns = |patterns: &[&str]| -> Vec<String> {
+            patterns.iter().map(|pattern| pattern.to_string()).collect()
+        };
+
+        assert_eq!(matching(&FileFilter::default()), files);
+
+        // include only
+        let filter = FileFilter::new(&patterns(&["*.onnx", "*.json"]), &[]).unwrap();
+        assert_eq!(matching(&filter), ["model.onnx", "tokenizer/vocab.json"]);
+
+        // exclude only
+        let filter = FileFilter::new(&[], &patterns(&["*.bin"])).unwrap();
+        assert_eq!(matching(&filter), ["model.onnx", "tokenizer/vocab.json"]);
+
+        // exclude wins over include
+        let filter =
+            FileFilter::new(&patterns(&["model.*", "optim/*"]), &patterns(&["*.bin"])).unwrap();
+        assert_eq!(matching(&filter), ["model.onnx"]);
+
+        assert!(FileFilter::new(&patterns(&["[invalid"]), &[]).is_err());
+    }
 
-    let model_downloader = ModelDownloader {
-        name,
-        version,
-        repository,
-        uid,
-        write_dir,
-        ignore_release_candidates,
-        onnx: &false,
-        quantize: &false,
-        preprocessor: &false,
-    };
-    model_downloader.get_metadata().await
-}
+    #[tokio::test]
+    async fn test_download_models_concurrently() {
+        let _env = TestEnv::lock();
+        let output_dir = TestDir::new();
+        let test_dir = output_dir.path();
+        let metadata = fs::read_to_string("./src/api/test_utils/metadata.json").unwrap();
 
-/// Downloads model file
-///
-/// * `name` - Name of model
-/// * `repository` - repository associated with model
-/// * `version` - Version of model
-/// * `uid` - uid of model
-/// * `url` - url of opsml server
-/// * `write_dir` - directory to write to
-/// * `no_onnx` - Flag to not download onnx model
-/// * `onnx` - Flag to download onnx model
-///
-#[tokio::main]
-#[allow(clippy::too_many_arguments)]
-pub async fn download_model(
-    name: Option<&str>,
-    version: Option<&str>,
-    repository: Option<&str>,
-    uid: Option<&str>,
-    write_dir: &str,
-    onnx: &bool,
-    quantize: &bool,
-    preprocessor: &bool,
-    ignore_release_candidates: &bool,
-) -> Result<(), anyhow::Error> {
-    let model_downloader = ModelDownloader {
-        name,
-        version,
-        repository,
-        uid,
-        write_dir,
-        ignore_release_candidates,
-        onnx,
-        quantize,
-        preprocessor,
-    };
-    model_downloader.download_model().await
-}
+        let mut download_server = mockito::Server::new();
+        let url = download_server.url();
+        env::set_var("OPSML_TRACKING_URI", url);
+
+        let uids = ["uid-a", "uid-b", "uid-c"];
+        let mut mocks = Vec::new();
+        for uid in ["uid-a", "uid-c"] {
+            let model_rpath = format!("{}/{}/model.onnx", test_dir, uid);
+            let mut model_metadata: types::ModelMetadata = serde_json::from_str(&metadata).unwrap();
+            model_metadata.onnx_uri = Some(model_rpath.clone());
+
+            mocks.push(
+                download_server
+                    .mock("POST", "/opsml/models/metadata")
+                    .match_body(mockito::Matcher::PartialJson(
+                        serde_json::json!({ "uid": uid }),
+                    ))
+                    .with_status(201)
+                    .with_body(serde_json::to_string(&model_metadata).unwrap())
+                    .create(),
+            );
+            mocks.push(
+                download_server
+                    .mock(
+                        "GET",
+                        format!("/opsml/files/list?path={}", model_rpath).as_str(),
+                    )
+                    .with_status(201)
+                    .with_body(
+                        serde_json::to_string(&types::ListFileResponse {
+                            files: vec![model_rpath.clone()],
+                        })
+                        .unwrap(),
+                    )
+                    .create(),
+            );
+            mocks.push(
+                download_server
+                    .mock(
+                        "GET",
+                        format!("/opsml/files/download?path={}", model_rpath).as_str(),
+                    )
+                    .with_status(200)
+                    .with_body("model")
+                    .create(),
+            );
+        }
+        mocks.push(
+            download_server
+                .mock("POST", "/opsml/models/metadata")
+                .match_body(mockito::Matcher::PartialJson(
+                    serde_json::json!({ "uid": "uid-b" }),
+                ))
+                .with_status(404)
+                .with_body("model not found")
+                .create(),
+        );
+
+        let write_dirs: Vec<String> = uids
+            .iter()
+            .map(|uid| format!("{}/downloaded/{}", test_dir, uid))
+            .collect();
+        let downloaders: Vec<ModelDownloader> = uids
+            .iter()
+            .zip(write_dirs.iter())
+            .map(|(uid, write_dir)| ModelDownloader {
+                uid: Some(uid),
+                write_dir,
+                onnx: &true,
+                check_space: &true,
+                ..test_downloader()
+            })
+            .collect();
+
+        let results = download_models_concurrently(&downloaders, 2).await;
+
+        for mock in mocks.iter() {
+            mock.assert();
+        }
+        let models: Vec<&str> = results.iter().map(|result| result.model.as_str()).collect();
+        assert_eq!(models, ["uid uid-a", "uid uid-b", "uid uid-c"]);
+        assert_eq!(*results[0].outcome.as_ref().unwrap(), 5);
+        assert!(results[1].outcome.is_err());
+        assert_eq!(*results[2].outcome.as_ref().unwrap(), 5);
+
+        let summary = build_model_summary(&results, utils::TableStyle::Ascii);
+        assert_eq!(summary.matches("ok").count(), 2);
+        assert_eq!(summary.matches("failed").count(), 1);
+        assert!(summary.contains("model not found"));
+        assert_eq!(
+            fs::read_to_string(Path::new(&write_dirs[2]).join("model.onnx")).unwrap(),
+            "model"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_download_versions_since() {
+        let _env = TestEnv::lock();
+        let output_dir = TestDir::new();
+        let test_dir = output_dir.path();
+        let metadata = fs::read_to_string("./src/api/test_utils/metadata.json").unwrap();
+
+        let mut download_server = mockito::Server::new();
+        let url = download_server.url();
+        env::set_var("OPSML_TRACKING_URI", url);
+
+        let card = |version: &str| types::Card {
+            name: "model".to_string(),
+            repository: "team".to_string(),
+            date: None,
+            contact: "fake_email".to_string(),
+            version: version.to_string(),
+            uid: version.to_string(),
+            tags: HashMap::new(),
+        };
+        let mock_list = download_server
+            .mock("POST", "/opsml/cards/list")
+            .match_body(mockito::Matcher::PartialJson(
+                serde_json::json!({ "name": "model" }),
+            ))
+            .with_status(201)
+            .with_body(
+                serde_json::to_string(&types::ListCardResponse {
+                    cards: ["1.10.0", "1.1.0", "1.2.0", "not-semver", "1.2.1"]
+                        .map(card)
+                        .into(),
+                })
+                .unwrap(),
+            )
+            .create();
+
+        let mut mocks = Vec::new();
+        for version in ["1.2.1", "1.10.0"] {
+            let model_rpath = format!("{}/{}/model.onnx", test_dir, version);
+            let mut model_metadata: types::ModelMetadata = serde_json::from_str(&metadata).unwrap();
+            model_metadata.model_version = version.to_string();
+            model_metadata.onnx_uri = Some(model_rpath.clone());
+
+            mocks.push(
+                download_server
+                    .mock("POST", "/opsml/models/metadata")
+                    .match_body(mockito::Matcher::PartialJson(
+                        serde_json::json!({ "name": "model", "version": version }),
+                    ))
+                    .with_status(201)
+                    .with_body(serde_json::to_string(&model_metadata).unwrap())
+                    .create(),
+            );
+            mocks.push(
+                download_server
+                    .mock(
+                        "GET",
+                        format!("/opsml/files/list?path={}", model_rpath).as_str(),
+                    )
+                    .with_status(201)
+                    .with_body(
+                        serde_json::to_string(&types::ListFileResponse {
+                            files: vec![model_rpath.clone()],
+                        })
+                        .unwrap(),
+                    )
+                    .create(),
+            );
+            mocks.push(
+                download_server
+                    .mock(
+                        "GET",
+                        format!("/opsml/files/download?path={}", model_rpath).as_str(),
+                    )
+                    .with_status(200)
+                    .with_body(version)
+                    .create(),
+            );
+        }
+
+        let new_dir = format!("{}/downloaded", test_dir);
+        let downloader = ModelDownloader {
+            name: Some("model"),
+            write_dir: &new_dir,
+            onnx: &true,
+            check_space: &true,
+            ..test_downloader()
+        };
+
+        downloader
+            .download_versions_since("1.2.0", DEFAULT_MAX_DOWNLOADS)
+            .await
+            .unwrap();
+
+        mock_list.assert();
+        for mock in mocks.iter() {
+            mock.assert();
+        }
+        for version in ["1.2.1", "1.10.0"] {
+            assert_eq!(
+                fs::read_to_string(Path::new(&new_dir).join(version).join("model.onnx")).unwrap(),
+                version
+            );
+        }
+        let downloaded: Vec<String> = fs::read_dir(&new_dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        assert_eq!(downloaded.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_download_tagged() {
+        let _env = TestEnv::lock();
+        let output_dir = TestDir::new();
+        let test_dir = output_dir.path();
+        let metadata = fs::read_to_string("./src/api/test_utils/metadata.json").unwrap();
+
+        let mut download_server = mockito::Server::new();
+        let url = download_server.url();
+        env::set_var("OPSML_TRACKING_URI", url);
+
+        let card = |uid: &str| types::Card {
+            name: "model".to_string(),
+            repository: "team".to_string(),
+            date: None,
+            contact: "fake_email".to_string(),
+            version: "1.0.0".to_string(),
+            uid: uid.to_string(),
+            tags: HashMap::from([("status".to_string(), "prod".to_string())]),
+        };
+        let mock_list = download_server
+            .mock("POST", "/opsml/cards/list")
+            .match_body(mockito::Matcher::PartialJson(serde_json::json!({
+                "registry_type": "model",
+                "tags": { "status": "prod" },
+            })))
+            .with_status(201)
+            .with_body(
+                serde_json::to_string(&types::ListCardResponse {
+                    cards: vec![card("uid-a"), card("uid-b")],
+                })
+                .unwrap(),
+            )
+            .expect(2)
+            .create();
+
+        // uid-a is downloaded by both runs
+        let mut mocks = Vec::new();
+        for (uid, hits) in [("uid-a", 2), ("uid-b", 1)] {
+            let model_rpath = format!("{}/{}/model.onnx", test_dir, uid);
+            let mut model_metadata: types::ModelMetadata = serde_json::from_str(&metadata).unwrap();
+            model_metadata.onnx_uri = Some(model_rpath.clone());
+
+            mocks.push(
+                download_server
+                    .mock("POST", "/opsml/models/metadata")
+                    .match_body(mockito::Matcher::PartialJson(
+                        serde_json::json!({ "uid": uid }),
+                    ))
+                    .with_status(201)
+                    .with_body(serde_json::to_string(&model_metadata).unwrap())
+                    .expect(hits)
+                    .create(),
+            );
+            mocks.push(
+                download_server
+                    .mock(
+                        "GET",
+                        format!("/opsml/files/list?path={}", model_rpath).as_str(),
+                    )
+                    .with_status(201)
+                    .with_body(
+                        serde_json::to_string(&types::ListFileResponse {
+                            files: vec![model_rpath.clone()],
+                        })
+                        .unwrap(),
+                    )
+                    .expect(hits)
+                    .create(),
+            );
+            mocks.push(
+                download_server
+                    .mock(
+                        "GET",
+                        format!("/opsml/files/download?path={}", model_rpath).as_str(),
+                    )
+                    .with_status(200)
+                    .with_body(uid)
+                    .expect(hits)
+                    .create(),
+            );
+        }
+
+        let tags = vec![("status".to_string(), "prod".to_string())];
+        let all_dir = format!("{}/all", test_dir);
+        let first_dir = format!("{}/first", test_dir);
+        for (write_dir, first) in [(&all_dir, false), (&first_dir, true)] {
+            let downloader = ModelDownloader {
+                write_dir,
+                onnx: &true,
+                check_space: &true,
+                ..test_downloader()
+            };
+            downloader
+                .download_tagged(&tags, first, DEFAULT_MAX_DOWNLOADS)
+                .await
+                .unwrap();
+        }
+
+        mock_list.assert();
+        for mock in mocks.iter() {
+            mock.assert();
+        }
+
+        // every match gets a subdirectory, unless only the first is downloaded
+        for uid in ["uid-a", "uid-b"] {
+            assert_eq!(
+                fs::read_to_string(Path::new(&all_dir).join(uid).join("model.onnx")).unwrap(),
+                uid
+            );
+        }
+        assert_eq!(
+            fs::read_to_string(Path::new(&first_dir).join("model.onnx")).unwrap(),
+            "uid-a"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_download_tagged_max_downloads() {
+        let _env = TestEnv::lock();
+        let mut server = mockito::Server::new();
+        env::set_var("OPSML_TRACKING_URI", server.url());
+
+        let cards = ["uid-a", "uid-b", "uid-c"]
+            .iter()
+            .map(|uid| types::Card {
+                name: "model".to_string(),
+                repository: "team".to_string(),
+                date: None,
+                contact: "fake_email".to_string(),
+                version: "1.0.0".to_string(),
+                uid: uid.to_string(),
+                tags: HashMap::new(),
+            })
+            .collect();
+        let mock_list = server
+            .mock("POST", "/opsml/cards/list")
+            .with_status(201)
+            .with_body(serde_json::to_string(&types::ListCardResponse { cards }).unwrap())
+            .create();
+        let mock_metadata = server
+            .mock("POST", "/opsml/models/metadata")
+            .expect(0)
+            .create();
+
+        let downloader = ModelDownloader {
+            write_dir: "./src/api/test_utils/unused",
+            onnx: &true,
+            check_space: &true,
+            ..test_downloader()
+        };
+        let tags = vec![("status".to_string(), "prod".to_string())];
+        let error = downloader
+            .download_tagged(&tags, false, 2)
+            .await
+            .unwrap_err()
+            .to_string();
+
+        // nothing is downloaded once the cap is exceeded
+        mock_list.assert();
+        mock_metadata.assert();
+        assert!(error.contains("matched 3 models, more than --max-downloads 2"));
+        assert!(!Path::new("./src/api/test_utils/unused").exists());
+
+        // 0 disables the cap
+        assert!(check_max_downloads(3, 0).is_ok());
+        assert!(check_max_downloads(2, 2).is_ok());
+    }
+
+    #[test]
+    fn test_download_model_metadata_single_request() {
+        let _env = TestEnv::lock();
+        let output_dir = TestDir::new();
+        let test_dir = output_dir.path();
+        let metadata = fs::read_to_string("./src/api/test_utils/metadata.json").unwrap();
+
+        let mut download_server = mockito::Server::new();
+        let url = download_server.url();
+        env::set_var("OPSML_TRACKING_URI", url);
+
+        let mock_metadata = download_server
+            .mock("POST", "/opsml/models/metadata")
+            .with_status(201)
+            .with_body(&metadata)
+            .expect(1)
+            .create();
+        let mock_files = download_server
+            .mock(
+                "GET",
+                mockito::Matcher::Regex(r"^/opsml/files/(list|download)".to_string()),
+            )
+            .expect(0)
+            .create();
+        let mock_head = download_server
+            .mock("HEAD", mockito::Matcher::Any)
+            .expect(0)
+            .create();
+
+        download_model_metadata(
+            Some("name"),
+            Some("1.0.0"),
+            None,
+            None,
+            test_dir,
+            &false,
+            None,
+            MetadataFormat::Json,
+        )
+        .unwrap();
+
+        mock_metadata.assert();
+        mock_files.assert();
+        mock_head.assert();
+        assert!(Path::new(test_dir).join(MODEL_METADATA_FILE).exists());
+    }
+
+    #[test]
+    fn test_uid_write_dir() {
+        assert_eq!(
+            uid_write_dir("models", "abc-123").unwrap(),
+            Path::new("models").join("abc-123").to_string_lossy()
+        );
+
+        for uid in ["", ".", "..", "../etc", "a/b", "a\\b", "/tmp"] {
+            let error = uid_write_dir("models", uid).unwrap_err().to_string();
+            assert!(error.contains("Invalid model uid"), "{}", error);
+        }
+    }
+
+    #[test]
+    fn test_auto_batch_size() {
+        // many small files
+        assert_eq!(auto_batch_size(&[Some(20_000); 200]), 16);
+        // a few medium files
+        assert_eq!(auto_batch_size(&[Some(50_000_000); 10]), 8);
+        // large weights with a small config, averaging under 1 GB
+        assert_eq!(
+            auto_batch_size(&[Some(1_500_000_000), Some(300_000_000), Some(2_000)]),
+            3
+        );
+        // few large files
+        assert_eq!(auto_batch_size(&[Some(5_000_000_000); 6]), 2);
+        // never more than the number of files
+        assert_eq!(auto_batch_size(&[Some(100)]), 1);
+        // unknown sizes are ignored, and sizes that are all unknown get a moderate default
+        assert_eq!(auto_batch_size(&[None, Some(5_000_000_000), None]), 2);
+        assert_eq!(auto_batch_size(&[None; 10]), 4);
+        assert_eq!(auto_batch_size(&[]), 1);
+    }
+
+    #[tokio::test]
+    async fn test_download_check_space_and_auto_concurrency() {
+        let _env = TestEnv::lock();
+        let test_dir = TestDir::new();
+        let model_rpath = format!("{}/model", test_dir.path());
+        let files: Vec<String> = ["model.onnx", "config.json"]
+            .iter()
+            .map(|name| format!("{}/{}", model_rpath, name))
+            .collect();
+        let metadata = fs::read_to_string("./src/api/test_utils/metadata.json").unwrap();
+        let mut model_metadata: types::ModelMetadata = serde_json::from_str(&metadata).unwrap();
+        model_metadata.onnx_uri = Some(model_rpath.clone());
+
+        let mut download_server = mockito::Server::new();
+        let url = download_server.url();
+        env::set_var("OPSML_TRACKING_URI", url);
+
+        let mock_list = download_server
+            .mock(
+                "GET",
+                format!("/opsml/files/list?path={}", model_rpath).as_str(),
+            )
+            .with_status(201)
+            .with_body(
+                serde_json::to_string(&types::ListFileResponse {
+                    files: files.clone(),
+                })
+                .unwrap(),
+            )
+            .expect(2)
+            .create();
+        let mock_download = download_server
+            .mock(
+                "GET",
+                mockito::Matcher::Regex(r"^/opsml/files/download".to_string()),
+            )
+            .with_status(200)
+            .with_body("model")
+            .expect(2)
+            .create();
+        // one size lookup per file serves both --check-space and --auto-concurrency
+        let mock_head = download_server
+            .mock("HEAD", mockito::Matcher::Any)
+            .with_status(200)
+            .with_header("content-length", "5")
+            .expect(2)
+            .create();
+
+        let write_dir = format!("{}/downloaded", test_dir.path());
+        let downloader = ModelDownloader {
+            name: Some("name"),
+            version: Some("version"),
+            write_dir: &write_dir,
+            onnx: &true,
+            check_space: &true,
+            auto_concurrency: true,
+            write_metadata: false,
+            ..test_downloader()
+        };
+
+        let downloaded = downloader
+            .download_model_with_metadata(&model_metadata)
+            .await
+            .unwrap();
+        assert_eq!(downloaded.len(), 2);
+        mock_download.assert();
+        mock_head.assert();
+
+        // nothing is downloaded when the files do not fit on disk
+        mock_download.remove();
+        mock_head.remove();
+        let mock_download = download_server
+            .mock(
+                "GET",
+                mockito::Matcher::Regex(r"^/opsml/files/download".to_string()),
+            )
+            .expect(0)
+            .create();
+        let mock_head = download_server
+            .mock("HEAD", mockito::Matcher::Any)
+            .with_status(200)
+            .with_header("content-length", "1000000000000000000")
+            .expect(2)
+            .create();
+
+        let error = downloader
+            .download_model_with_metadata(&model_metadata)
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("Not enough disk space"), "{}", error);
+        mock_list.assert();
+        mock_download.assert();
+        mock_head.assert();
+    }
+
+    #[test]
+    fn test_build_download_report() {
+        let files: Vec<types::ManifestFile> =
+            [("model.onnx", 2_000_000, 2), ("tokenizer.json", 500, 0)]
+                .iter()
+                .map(|(name, size, seconds)| types::ManifestFile {
+                    remote_path: name.to_string(),
+                    local_path: format!("models/{}", name),
+                    size: *size,
+                    checksum: "checksum".to_string(),
+                    sha256: "sha256".to_string(),
+                    duration: std::time::Duration::from_secs(*seconds),
+                })
+                .collect();
+
+        let report = build_download_report(&files, utils::TableStyle::Markdown);
+        let rows: Vec<&str> = report.lines().skip(2).collect();
+
+        assert_eq!(rows.len(), files.len());
+        assert!(report.lines().next().unwrap().contains("MBps"));
+        assert!(rows[0].contains("models/model.onnx") && rows[0].contains("1.00"));
+        assert!(rows[0].contains("2.0 MB") && rows[1].contains("500 B"));
+        assert!(rows[1].contains("models/tokenizer.json") && rows[1].contains(" - "));
+    }
+
+    #[tokio::test]
+    async fn test_download_files_in_batches() {
+        let _env = TestEnv::lock();
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        let mut download_server = mockito::Server::new();
+        let url = download_server.url();
+        env::set_var("OPSML_TRACKING_URI", url);
+
+        let output_dir = TestDir::new();
+        let test_dir = output_dir.path();
+
+        // track how many downloads are in flight at once, and whether a batch started before
+        // the previous one finished
+        let started = Arc::new(AtomicUsize::new(0));
+        let finished = Arc::new(AtomicUsize::new(0));
+        let active = Arc::new(AtomicUsize::new(0));
+        let max_active = Arc::new(AtomicUsize::new(0));
+        let overlapped = Arc::new(AtomicUsize::new(0));
+        let (mock_started, mock_finished, mock_active, mock_max_active, mock_overlapped) = (
+            started.clone(),
+            finished.clone(),
+            active.clone(),
+            max_active.clone(),
+            overlapped.clone(),
+        );
+
+        let mock_download = download_server
+            .mock(
+                "GET",
+                mockito::Matcher::Regex(r"^/opsml/files/download\?path=".to_string()),
+            )
+            .with_status(200)
+            .with_chunked_body(move |w| {
+                let index = mock_started.fetch_add(1, Ordering::SeqCst);
+                let batch_start = index / 2 * 2;
+                if mock_finished.load(Ordering::SeqCst) < batch_start {
+                    mock_overlapped.fetch_add(1, Ordering::SeqCst);
+                }
+                let current = mock_active.fetch_add(1, Ordering::SeqCst) + 1;
+                mock_max_active.fetch_max(current, Ordering::SeqCst);
+
+                // gate: hold each download until the rest of its batch has started. The wait
+                // is bounded so a serial download fails the max_active check instead of hanging
+                let batch_end = (batch_start + 2).min(5);
+                let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+                while mock_started.load(Ordering::SeqCst) < batch_end
+                    && std::time::Instant::now() < deadline
+                {
+                    std::thread::sleep(std::time::Duration::from_millis(5));
+                }
+
+                mock_active.fetch_sub(1, Ordering::SeqCst);
+                mock_finished.fetch_add(1, Ordering::SeqCst);
+                w.write_all(b"weights")
+            })
+            .expect(5)
+            .create();
+
+        let model_files: Vec<ModelFile> = (0..5)
+            .map(|i| ModelFile {
+                remote_path: format!("model/file-{}.bin", i),
+                path: PathBuf::from(format!("file-{}.bin", i)),
+            })
+            .collect();
+
+        let downloader = ModelDownloader {
+            uid: Some("uid"),
+            write_dir: test_dir,
+            batch_size: 2,
+            ..test_downloader()
+        };
+
+        let downloaded = downloader
+            .download_files(&model_files, None, downloader.batch_size)
+            .await
+            .unwrap();
+
+        mock_download.assert();
+        assert_eq!(downloaded.len(), 5);
+        assert_eq!(max_active.load(Ordering::SeqCst), 2);
+        // batches of 2, 2 and 1 run one after another
+        assert_eq!(overlapped.load(Ordering::SeqCst), 0);
+        for (i, file) in downloaded.iter().enumerate() {
+            assert_eq!(file.remote_path, format!("model/file-{}.bin", i));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fetch_metadata_retries_with_spinner() {
+        let _env = TestEnv::lock();
+        let metadata = fs::read_to_string("./src/api/test_utils/metadata.json").unwrap();
+        let expected: types::ModelMetadata = serde_json::from_str(&metadata).unwrap();
+
+        let mut download_server = mockito::Server::new();
+        let url = download_server.url();
+        env::set_var("OPSML_TRACKING_URI", url);
+
+        // first attempt hits an unavailable server, the retry succeeds
+        let mock_unavailable = download_server
+            .mock("POST", "/opsml/models/metadata")
+            .with_status(503)
+            .expect(1)
+            .create();
+        let mock_metadata = download_server
+            .mock("POST", "/opsml/models/metadata")
+            .with_status(201)
+            .with_body(&metadata)
+            .expect(1)
+            .create();
+
+        let downloader = ModelDownloader {
+            uid: Some("uid"),
+            write_dir: "",
+            ..test_downloader()
+        };
+
+        let model_metadata = downloader.fetch_model_metadata().await.unwrap();
+
+        mock_unavailable.assert();
+        mock_metadata.assert();
+        assert_eq!(model_metadata.model_name, expected.model_name);
+        assert_eq!(model_metadata.model_uri, expected.model_uri);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_metadata_honors_retry_after() {
+        let _env = TestEnv::lock();
+        let metadata = fs::read_to_string("./src/api/test_utils/metadata.json").unwrap();
+
+        let mut download_server = mockito::Server::new();
+        let url = download_server.url();
+        env::set_var("OPSML_TRACKING_URI", url);
+
+        // first attempt is rate limited, the retry succeeds
+        let mock_rate_limited = download_server
+            .mock("POST", "/opsml/models/metadata")
+            .with_status(429)
+            .with_header("retry-after", "1")
+            .expect(1)
+            .create();
+        let mock_metadata = download_server
+            .mock("POST", "/opsml/models/metadata")
+            .with_status(201)
+            .with_body(&metadata)
+            .expect(1)
+            .create();
+
+        let downloader = ModelDownloader {
+            uid: Some("uid"),
+            write_dir: "",
+            ..test_downloader()
+        };
+
+        let start = Instant::now();
+        downloader.fetch_model_metadata().await.unwrap();
+
+        // the default backoff for a first retry is 250ms
+        assert!(start.elapsed() >= Duration::from_secs(1));
+        mock_rate_limited.assert();
+        mock_metadata.assert();
+    }
+
+    #[tokio::test]
+    async fn test_fetch_metadata_retries_404() {
+        let _env = TestEnv::lock();
+        let metadata = fs::read_to_string("./src/api/test_utils/metadata.json").unwrap();
+
+        let mut download_server = mockito::Server::new();
+        env::set_var("OPSML_TRACKING_URI", download_server.url());
+
+        let downloader = ModelDownloader {
+            uid: Some("uid"),
+            write_dir: "",
+            ..test_downloader()
+        };
+
+        // 404s fail straight away by default
+        let mock_not_found = download_server
+            .mock("POST", "/opsml/models/metadata")
+            .with_status(404)
+            .expect(1)
+            .create();
+        assert!(downloader.fetch_model_metadata().await.is_err());
+        mock_not_found.assert();
+        mock_not_found.remove();
+
+        // the registry has caught up by the retry
+        let mock_not_found = download_server
+            .mock("POST", "/opsml/models/metadata")
+            .with_status(404)
+            .expect(1)
+            .create();
+        let mock_metadata = download_server
+            .mock("POST", "/opsml/models/metadata")
+            .with_status(201)
+            .with_body(&metadata)
+            .expect(1)
+            .create();
+
+        env::set_var("OPSML_RETRY_ON_404", "1");
+        let result = downloader.fetch_model_metadata().await;
+        env::remove_var("OPSML_RETRY_ON_404");
+
+        assert_eq!(result.unwrap().model_version, "1.1.0");
+        mock_not_found.assert();
+        mock_metadata.assert();
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use std::env;
-    use std::fs;
-    use std::fs::File;
-    use std::io::Write;
-    use tokio;
-    use uuid::Uuid;
     #[tokio::test]
-    async fn test_download_model() {
-        let uid = &Uuid::new_v4().to_string();
-        // Populate files
-        let test_dir = format!("./src/api/test_utils/{}", uid);
-        std::fs::create_dir_all(&test_dir).unwrap();
+    async fn test_fetch_metadata_stops_retrying_when_budget_is_spent() {
+        let _env = TestEnv::lock();
+        let mut download_server = mockito::Server::new();
+        env::set_var("OPSML_TRACKING_URI", download_server.url());
 
-        // create fake model file
-        let model_path = Path::new(&test_dir).join("model.onnx");
-        let model_rpath = model_path.to_str().unwrap();
-        let mut file = File::create(&model_path).unwrap();
-        file.write_all(b"model").unwrap();
+        // without a budget this would wait 5s before each of 3 retries
+        let mock_unavailable = download_server
+            .mock("POST", "/opsml/models/metadata")
+            .with_status(503)
+            .with_header("retry-after", "5")
+            .expect(2)
+            .create();
 
-        // load and write metadata
-        let metadata_path = Path::new(&test_dir).join("metadata.json");
-        let metadata = fs::read_to_string("./src/api/test_utils/metadata.json").unwrap();
-        let mut metadata_file = File::create(metadata_path).unwrap();
-        metadata_file.write_all(metadata.as_bytes()).unwrap();
+        let downloader = ModelDownloader {
+            uid: Some("uid"),
+            write_dir: "",
+            ..test_downloader()
+        };
+
+        env::set_var("OPSML_RETRY_BUDGET", "1");
+        let result = utils::with_shared_client(downloader.fetch_model_metadata()).await;
+        env::remove_var("OPSML_RETRY_BUDGET");
 
+        // the first retry waits out the budget, after which the failure is returned. Two
+        // requests means exactly one wait, instead of the three retries allowed without a budget
+        assert!(result.is_err());
+        mock_unavailable.assert();
+    }
+
+    #[tokio::test]
+    async fn test_download_only_named_files() {
+        let _env = TestEnv::lock();
+        let metadata = fs::read_to_string("./src/api/test_utils/metadata.json").unwrap();
         let mut model_metadata: types::ModelMetadata = serde_json::from_str(&metadata).unwrap();
-        model_metadata.onnx_uri = Some(model_rpath.to_string());
+        model_metadata.model_uri = "models/uid".to_string();
 
-        // setup server
         let mut download_server = mockito::Server::new();
         let url = download_server.url();
         env::set_var("OPSML_TRACKING_URI", url);
 
-        // get files
         let files = types::ListFileResponse {
-            files: vec![model_rpath.to_string()],
+            files: vec![
+                "models/uid/config.json".to_string(),
+                "models/uid/model.bin".to_string(),
+                "models/uid/tokenizer/config.json".to_string(),
+            ],
         };
-        let file_response = serde_json::to_string(&files).unwrap();
-
-        // directory to write to
-        let new_dir = format!("./src/api/test_utils/{}/{}", uid, "downloaded");
 
-        // mock metadata
-        let mock_metadata_path = download_server
+        let mock_metadata = download_server
             .mock("POST", "/opsml/models/metadata")
             .with_status(201)
             .with_body(serde_json::to_string(&model_metadata).unwrap())
+            .expect(2)
             .create();
-
-        // mock list files
-        let artifact_path = format!("/opsml/files/list?path={}", model_rpath);
-        let mock_list_path = download_server
-            .mock("GET", artifact_path.as_str())
+        let mock_list = download_server
+            .mock("GET", "/opsml/files/list?path=models/uid")
             .with_status(201)
-            .with_body(&file_response)
+            .with_body(serde_json::to_string(&files).unwrap())
+            .expect(2)
             .create();
-
-        // mock model
-        let get_path = format!("/opsml/files/download?path={}", model_rpath);
-        let mock_model_path = download_server
-            .mock("GET", get_path.as_str())
-            .with_status(201)
-            .with_body(&metadata)
+        let mock_config = download_server
+            .mock("GET", "/opsml/files/download?path=models/uid/config.json")
+            .with_status(200)
+            .with_body("{}")
+            .expect(1)
+            .create();
+        let mock_tokenizer_config = download_server
+            .mock(
+                "GET",
+                "/opsml/files/download?path=models/uid/tokenizer/config.json",
+            )
+            .with_status(200)
+            .with_body("{}")
+            .expect(1)
+            .create();
+        let mock_model = download_server
+            .mock("GET", "/opsml/files/download?path=models/uid/model.bin")
+            .expect(0)
             .create();
 
-        let downloader = ModelDownloader {
-            name: Some("name"),
-            version: Some("version"),
-            repository: Some("repo"),
-            uid: None,
-            write_dir: &new_dir,
-            ignore_release_candidates: &false,
-            onnx: &true,
-            quantize: &false,
-            preprocessor: &false,
+        let output_dir = TestDir::new();
+        let test_dir = output_dir.path();
+        let mut downloader = ModelDownloader {
+            uid: Some("uid"),
+            write_dir: test_dir,
+            file_filter: FileFilter::default().with_only(&["config.json".to_string()]),
+            ..test_downloader()
         };
 
-        let _ = downloader.get_metadata().await.unwrap();
-        mock_metadata_path.assert();
-
+        // a bare file name matches the file in any directory
         downloader.download_model().await.unwrap();
+        assert!(Path::new(test_dir).join("config.json").exists());
+        assert!(Path::new(test_dir).join("tokenizer/config.json").exists());
+        assert!(!Path::new(test_dir).join("model.bin").exists());
 
-        mock_list_path.assert();
-        mock_model_path.assert();
+        downloader.file_filter = FileFilter::default().with_only(&["missing.json".to_string()]);
+        let error = downloader.download_model().await.unwrap_err().to_string();
+        assert!(error.contains("No file named missing.json found"));
 
-        // clean up
-        fs::remove_dir_all(&test_dir).unwrap();
+        mock_metadata.assert();
+        mock_list.assert();
+        mock_config.assert();
+        mock_tokenizer_config.assert();
+        mock_model.assert();
     }
 
     #[tokio::test]
-    async fn test_download_processor_model() {
-        let uid = &Uuid::new_v4().to_string();
-        // Populate files
-        let test_dir = format!("./src/api/test_utils/{}", uid);
-        std::fs::create_dir_all(&test_dir).unwrap();
-
-        // create fake model file
-        let model_path = Path::new(&test_dir).join("trained_model/model.onnx");
-        std::fs::create_dir_all(model_path.parent().unwrap()).unwrap();
-        let model_rpath = model_path.to_str().unwrap();
-        let model_parent = model_path.parent().unwrap().to_str().unwrap();
-        let mut file = File::create(&model_path).unwrap();
-        file.write_all(b"model").unwrap();
-
-        // create fake preprocessor file
-        let preprocessor_path =
-            Path::new(&test_dir).join("preprocessor/nested/preprocessor.joblib");
-        std::fs::create_dir_all(preprocessor_path.parent().unwrap()).unwrap();
-        let preprocessor_rpath = preprocessor_path.to_str().unwrap();
-        let preprocessor_parent = preprocessor_path
-            .parent()
-            .unwrap()
-            .parent()
-            .unwrap()
-            .to_str()
-            .unwrap();
-        let mut file = File::create(&preprocessor_path).unwrap();
-        file.write_all(b"preprocessor").unwrap();
-
-        // load and write metadata
-        let metadata_path = Path::new(&test_dir).join("metadata.json");
+    async fn test_list_files() {
+        let _env = TestEnv::lock();
         let metadata = fs::read_to_string("./src/api/test_utils/metadata.json").unwrap();
-        let mut metadata_file = File::create(metadata_path).unwrap();
-        metadata_file.write_all(metadata.as_bytes()).unwrap();
-
         let mut model_metadata: types::ModelMetadata = serde_json::from_str(&metadata).unwrap();
-        model_metadata.onnx_uri = Some(model_parent.to_string());
-        model_metadata.preprocessor_uri = Some(preprocessor_parent.to_string());
+        model_metadata.model_uri = "models/uid".to_string();
 
-        // setup server
         let mut download_server = mockito::Server::new();
-        let url = download_server.url();
-        env::set_var("OPSML_TRACKING_URI", url);
+        env::set_var("OPSML_TRACKING_URI", download_server.url());
 
-        // get model files
-        let model_files = types::ListFileResponse {
-            files: vec![model_rpath.to_string()],
+        let files = types::ListFileResponse {
+            files: vec![
+                "models/uid/config.json".to_string(),
+                "models/uid/weights/model.bin".to_string(),
+            ],
         };
 
-        let model_file_response = serde_json::to_string(&model_files).unwrap();
+        let mock_metadata = download_server
+            .mock("POST", "/opsml/models/metadata")
+            .with_status(201)
+            .with_body(serde_json::to_string(&model_metadata).unwrap())
+            .create();
+        let mock_list = download_server
+            .mock("GET", "/opsml/files/list?path=models/uid")
+            .with_status(201)
+            .with_body(serde_json::to_string(&files).unwrap())
+            .create();
+        let mock_config = download_server
+            .mock("HEAD", "/opsml/files/download?path=models/uid/config.json")
+            .with_status(200)
+            .with_header("content-length", "2048")
+            .create();
+        // no content-length, so the size is unknown
+        let mock_model = download_server
+            .mock(
+                "HEAD",
+                "/opsml/files/download?path=models/uid/weights/model.bin",
+            )
+            .with_status(405)
+            .create();
+        let mock_download = download_server
+            .mock(
+                "GET",
+                mockito::Matcher::Regex(r"^/opsml/files/download".to_string()),
+            )
+            .expect(0)
+            .create();
 
-        // get preprocessor files
-        let preprocessor_files = types::ListFileResponse {
-            files: vec![preprocessor_rpath.to_string()],
+        let downloader = ModelDownloader {
+            uid: Some("uid"),
+            write_dir: "",
+            ..test_downloader()
         };
 
-        let preprocessor_file_response = serde_json::to_string(&preprocessor_files).unwrap();
+        let listed = downloader.list_files().await.unwrap();
+        mock_metadata.assert();
+        mock_list.assert();
+        mock_config.assert();
+        mock_model.assert();
+        mock_download.assert();
 
-        // directory to write to
-        let new_dir = format!("./src/api/test_utils/{}/{}", uid, "downloaded");
+        assert_eq!(listed.len(), 2);
+        assert_eq!(listed[0].remote_path, "models/uid/config.json");
+        assert_eq!(listed[0].size, Some(2048));
+        assert_eq!(listed[1].size, None);
 
-        // mock metadata
-        let mock_metadata_path = download_server
+        let table = build_file_table(&listed, utils::TableStyle::Ascii);
+        assert!(table.contains("config.json"));
+        assert!(table.contains("models/uid/weights/model.bin"));
+        assert!(table.contains("2.0 KB"));
+        assert!(table.contains(" - "));
+    }
+
+    #[tokio::test]
+    async fn test_verify_model_files() {
+        let _env = TestEnv::lock();
+        let metadata = fs::read_to_string("./src/api/test_utils/metadata.json").unwrap();
+        let mut model_metadata: types::ModelMetadata = serde_json::from_str(&metadata).unwrap();
+        model_metadata.model_uri = "models/uid".to_string();
+
+        let mut download_server = mockito::Server::new();
+        let url = download_server.url();
+        env::set_var("OPSML_TRACKING_URI", url);
+
+        let files = types::ListFileResponse {
+            files: vec![
+                "models/uid/config.json".to_string(),
+                "models/uid/weights/model.bin".to_string(),
+            ],
+        };
+
+        let mock_metadata = download_server
             .mock("POST", "/opsml/models/metadata")
             .with_status(201)
             .with_body(serde_json::to_string(&model_metadata).unwrap())
+            .expect(5)
             .create();
-
-        // mock list model files
-        let artifact_model_path = format!("/opsml/files/list?path={}", model_parent);
-        let model_list_path = download_server
-            .mock("GET", artifact_model_path.as_str())
+        let mock_list = download_server
+            .mock("GET", "/opsml/files/list?path=models/uid")
             .with_status(201)
-            .with_body(&model_file_response)
+            .with_body(serde_json::to_string(&files).unwrap())
+            .expect(5)
             .create();
-
-        // mock list preprocessor files
-        let artifact_preprocessor_path = format!("/opsml/files/list?path={}", preprocessor_parent);
-        let preprocessor_list_path = download_server
-            .mock("GET", artifact_preprocessor_path.as_str())
-            .with_status(201)
-            .with_body(&preprocessor_file_response)
+        // sizes of "{}" and "hello world". The missing config.json is never requested
+        let mock_config = download_server
+            .mock("HEAD", "/opsml/files/download?path=models/uid/config.json")
+            .with_status(200)
+            .with_header("content-length", "2")
+            .expect(3)
             .create();
-
-        // mock model
-        let get_path = format!("/opsml/files/download?path={}", model_rpath);
-        let mock_model_path = download_server
-            .mock("GET", get_path.as_str())
-            .with_status(201)
-            .with_body(&metadata)
+        let mock_model = download_server
+            .mock(
+                "HEAD",
+                "/opsml/files/download?path=models/uid/weights/model.bin",
+            )
+            .with_status(200)
+            .with_header("content-length", "11")
+            .with_header("x-checksum", "md5:5eb63bbbe01eeed093cb22bb8f5acdc3")
+            .expect(5)
             .create();
 
-        // mock model
-        let get_path = format!("/opsml/files/download?path={}", preprocessor_rpath);
-        let mock_preprocessor_path = download_server
-            .mock("GET", get_path.as_str())
-            .with_status(201)
-            .with_body(&metadata)
-            .create();
+        let output_dir = TestDir::new();
+        let test_dir = output_dir.path();
+        fs::create_dir_all(Path::new(test_dir).join("weights")).unwrap();
+        fs::write(Path::new(test_dir).join("config.json"), "{}").unwrap();
+        fs::write(Path::new(test_dir).join("weights/model.bin"), "hello world").unwrap();
 
         let downloader = ModelDownloader {
-            name: Some("name"),
-            version: Some("version"),
-            repository: Some("repo"),
-            uid: None,
-            write_dir: &new_dir,
-            ignore_release_candidates: &false,
-            onnx: &true,
-            quantize: &false,
-            preprocessor: &true,
+            uid: Some("uid"),
+            write_dir: test_dir,
+            ..test_downloader()
         };
 
-        let _ = downloader.get_metadata().await.unwrap();
-        mock_metadata_path.assert();
+        // matching local files
+        assert!(downloader.verify_model_files().await.unwrap().is_empty());
+        downloader.verify_model().await.unwrap();
+
+        // same size, different content
+        fs::write(Path::new(test_dir).join("weights/model.bin"), "hello_world").unwrap();
+        // local paths are displayed with the platform separator
+        let model_path = Path::new("weights").join("model.bin");
+        let discrepancies = downloader.verify_model_files().await.unwrap();
+        assert_eq!(discrepancies.len(), 1);
+        assert!(
+            discrepancies[0].starts_with(&format!("{}: checksum mismatch", model_path.display()))
+        );
+
+        // different size, and a missing file
+        fs::write(Path::new(test_dir).join("weights/model.bin"), "hello").unwrap();
+        fs::remove_file(Path::new(test_dir).join("config.json")).unwrap();
+        let discrepancies = downloader.verify_model_files().await.unwrap();
+        assert_eq!(
+            discrepancies,
+            vec![
+                "config.json: missing".to_string(),
+                format!(
+                    "{}: size mismatch (local 5 bytes, server 11 bytes)",
+                    model_path.display()
+                ),
+            ]
+        );
+        let error = downloader.verify_model().await.unwrap_err().to_string();
+        assert!(error.contains("2 file(s)"));
+
+        mock_metadata.assert();
+        mock_list.assert();
+        mock_config.assert();
+        mock_model.assert();
+    }
+
+    #[tokio::test]
+    async fn test_download_model_without_metadata_file() {
+        let _env = TestEnv::lock();
+        let metadata = fs::read_to_string("./src/api/test_utils/metadata.json").unwrap();
+        let mut model_metadata: types::ModelMetadata = serde_json::from_str(&metadata).unwrap();
+        model_metadata.model_uri = "models/uid".to_string();
+
+        let mut download_server = mockito::Server::new();
+        env::set_var("OPSML_TRACKING_URI", download_server.url());
+
+        let files = types::ListFileResponse {
+            files: vec!["models/uid/model.bin".to_string()],
+        };
+        let mocks = [
+            download_server
+                .mock("POST", "/opsml/models/metadata")
+                .with_status(201)
+                .with_body(serde_json::to_string(&model_metadata).unwrap())
+                .create(),
+            download_server
+                .mock("GET", "/opsml/files/list?path=models/uid")
+                .with_status(201)
+                .with_body(serde_json::to_string(&files).unwrap())
+                .create(),
+            download_server
+                .mock("GET", "/opsml/files/download?path=models/uid/model.bin")
+                .with_status(200)
+                .with_body("model")
+                .create(),
+        ];
+
+        let output_dir = TestDir::new();
+        let test_dir = output_dir.path();
+        let downloader = ModelDownloader {
+            uid: Some("uid"),
+            write_dir: test_dir,
+            write_metadata: false,
+            ..test_downloader()
+        };
 
         downloader.download_model().await.unwrap();
+        for mock in mocks.iter() {
+            mock.assert();
+        }
 
-        model_list_path.assert();
-        preprocessor_list_path.assert();
-        mock_model_path.assert();
-        mock_preprocessor_path.assert();
-        assert!(Path::new(&test_dir)
-            .join("downloaded/nested/preprocessor.joblib")
-            .exists());
-        assert!(Path::new(&test_dir).join("downloaded/model.onnx").exists());
+        assert_eq!(
+            fs::read_to_string(Path::new(test_dir).join("model.bin")).unwrap(),
+            "model"
+        );
+        assert!(!Path::new(test_dir).join(MODEL_METADATA_FILE).exists());
+    }
 
-        // clean up
-        fs::remove_dir_all(&test_dir).unwrap();
+    #[tokio::test]
+    async fn test_resume_model_download() {
+        let _env = TestEnv::lock();
+        let metadata = fs::read_to_string("./src/api/test_utils/metadata.json").unwrap();
+        let mut model_metadata: types::ModelMetadata = serde_json::from_str(&metadata).unwrap();
+        model_metadata.model_uri = "models/uid".to_string();
+
+        let mut download_server = mockito::Server::new();
+        env::set_var("OPSML_TRACKING_URI", download_server.url());
+
+        let files = types::ListFileResponse {
+            files: vec![
+                "models/uid/config.json".to_string(),
+                "models/uid/tokenizer.json".to_string(),
+                "models/uid/weights/model.bin".to_string(),
+            ],
+        };
+
+        let mut mocks = vec![
+            download_server
+                .mock("POST", "/opsml/models/metadata")
+                .with_status(201)
+                .with_body(serde_json::to_string(&model_metadata).unwrap())
+                .create(),
+            download_server
+                .mock("GET", "/opsml/files/list?path=models/uid")
+                .with_status(201)
+                .with_body(serde_json::to_string(&files).unwrap())
+                .create(),
+            download_server
+                .mock("HEAD", "/opsml/files/download?path=models/uid/config.json")
+                .with_status(200)
+                .with_header("content-length", "2")
+                .create(),
+            download_server
+                .mock(
+                    "HEAD",
+                    "/opsml/files/download?path=models/uid/weights/model.bin",
+                )
+                .with_status(200)
+                .with_header("content-length", "11")
+                .create(),
+            // the complete file is not downloaded again
+            download_server
+                .mock("GET", "/opsml/files/download?path=models/uid/config.json")
+                .expect(0)
+                .create(),
+            // the partial file is resumed after the 5 bytes already written
+            download_server
+                .mock(
+                    "GET",
+                    "/opsml/files/download?path=models/uid/weights/model.bin",
+                )
+                .match_header("range", "bytes=5-")
+                .with_status(206)
+                .with_header("x-checksum", "md5:5eb63bbbe01eeed093cb22bb8f5acdc3")
+                .with_body(" world")
+                .create(),
+        ];
+        // the missing file is downloaded in full
+        mocks.push(
+            download_server
+                .mock(
+                    "GET",
+                    "/opsml/files/download?path=models/uid/tokenizer.json",
+                )
+                .with_status(200)
+                .with_body("tokens")
+                .create(),
+        );
+
+        // a previous run wrote config.json and part of model.bin
+        let output_dir = TestDir::new();
+        let test_dir = output_dir.path();
+        fs::create_dir_all(Path::new(test_dir).join("weights")).unwrap();
+        fs::write(Path::new(test_dir).join("config.json"), "{}").unwrap();
+        fs::write(Path::new(test_dir).join("weights/model.bin"), "hello").unwrap();
+
+        let downloader = ModelDownloader {
+            uid: Some("uid"),
+            write_dir: test_dir,
+            resume: true,
+            ..test_downloader()
+        };
+
+        let summary = downloader.download_model_summary().await.unwrap();
+        for mock in mocks.iter() {
+            mock.assert();
+        }
+
+        assert_eq!(
+            fs::read_to_string(Path::new(test_dir).join("weights/model.bin")).unwrap(),
+            "hello world"
+        );
+        assert_eq!(
+            fs::read_to_string(Path::new(test_dir).join("tokenizer.json")).unwrap(),
+            "tokens"
+        );
+        assert_eq!(summary.total_bytes, 2 + 6 + 11);
+
+        let model_bin = summary
+            .files
+            .iter()
+            .find(|file| file.remote_path.ends_with("model.bin"))
+            .unwrap();
+        assert_eq!(
+            model_bin.sha256,
+            "b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9"
+        );
     }
 }
@@ -0,0 +1,353 @@
+/// Copyright (c) Shipt, Inc.
+/// This source code is licensed under the MIT license found in the
+/// LICENSE file in the root directory of this source tree.
+use crate::api::utils;
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use lazy_static::lazy_static;
+use serde::Serialize;
+use std::env;
+use std::fmt::Display;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+lazy_static! {
+    /// Output file set by `--output-file`, opened once and kept open for every line written
+    static ref OUTPUT_FILE: Mutex<Option<(PathBuf, File)>> = Mutex::new(None);
+}
+
+#[cfg(test)]
+thread_local! {
+    /// Lines printed on this thread while a test captures output
+    static CAPTURED: std::cell::RefCell<Option<Vec<String>>> = const { std::cell::RefCell::new(None) };
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, ValueEnum)]
+pub enum OutputFormat {
+    #[default]
+    Table,
+    Json,
+    /// One compact JSON object per line (NDJSON)
+    Jsonl,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct OutputOptions {
+    pub format: OutputFormat,
+    pub compact: bool,
+    pub raw: bool,
+}
+
+impl OutputOptions {
+    /// Whether output should be decorated with headers or tables
+    pub fn is_plain(&self) -> bool {
+        self.raw || self.is_json()
+    }
+
+    /// Whether output should be rendered as JSON
+    pub fn is_json(&self) -> bool {
+        matches!(self.format, OutputFormat::Json | OutputFormat::Jsonl)
+    }
+
+    /// Serializes a value to JSON, pretty-printed unless compact output is set
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - Value to serialize
+    ///
+    /// # Returns
+    /// * `Result<String, String>` - JSON string
+    ///
+    pub fn render_json<T: Serialize + ?Sized>(&self, value: &T) -> Result<String, anyhow::Error> {
+        let json_string = if self.compact || self.format == OutputFormat::Jsonl {
+            serde_json::to_string(value)
+        } else {
+            serde_json::to_string_pretty(value)
+        };
+
+        json_string.with_context(|| "Failed to serialize output")
+    }
+
+    /// Serializes a list of items to JSON. With JSON lines output each item is
+    /// serialized individually onto its own line
+    ///
+    /// # Arguments
+    ///
+    /// * `items` - Items to serialize
+    ///
+    /// # Returns
+    /// * `Result<String, String>` - JSON string
+    ///
+    pub fn render_json_list<T: Serialize>(&self, items: &[T]) -> Result<String, anyhow::Error> {
+        if self.format != OutputFormat::Jsonl {
+            return self.render_json(items);
+        }
+
+        let lines = items
+            .iter()
+            .map(|item| serde_json::to_string(item))
+            .collect::<Result<Vec<String>, _>>()
+            .with_context(|| "Failed to serialize output")?;
+
+        Ok(lines.join("\n"))
+    }
+}
+
+/// Where rendered command output is written
+#[derive(Debug, PartialEq)]
+pub enum OutputTarget {
+    Stdout,
+    File(PathBuf),
+}
+
+impl OutputTarget {
+    /// Reads the output target from `OPSML_OUTPUT_FILE`, set by `--output-file`
+    pub fn from_env() -> Self {
+        match env::var("OPSML_OUTPUT_FILE") {
+            Ok(path) if !path.is_empty() => OutputTarget::File(PathBuf::from(path)),
+            _ => OutputTarget::Stdout,
+        }
+    }
+
+    /// Creates the output file, truncating output left by a previous run
+    pub fn init(&self) -> Result<(), anyhow::Error> {
+        if let OutputTarget::File(path) = self {
+            utils::create_dir_path(path)?;
+            let file = File::create(path)
+                .with_context(|| format!("Failed to create output file {:?}", path))?;
+            *OUTPUT_FILE.lock().unwrap() = Some((path.clone(), file));
+        }
+        Ok(())
+    }
+
+    /// Writes a line of output. Colors are stripped from output written to a file
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - Text to write
+    /// * `stdout` - Writer used for the stdout target
+    ///
+    pub fn write<W: Write>(&self, text: &str, stdout: &mut W) -> Result<(), anyhow::Error> {
+        match self {
+            OutputTarget::Stdout => {
+                writeln!(stdout, "{}", styled(text)).with_context(|| "Failed to write output")
+            }
+            OutputTarget::File(path) => {
+                let mut output_file = OUTPUT_FILE.lock().unwrap();
+
+                // reuse the file opened by `init`, opening it once if it was not initialized
+                if !matches!(output_file.as_ref(), Some((open_path, _)) if open_path == path) {
+                    let file = OpenOptions::new()
+                        .create(true)
+                        .append(true)
+                        .open(path)
+                        .with_context(|| format!("Failed to open output file {:?}", path))?;
+                    *output_file = Some((path.clone(), file));
+                }
+
+                let (_, file) = output_file.as_mut().unwrap();
+                writeln!(file, "{}", strip_ansi(text))
+                    .with_context(|| format!("Failed to write output file {:?}", path))
+            }
+        }
+    }
+}
+
+/// Writes a line of command output to stdout, or to the file set by `--output-file`
+///
+/// # Arguments
+///
+/// * `text` - Text to write
+///
+pub fn print<T: Display>(text: T) -> Result<(), anyhow::Error> {
+    #[cfg(test)]
+    if CAPTURED
+        .with(|captured| {
+            captured
+                .borrow_mut()
+                .as_mut()
+                .map(|lines| lines.push(text.to_string()))
+        })
+        .is_some()
+    {
+        return Ok(());
+    }
+
+    OutputTarget::from_env().write(&text.to_string(), &mut std::io::stdout().lock())
+}
+
+/// Captures output printed on this thread instead of writing it, until `take_captured` is called
+#[cfg(test)]
+pub fn capture() {
+    CAPTURED.with(|captured| *captured.borrow_mut() = Some(Vec::new()));
+}
+
+/// Stops capturing output and returns the lines printed since `capture`
+#[cfg(test)]
+pub fn take_captured() -> String {
+    CAPTURED
+        .with(|captured| captured.borrow_mut().take())
+        .unwrap_or_default()
+        .iter()
+        .map(|line| format!("{}\n", line))
+        .collect()
+}
+
+/// Removes colors from text when they are disabled by `--no-color` or `NO_COLOR`
+///
+/// Output written with `print` is handled already, so this is only needed for text
+/// printed directly, such as progress lines and errors
+///
+/// # Arguments
+///
+/// * `text` - Text to write
+///
+pub fn styled<T: Display>(text: T) -> String {
+    if utils::color_enabled() {
+        text.to_string()
+    } else {
+        strip_ansi(&text.to_string())
+    }
+}
+
+/// Removes ANSI escape sequences (colors and styles) from text
+///
+/// # Arguments
+///
+/// * `text` - Text to strip
+///
+fn strip_ansi(text: &str) -> String {
+    let mut stripped = String::with_capacity(text.len());
+    let mut chars = text.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\u{1b}' {
+            stripped.push(c);
+            continue;
+        }
+
+        // skip the control sequence up to and including its final byte
+        if chars.next() == Some('[') {
+            for c in chars.by_ref() {
+                if ('@'..='~').contains(&c) {
+                    break;
+                }
+            }
+        }
+    }
+
+    stripped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::test_utils::{TestDir, TestEnv};
+    use crate::api::types;
+    use owo_colors::OwoColorize;
+    use std::collections::HashMap;
+    use std::fs;
+
+    #[test]
+    fn test_print_to_output_file() {
+        let _env = TestEnv::lock();
+        let output_dir = TestDir::new();
+        let test_dir = output_dir.path();
+        let path = PathBuf::from(test_dir).join("output.txt");
+
+        env::set_var("OPSML_OUTPUT_FILE", &path);
+        let target = OutputTarget::from_env();
+        assert_eq!(target, OutputTarget::File(path.clone()));
+
+        // output from a previous run is truncated
+        fs::create_dir_all(test_dir).unwrap();
+        fs::write(&path, "stale").unwrap();
+        target.init().unwrap();
+
+        let mut stdout = Vec::new();
+        target
+            .write(
+                &format!("Listing cards from {} registry", "model".bold().green()),
+                &mut stdout,
+            )
+            .unwrap();
+        target.write("│ name │", &mut stdout).unwrap();
+
+        // nothing reaches stdout while an output file is set
+        assert!(stdout.is_empty());
+
+        env::remove_var("OPSML_OUTPUT_FILE");
+        assert_eq!(OutputTarget::from_env(), OutputTarget::Stdout);
+        OutputTarget::Stdout.write("│ name │", &mut stdout).unwrap();
+        assert_eq!(String::from_utf8(stdout).unwrap(), "│ name │\n");
+
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "Listing cards from model registry\n│ name │\n"
+        );
+    }
+
+    #[test]
+    fn test_styled() {
+        let _env = TestEnv::lock();
+        let text = format!("Listing cards from {} registry", "model".bold().green());
+        env::set_var("NO_COLOR", "1");
+        let plain = styled(&text);
+        env::remove_var("NO_COLOR");
+        let colored = styled(&text);
+
+        assert_eq!(plain, "Listing cards from model registry");
+        assert_eq!(colored, text);
+    }
+
+    #[test]
+    fn test_to_json() {
+        let response = types::ListCardResponse {
+            cards: vec![types::Card {
+                name: "test".to_string(),
+                repository: "repo".to_string(),
+                date: None,
+                contact: "fake_email".to_string(),
+                version: "1.0.0".to_string(),
+                uid: "uid".to_string(),
+                tags: HashMap::new(),
+            }],
+        };
+
+        let compact = OutputOptions {
+            format: OutputFormat::Json,
+            compact: true,
+            raw: false,
+        };
+        assert_eq!(
+            compact.render_json(&response).unwrap(),
+            r#"{"cards":[{"name":"test","repository":"repo","date":null,"contact":"fake_email","version":"1.0.0","uid":"uid","tags":{}}]}"#
+        );
+
+        let pretty = OutputOptions {
+            format: OutputFormat::Json,
+            compact: false,
+            raw: false,
+        };
+        assert_eq!(
+            pretty.render_json(&response).unwrap(),
+            concat!(
+                "{\n",
+                "  \"cards\": [\n",
+                "    {\n",
+                "      \"name\": \"test\",\n",
+                "      \"repository\": \"repo\",\n",
+                "      \"date\": null,\n",
+                "      \"contact\": \"fake_email\",\n",
+                "      \"version\": \"1.0.0\",\n",
+                "      \"uid\": \"uid\",\n",
+                "      \"tags\": {}\n",
+                "    }\n",
+                "  ]\n",
+                "}",
+            )
+        );
+    }
+}
@@ -1,18 +1,63 @@
 /// Copyright (c) Shipt, Inc.
 /// This source code is licensed under the MIT license found in the
 /// LICENSE file in the root directory of this source tree.
+use crate::api::cache;
+use crate::api::checksum::{self, ChecksumAlgo, ExpectedChecksum};
+use crate::api::output;
 use crate::api::types;
 use crate::api::utils;
 use anyhow::Context;
 use futures_util::StreamExt;
 use owo_colors::OwoColorize;
-use reqwest::{self, Response};
+use reqwest::header::{CONTENT_LENGTH, LAST_MODIFIED, RANGE};
+use reqwest::{self, Method, RequestBuilder, Response, StatusCode};
 use serde::Serialize;
+use serde_json::Value;
+use std::io::SeekFrom;
+use std::time::Instant;
 use std::{format, path::Path};
+use tokio::io::{AsyncSeekExt, AsyncWrite, AsyncWriteExt};
+
+const CHECKSUM_HEADER: &str = "x-checksum";
 
 pub struct RouteHelper {}
 
 impl RouteHelper {
+    /// Sends a request, logging it to stderr first when verbose output is enabled
+    ///
+    /// With `--debug-headers`, the response status and selected headers are logged after.
+    /// Secrets in the url and headers are redacted before logging
+    ///
+    /// # Arguments
+    ///
+    /// * `builder` - Request to send
+    ///
+    async fn send(builder: RequestBuilder) -> reqwest::Result<Response> {
+        let (client, request) = builder.build_split();
+        let request = request?;
+
+        if utils::verbose() {
+            eprintln!(
+                "> {} {}",
+                request.method(),
+                utils::redact_url(request.url())
+            );
+            for header in utils::redact_headers(request.headers()) {
+                eprintln!("> {}", header);
+            }
+        }
+
+        let response = client.execute(request).await?;
+
+        if utils::debug_headers() {
+            for line in utils::debug_response_lines(response.status(), response.headers()) {
+                eprintln!("{}", line);
+            }
+        }
+
+        Ok(response)
+    }
+
     /// async post request for metadata
     ///
     /// # Arguments
@@ -24,8 +69,7 @@ impl RouteHelper {
         url: &str,
         payload: &T,
     ) -> Result<Response, anyhow::Error> {
-        let (client, parsed_url) = utils::create_client(url).await.unwrap();
-        let msg = client.post(parsed_url).json(payload).send();
+        let msg = Self::send(utils::api_request(Method::POST, url)?.json(payload));
 
         match msg.await {
             Ok(response) => Ok(response),
@@ -43,8 +87,7 @@ impl RouteHelper {
     /// * `url` - A string slice
     ///
     pub async fn make_get_request(url: &str) -> Result<Response, anyhow::Error> {
-        let (client, parsed_url) = utils::create_client(url).await.unwrap();
-        let msg = client.get(parsed_url).send();
+        let msg = Self::send(utils::api_request(Method::GET, url)?);
 
         match msg.await {
             Ok(response) => Ok(response),
@@ -55,6 +98,53 @@ impl RouteHelper {
         }
     }
 
+    /// Extracts an error message from a failed response body
+    ///
+    /// JSON error envelopes (`{"error": ..., "detail": ...}`) are reduced to their `detail`
+    /// field. Any other body is returned as-is.
+    ///
+    /// # Arguments
+    ///
+    /// * `status` - Response status
+    /// * `body` - Response body
+    ///
+    /// # Returns
+    /// * `String` - Error message
+    ///
+    pub fn parse_error_body(status: StatusCode, body: &str) -> String {
+        let detail = serde_json::from_str::<types::ServerError>(body)
+            .ok()
+            .and_then(|server_error| server_error.detail);
+
+        match detail {
+            Some(Value::String(detail)) => detail,
+            Some(detail) => detail.to_string(),
+            None if body.trim().is_empty() => status.to_string(),
+            None => body.to_string(),
+        }
+    }
+
+    /// Builds an error from a failed response
+    ///
+    /// # Arguments
+    ///
+    /// * `response` - Failed response
+    /// * `message` - Message describing the failed request
+    ///
+    /// # Returns
+    /// * `anyhow::Error` - Error including the server's error message
+    ///
+    pub async fn response_error(response: Response, message: &str) -> anyhow::Error {
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+
+        anyhow::Error::msg(format!(
+            "{}: {}",
+            message,
+            RouteHelper::parse_error_body(status, &body)
+        ))
+    }
+
     /// Lists files associated with a model
     ///
     /// # Arguments
@@ -64,47 +154,604 @@ impl RouteHelper {
     /// # Returns
     /// * `Result<types::ListFileResponse, String>` - Result of file download
     ///
-    pub async fn list_files(rpath: &Path) -> Result<types::ListFileResponse, anyhow::Error> {
-        let file_url = format!(
-            "{}?path={}",
-            utils::OpsmlPaths::ListFile.as_str(),
-            rpath.to_str().unwrap()
-        );
+    pub async fn list_files(rpath: &str) -> Result<types::ListFileResponse, anyhow::Error> {
+        let file_url = format!("{}?path={}", utils::OpsmlPaths::ListFile.as_str(), rpath);
 
         let response = RouteHelper::make_get_request(&file_url).await?;
+        if !response.status().is_success() {
+            return Err(RouteHelper::response_error(response, "Failed to list files").await);
+        }
         let files = response.json::<types::ListFileResponse>().await?;
         Ok(files)
     }
 
-    /// Downloads a stream to a file
+    /// Builds the url a remote file is downloaded from
     ///
     /// # Arguments
     ///
-    /// * `response` - Response object
-    /// * `filename` - Path to save file to
+    /// * `rpath` - Remote path to file
+    ///
+    pub fn download_url(rpath: &str) -> String {
+        format!("{}?path={}", utils::OpsmlPaths::Download.as_str(), rpath)
+    }
+
+    /// Gets metadata for a remote file from a HEAD request to the download endpoint
+    ///
+    /// # Arguments
+    ///
+    /// * `rpath` - Remote path to file
     ///
     /// # Returns
-    /// * `Result<(), String>` - Result of file download
+    /// * `Result<types::FileMeta, String>` - Size, last modified time and checksum. Fields are None when the server does not report them
     ///
-    pub async fn download_stream_to_file(
+    pub async fn head_file(rpath: &str) -> Result<types::FileMeta, anyhow::Error> {
+        let file_url = RouteHelper::download_url(rpath);
+        let response = Self::send(utils::api_request(Method::HEAD, &file_url)?)
+            .await
+            .with_context(|| format!("Failed to make head request for {}", rpath))?;
+
+        // servers that do not support HEAD leave the metadata unknown
+        if !response.status().is_success() {
+            return Ok(types::FileMeta::default());
+        }
+
+        let header = |name| {
+            response
+                .headers()
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(|value| value.to_string())
+        };
+
+        Ok(types::FileMeta {
+            size: header(CONTENT_LENGTH).and_then(|length| length.parse::<u64>().ok()),
+            last_modified: header(LAST_MODIFIED),
+            checksum: RouteHelper::expected_checksum(&response)?,
+        })
+    }
+
+    /// async get request for a file download
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - A string slice
+    ///
+    pub async fn make_download_request(url: &str) -> Result<Response, anyhow::Error> {
+        let msg = Self::send(utils::download_request(url)?);
+
+        match msg.await {
+            Ok(response) => Ok(response),
+            Err(e) => Err(anyhow::Error::msg(format!(
+                "Failed to make download request: {}",
+                e
+            ))),
+        }
+    }
+
+    /// async get request for the remainder of a file, starting at `start` bytes
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - A string slice
+    /// * `start` - Byte offset to resume from
+    ///
+    pub async fn make_range_request(url: &str, start: u64) -> Result<Response, anyhow::Error> {
+        let msg =
+            Self::send(utils::download_request(url)?.header(RANGE, format!("bytes={}-", start)));
+
+        match msg.await {
+            Ok(response) => Ok(response),
+            Err(e) => Err(anyhow::Error::msg(format!(
+                "Failed to make range request: {}",
+                e
+            ))),
+        }
+    }
+
+    /// Requests the rest of an interrupted download
+    ///
+    /// Rate-limited and transient gateway responses are retried after the wait their
+    /// `Retry-After` header asks for. Retries count towards the download's `attempt`
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - A string slice
+    /// * `start` - Byte offset to resume from
+    /// * `attempt` - Retries made so far for this download
+    ///
+    async fn resume_request(
+        url: &str,
+        start: u64,
+        attempt: &mut u32,
+    ) -> Result<Response, anyhow::Error> {
+        let mut retry_start: Option<Instant> = None;
+
+        loop {
+            let response = RouteHelper::make_range_request(url, start).await;
+
+            if let Some(start) = retry_start.take() {
+                utils::charge_retry(start.elapsed());
+            }
+
+            let response = response?;
+            if !utils::is_retryable_status(response.status())
+                || *attempt >= utils::max_retries()
+                || utils::retry_budget_exhausted()
+            {
+                return Ok(response);
+            }
+
+            *attempt += 1;
+            retry_start = Some(Instant::now());
+            eprintln!(
+                "Resuming download returned {}, retrying ({}/{})",
+                response.status(),
+                attempt,
+                utils::max_retries()
+            );
+            utils::wait_before_retry(Some(&response), utils::RETRY_DELAY * *attempt).await;
+        }
+    }
+
+    /// Reads the checksum the server provides for a download, if any
+    ///
+    /// # Arguments
+    ///
+    /// * `response` - Download response
+    ///
+    /// # Returns
+    /// * `Result<Option<ExpectedChecksum>, String>` - Expected checksum
+    ///
+    fn expected_checksum(response: &Response) -> Result<Option<ExpectedChecksum>, anyhow::Error> {
+        match response.headers().get(CHECKSUM_HEADER) {
+            Some(value) => {
+                let value = value.to_str().with_context(|| "Invalid checksum header")?;
+                Ok(Some(ExpectedChecksum::parse(
+                    value,
+                    utils::checksum_algo(),
+                )?))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Writes a response stream to a writer, updating the running size and checksum
+    ///
+    /// # Arguments
+    ///
+    /// * `response` - Response object
+    /// * `file` - Writer to append to
+    /// * `hasher` - Running checksums of written bytes
+    /// * `size` - Running count of written bytes
+    ///
+    async fn write_stream<W: AsyncWrite + Unpin>(
         response: Response,
-        filename: &Path,
+        file: &mut W,
+        hasher: &mut checksum::DownloadHasher,
+        size: &mut u64,
     ) -> Result<(), anyhow::Error> {
         let mut response_stream = response.bytes_stream();
-        let mut file = tokio::fs::File::create(filename).await.unwrap();
 
         while let Some(item) = response_stream.next().await {
-            let chunk =
-                item.with_context(|| format!("failed to read response for {:?}", filename))?;
-            tokio::io::copy(&mut chunk.as_ref(), &mut file)
+            let chunk = item.with_context(|| "failed to read response stream")?;
+            tokio::io::copy(&mut chunk.as_ref(), file)
                 .await
-                .with_context(|| format!("failed to write response for {:?}", filename))?;
+                .with_context(|| "failed to write response stream")?;
+            hasher.update(&chunk);
+            *size += chunk.len() as u64;
         }
         Ok(())
     }
 
+    /// Downloads a stream to a file
+    ///
+    /// If the stream fails partway, the file is re-requested with a `Range` header
+    /// starting at the bytes already written, up to `utils::max_retries` times.
+    /// When the server sends a checksum header, the written file is verified against it.
+    ///
+    /// # Arguments
+    ///
+    /// * `response` - Response object
+    /// * `url` - Url the response was requested from
+    /// * `filename` - Path to save file to
+    ///
+    /// # Returns
+    /// * `Result<types::DownloadedFile, String>` - Size and checksum of the written file
+    ///
+    pub async fn download_stream_to_file(
+        response: Response,
+        url: &str,
+        filename: &Path,
+    ) -> Result<types::DownloadedFile, anyhow::Error> {
+        let mut file = tokio::fs::File::create(filename)
+            .await
+            .with_context(|| format!("Failed to create file {:?}", filename))?;
+        let expected = RouteHelper::expected_checksum(&response)?;
+        let algo = expected
+            .as_ref()
+            .map_or(utils::checksum_algo(), |expected| expected.algo);
+        let mut hasher = checksum::DownloadHasher::new(algo);
+        let mut size: u64 = 0;
+        let mut response = response;
+        let mut attempt = 0;
+        let mut retry_start: Option<Instant> = None;
+
+        loop {
+            let result =
+                RouteHelper::write_stream(response, &mut file, &mut hasher, &mut size).await;
+
+            if let Some(start) = retry_start.take() {
+                utils::charge_retry(start.elapsed());
+            }
+
+            match result {
+                Ok(()) => break,
+                Err(e) if attempt < utils::max_retries() && !utils::retry_budget_exhausted() => {
+                    attempt += 1;
+                    retry_start = Some(Instant::now());
+                    eprintln!(
+                        "Download of {:?} interrupted after {} bytes, retrying ({}/{}): {}",
+                        filename,
+                        size,
+                        attempt,
+                        utils::max_retries(),
+                        e
+                    );
+
+                    response = RouteHelper::resume_request(url, size, &mut attempt).await?;
+
+                    // server ignored the range and is sending the whole file again
+                    if response.status() == StatusCode::OK {
+                        file.set_len(0).await?;
+                        file.seek(SeekFrom::Start(0)).await?;
+                        hasher = checksum::DownloadHasher::new(algo);
+                        size = 0;
+                    } else if response.status() != StatusCode::PARTIAL_CONTENT {
+                        return Err(anyhow::Error::msg(format!(
+                            "Failed to resume download for {:?}: {}",
+                            filename,
+                            response.status()
+                        )));
+                    }
+                }
+                Err(e) => {
+                    return Err(e).with_context(|| format!("failed to download {:?}", filename))
+                }
+            }
+        }
+
+        let (checksum, sha256) = hasher.finalize();
+        if let Some(expected) = expected {
+            expected
+                .verify(&checksum)
+                .with_context(|| format!("Failed to verify {:?}", filename))?;
+        }
+
+        Ok(types::DownloadedFile {
+            size,
+            checksum,
+            sha256,
+        })
+    }
+
+    /// Downloads a stream to a writer such as stdout
+    ///
+    /// Interrupted streams are resumed with a `Range` request like `download_stream_to_file`,
+    /// but bytes already written cannot be taken back, so the server must support ranges.
+    /// Nothing is printed to stdout.
+    ///
+    /// # Arguments
+    ///
+    /// * `response` - Response object
+    /// * `url` - Url the response was requested from
+    /// * `writer` - Writer to copy bytes to
+    ///
+    /// # Returns
+    /// * `Result<types::DownloadedFile, String>` - Size and checksum of the written bytes
+    ///
+    pub async fn download_stream_to_writer<W: AsyncWrite + Unpin>(
+        response: Response,
+        url: &str,
+        writer: &mut W,
+    ) -> Result<types::DownloadedFile, anyhow::Error> {
+        let expected = RouteHelper::expected_checksum(&response)?;
+        let algo = expected
+            .as_ref()
+            .map_or(utils::checksum_algo(), |expected| expected.algo);
+        let mut hasher = checksum::DownloadHasher::new(algo);
+        let mut size: u64 = 0;
+        let mut response = response;
+        let mut attempt = 0;
+        let mut retry_start: Option<Instant> = None;
+
+        loop {
+            let result = RouteHelper::write_stream(response, writer, &mut hasher, &mut size).await;
+
+            if let Some(start) = retry_start.take() {
+                utils::charge_retry(start.elapsed());
+            }
+
+            match result {
+                Ok(()) => break,
+                Err(e) if attempt < utils::max_retries() && !utils::retry_budget_exhausted() => {
+                    attempt += 1;
+                    retry_start = Some(Instant::now());
+                    eprintln!(
+                        "Download interrupted after {} bytes, retrying ({}/{}): {}",
+                        size,
+                        attempt,
+                        utils::max_retries(),
+                        e
+                    );
+
+                    response = RouteHelper::resume_request(url, size, &mut attempt).await?;
+
+                    if response.status() != StatusCode::PARTIAL_CONTENT {
+                        return Err(anyhow::Error::msg(format!(
+                            "Failed to resume download: {}. The server must support range requests to resume a streamed download",
+                            response.status()
+                        )));
+                    }
+                }
+                Err(e) => return Err(e).with_context(|| "failed to download file"),
+            }
+        }
+
+        writer
+            .flush()
+            .await
+            .with_context(|| "failed to flush output")?;
+
+        // bytes are already written, but a mismatch still fails the command
+        let (checksum, sha256) = hasher.finalize();
+        if let Some(expected) = expected {
+            expected.verify(&checksum)?;
+        }
+
+        Ok(types::DownloadedFile {
+            size,
+            checksum,
+            sha256,
+        })
+    }
+
+    /// Downloads an artifact file to a writer
+    ///
+    /// # Arguments
+    ///
+    /// * `rpath` - Remote path to file
+    /// * `writer` - Writer to copy bytes to
+    ///
+    /// # Returns
+    /// * `Result<types::DownloadedFile, String>` - Size and checksum of the downloaded file
+    ///
+    pub async fn download_file_to_writer<W: AsyncWrite + Unpin>(
+        rpath: &str,
+        writer: &mut W,
+    ) -> Result<types::DownloadedFile, anyhow::Error> {
+        let model_url = RouteHelper::download_url(rpath);
+        let response = RouteHelper::make_download_request(&model_url).await?;
+
+        if response.status().is_success() {
+            RouteHelper::download_stream_to_writer(response, &model_url, writer).await
+        } else {
+            Err(RouteHelper::response_error(response, "Failed to download file").await)
+        }
+    }
+
     /// Downloads an artifact file
     ///
+    /// When `OPSML_FILE_TIMEOUT` is set, a download that takes longer fails instead of
+    /// blocking the files downloaded alongside it
+    ///
+    /// # Arguments
+    ///
+    /// * `lpath` - path to save file to
+    /// * `rpath` - remote path of file
+    ///
+    /// # Returns
+    /// * `Result<types::DownloadedFile, String>` - Size and checksum of the downloaded file
+    ///
+    pub async fn download_file(
+        lpath: &Path,
+        rpath: &str,
+    ) -> Result<types::DownloadedFile, anyhow::Error> {
+        let Some(file_timeout) = utils::file_timeout() else {
+            return RouteHelper::download_file_untimed(lpath, rpath).await;
+        };
+
+        tokio::time::timeout(
+            file_timeout,
+            RouteHelper::download_file_untimed(lpath, rpath),
+        )
+        .await
+        .map_err(|_| {
+            anyhow::Error::msg(format!(
+                "Timed out downloading {} after {}s",
+                rpath,
+                file_timeout.as_secs()
+            ))
+        })?
+    }
+
+    /// Resumes a partially downloaded file from the bytes already written
+    ///
+    /// The bytes on disk are hashed first, so the checksum covers the whole file. When the
+    /// server ignores the range and sends the whole file, the file is rewritten instead
+    ///
+    /// # Arguments
+    ///
+    /// * `lpath` - path of the partial file
+    /// * `rpath` - remote path of file
+    ///
+    /// # Returns
+    /// * `Result<types::DownloadedFile, String>` - Size and checksum of the complete file
+    ///
+    pub async fn resume_file(
+        lpath: &Path,
+        rpath: &str,
+    ) -> Result<types::DownloadedFile, anyhow::Error> {
+        let url = RouteHelper::download_url(rpath);
+        let mut size = std::fs::metadata(lpath)
+            .with_context(|| format!("Failed to read {:?}", lpath))?
+            .len();
+        let response = RouteHelper::make_range_request(&url, size).await?;
+
+        if response.status() == StatusCode::OK {
+            return RouteHelper::download_stream_to_file(response, &url, lpath).await;
+        }
+        if response.status() != StatusCode::PARTIAL_CONTENT {
+            return Err(RouteHelper::response_error(response, "Failed to resume download").await);
+        }
+
+        let expected = RouteHelper::expected_checksum(&response)?;
+        let algo = expected
+            .as_ref()
+            .map_or(utils::checksum_algo(), |expected| expected.algo);
+        let mut hasher = checksum::DownloadHasher::new(algo);
+        hasher.update_file(lpath)?;
+
+        if !utils::quiet() {
+            println!("Resuming file: {:?} from {} bytes", lpath, size);
+        }
+        let mut file = tokio::fs::OpenOptions::new()
+            .append(true)
+            .open(lpath)
+            .await
+            .with_context(|| format!("Failed to open {:?}", lpath))?;
+        RouteHelper::write_stream(response, &mut file, &mut hasher, &mut size)
+            .await
+            .with_context(|| format!("failed to download {:?}", lpath))?;
+        file.flush().await?;
+
+        let (checksum, sha256) = hasher.finalize();
+        if let Some(expected) = expected {
+            expected
+                .verify(&checksum)
+                .with_context(|| format!("Failed to verify {:?}", lpath))?;
+        }
+
+        Ok(types::DownloadedFile {
+            size,
+            checksum,
+            sha256,
+        })
+    }
+
+    /// Downloads an artifact file without a time limit
+    ///
+    /// A download that completes with 0 bytes is checked against the size the server reports
+    /// for the file. When the server reports a non-zero size, the empty file is deleted and
+    /// the download retried, up to `utils::max_retries` times.
+    ///
+    /// # Arguments
+    ///
+    /// * `lpath` - path to save file to
+    /// * `rpath` - remote path of file
+    ///
+    /// # Returns
+    /// * `Result<types::DownloadedFile, String>` - Size and checksum of the downloaded file
+    ///
+    async fn download_file_untimed(
+        lpath: &Path,
+        rpath: &str,
+    ) -> Result<types::DownloadedFile, anyhow::Error> {
+        let mut attempt = 0;
+        let mut retry_start: Option<Instant> = None;
+
+        loop {
+            let downloaded = RouteHelper::download_file_once(lpath, rpath).await;
+            if let Some(start) = retry_start.take() {
+                utils::charge_retry(start.elapsed());
+            }
+
+            let downloaded = downloaded?;
+            if downloaded.size > 0 {
+                return Ok(downloaded);
+            }
+
+            let expected_size = match RouteHelper::head_file(rpath).await?.size {
+                Some(size) if size > 0 => size,
+                _ => return Ok(downloaded),
+            };
+
+            std::fs::remove_file(lpath)
+                .with_context(|| format!("Failed to remove empty file {:?}", lpath))?;
+
+            if attempt >= utils::max_retries() || utils::retry_budget_exhausted() {
+                return Err(anyhow::Error::msg(format!(
+                    "Downloaded 0 bytes of {}, expected {} bytes",
+                    rpath, expected_size
+                )));
+            }
+
+            attempt += 1;
+            retry_start = Some(Instant::now());
+            eprintln!(
+                "Download of {} returned 0 bytes, expected {} bytes, retrying ({}/{})",
+                rpath,
+                expected_size,
+                attempt,
+                utils::max_retries()
+            );
+        }
+    }
+
+    /// Restores a file from the download cache without downloading it
+    ///
+    /// The checksum of the file is looked up with a HEAD request, so a cache hit never sends
+    /// the download request
+    ///
+    /// # Arguments
+    ///
+    /// * `lpath` - path to restore the file to
+    /// * `rpath` - remote path of the file
+    ///
+    /// # Returns
+    /// * `Result<Option<types::DownloadedFile>, String>` - Restored file, or None on a cache miss
+    ///
+    async fn restore_cached(
+        lpath: &Path,
+        rpath: &str,
+    ) -> Result<Option<types::DownloadedFile>, anyhow::Error> {
+        let Some(cache_dir) = utils::cache_dir() else {
+            return Ok(None);
+        };
+        let Some(expected) = RouteHelper::head_file(rpath).await?.checksum else {
+            return Ok(None);
+        };
+        let Some(cached) = cache::cache_path(&cache_dir, &expected) else {
+            return Ok(None);
+        };
+
+        // hashing and copying a large file blocks, so keep it off the runtime thread that
+        // drives the other downloads
+        let lpath = lpath.to_path_buf();
+        tokio::task::spawn_blocking(move || -> Result<_, anyhow::Error> {
+            let Some(size) = cache::restore(&cached, &lpath, &expected)? else {
+                return Ok(None);
+            };
+
+            let sha256 = match expected.algo {
+                ChecksumAlgo::Sha256 => expected.digest.clone(),
+                _ => checksum::file_checksum(&lpath, ChecksumAlgo::Sha256)?,
+            };
+
+            Ok(Some(types::DownloadedFile {
+                size,
+                checksum: expected.digest,
+                sha256,
+            }))
+        })
+        .await
+        .with_context(|| "Failed to restore file from cache")?
+    }
+
+    /// Downloads an artifact file once
+    ///
+    /// When `OPSML_CACHE_DIR` is set and the server reports a checksum, the file is restored
+    /// from the cache instead of downloaded, and fresh downloads are added to it
+    ///
     /// # Arguments
     ///
     /// * `url` - url of opsml server
@@ -112,25 +759,59 @@ impl RouteHelper {
     /// * `local_save_path` - path to save model to
     ///
     /// # Returns
-    /// * `Result<(), String>` - Result of file download
+    /// * `Result<types::DownloadedFile, String>` - Size and checksum of the downloaded file
     ///
-    pub async fn download_file(lpath: &Path, rpath: &str) -> Result<(), anyhow::Error> {
+    async fn download_file_once(
+        lpath: &Path,
+        rpath: &str,
+    ) -> Result<types::DownloadedFile, anyhow::Error> {
         let filename = lpath.file_name().unwrap().to_str().unwrap().to_string();
-        let model_url = format!("{}?path={}", utils::OpsmlPaths::Download.as_str(), rpath);
-        let response = RouteHelper::make_get_request(&model_url).await?;
 
-        if response.status().is_success() {
-            println!("Downloading file: {}, {}", filename.green(), rpath);
-            RouteHelper::download_stream_to_file(response, lpath).await?;
-        } else {
-            let error_message = format!(
-                "Failed to download model: {}",
-                response.text().await.unwrap().red()
+        if let Some(restored) = RouteHelper::restore_cached(lpath, rpath).await? {
+            if !utils::quiet() {
+                println!(
+                    "Using cached file: {}, {}",
+                    output::styled(filename.green()),
+                    rpath
+                );
+            }
+            return Ok(restored);
+        }
+
+        let model_url = RouteHelper::download_url(rpath);
+        let response = RouteHelper::make_download_request(&model_url).await?;
+
+        if !response.status().is_success() {
+            return Err(RouteHelper::response_error(response, "Failed to download model").await);
+        }
+
+        let expected = RouteHelper::expected_checksum(&response)?;
+        let cached = utils::cache_dir()
+            .zip(expected.as_ref())
+            .and_then(|(cache_dir, expected)| cache::cache_path(&cache_dir, expected));
+
+        if !utils::quiet() {
+            println!(
+                "Downloading file: {}, {}",
+                output::styled(filename.green()),
+                rpath
             );
-            return Err(anyhow::anyhow!(error_message));
         }
+        let downloaded = RouteHelper::download_stream_to_file(response, &model_url, lpath).await?;
 
-        Ok(())
+        // a failure to populate the cache should not fail the download
+        if let Some(cached) = cached {
+            let stored_path = lpath.to_path_buf();
+            let stored = tokio::task::spawn_blocking(move || cache::store(&stored_path, &cached))
+                .await
+                .map_err(anyhow::Error::from)
+                .and_then(|stored| stored);
+            if let Err(e) = stored {
+                eprintln!("Failed to cache {:?}: {:#}", lpath, e);
+            }
+        }
+
+        Ok(downloaded)
     }
 
     /// Parses stream response
@@ -158,10 +839,14 @@ impl RouteHelper {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::api::test_utils::{TestDir, TestEnv};
     use assert_json_diff::assert_json_eq;
 
     use std::env;
     use std::fs;
+    use std::io::Write;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
     use tokio;
 
     #[tokio::test]
@@ -185,6 +870,144 @@ mod tests {
         mock_get_path.assert();
     }
 
+    #[tokio::test]
+    async fn test_accept_header_api_version() {
+        let _env = TestEnv::lock();
+        let mut download_server = mockito::Server::new();
+        let get_path = format!("{}/get", download_server.url());
+
+        let mock_default = download_server
+            .mock("GET", "/get")
+            .match_header("accept", "application/json; version=1")
+            .with_status(200)
+            .create();
+        RouteHelper::make_get_request(&get_path).await.unwrap();
+        mock_default.assert();
+
+        env::set_var("OPSML_API_VERSION", "2");
+        let mock_configured = download_server
+            .mock("GET", "/get")
+            .match_header("accept", "application/json; version=2")
+            .with_status(200)
+            .create();
+        let result = RouteHelper::make_get_request(&get_path).await;
+        env::remove_var("OPSML_API_VERSION");
+
+        result.unwrap();
+        mock_configured.assert();
+    }
+
+    #[tokio::test]
+    async fn test_debug_response_headers() {
+        let mut download_server = mockito::Server::new();
+        let get_path = format!("{}/get", download_server.url());
+
+        let mock_get = download_server
+            .mock("GET", "/get")
+            .with_status(403)
+            .with_header("content-type", "text/html")
+            .with_header("server", "envoy")
+            .with_header("set-cookie", "session=secret")
+            .with_body("denied")
+            .create();
+
+        let response = RouteHelper::make_get_request(&get_path).await.unwrap();
+        mock_get.assert();
+
+        let lines = utils::debug_response_lines(response.status(), response.headers());
+        assert_eq!(
+            lines,
+            [
+                "< 403 Forbidden",
+                "< content-type: text/html",
+                "< content-length: 6",
+                "< server: envoy",
+            ]
+        );
+    }
+
+    /// Starts a keep-alive http server answering every request with `{}`
+    ///
+    /// # Returns
+    /// * `(String, Arc<AtomicUsize>)` - Server url, and the number of connections accepted
+    fn start_counting_server() -> (String, Arc<AtomicUsize>) {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let connections = Arc::new(AtomicUsize::new(0));
+
+        let accepted = connections.clone();
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(stream) = stream else { break };
+                accepted.fetch_add(1, Ordering::SeqCst);
+                std::thread::spawn(move || {
+                    let mut reader = std::io::BufReader::new(stream.try_clone().unwrap());
+                    let mut stream = stream;
+                    loop {
+                        // read one request head, then answer it and wait for the next
+                        let mut line = String::new();
+                        loop {
+                            line.clear();
+                            match std::io::BufRead::read_line(&mut reader, &mut line) {
+                                Ok(0) | Err(_) => return,
+                                Ok(_) if line == "\r\n" => break,
+                                Ok(_) => {}
+                            }
+                        }
+                        let response = "HTTP/1.1 200 OK\r\ncontent-length: 2\r\n\r\n{}";
+                        if stream.write_all(response.as_bytes()).is_err() {
+                            return;
+                        }
+                    }
+                });
+            }
+        });
+
+        (url, connections)
+    }
+
+    #[tokio::test]
+    async fn test_shared_client_reuses_connections() {
+        let (url, connections) = start_counting_server();
+        let get_path = format!("{}/get", url);
+
+        // every request in a scope is sent over the one pooled connection
+        utils::with_shared_client(async {
+            for _ in 0..3 {
+                RouteHelper::make_get_request(&get_path).await?;
+            }
+            Ok(())
+        })
+        .await
+        .unwrap();
+        assert_eq!(connections.load(Ordering::SeqCst), 1);
+
+        // outside a scope each request opens its own connection
+        for _ in 0..2 {
+            RouteHelper::make_get_request(&get_path).await.unwrap();
+        }
+        assert_eq!(connections.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_user_agent_header() {
+        let mut download_server = mockito::Server::new();
+        let url = download_server.url();
+
+        let get_path = format!("{}/get", url);
+        let mock_get_path = download_server
+            .mock("GET", "/get")
+            .match_header(
+                "user-agent",
+                format!("opsml-cli/{}", env!("CARGO_PKG_VERSION")).as_str(),
+            )
+            .with_status(200)
+            .create();
+
+        let _ = RouteHelper::make_get_request(&get_path).await.unwrap();
+        mock_get_path.assert();
+    }
+
     #[tokio::test]
     async fn test_post_request() {
         let mut download_server = mockito::Server::new();
@@ -219,6 +1042,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_list_files() {
+        let _env = TestEnv::lock();
         let mut download_server = mockito::Server::new();
         let url = download_server.url();
         env::set_var("OPSML_TRACKING_URI", url);
@@ -238,10 +1062,460 @@ mod tests {
             .with_body(&files)
             .create();
 
-        let file_response = RouteHelper::list_files(Path::new("files")).await.unwrap();
+        let file_response = RouteHelper::list_files("files").await.unwrap();
         mock_list_files.assert();
 
         // assert structs are the same
         assert_json_eq!(list_files, file_response);
     }
+
+    #[tokio::test]
+    async fn test_download_ignores_request_timeout() {
+        let _env = TestEnv::lock();
+        let mut download_server = mockito::Server::new();
+        let url = download_server.url();
+        env::set_var("OPSML_TRACKING_URI", url);
+        env::set_var("OPSML_REQUEST_TIMEOUT", "1");
+
+        let output_dir = TestDir::new();
+        let test_dir = output_dir.path();
+        fs::create_dir_all(test_dir).unwrap();
+        let lpath = Path::new(test_dir).join("model.bin");
+
+        // slow but steadily progressing stream that outlasts the request timeout
+        let mock_slow_path = download_server
+            .mock("GET", "/opsml/files/download?path=model.bin")
+            .with_status(200)
+            .with_chunked_body(|w| {
+                for _ in 0..3 {
+                    w.write_all(b"chunk")?;
+                    std::thread::sleep(std::time::Duration::from_millis(600));
+                }
+                Ok(())
+            })
+            .expect(2)
+            .create();
+
+        // api requests are held to the request timeout
+        let slow_url = format!("{}?path=model.bin", utils::OpsmlPaths::Download.as_str());
+        let api_result = async {
+            RouteHelper::make_get_request(&slow_url)
+                .await?
+                .text()
+                .await
+                .map_err(anyhow::Error::from)
+        }
+        .await;
+
+        // downloads are not
+        let downloaded = RouteHelper::download_file(&lpath, "model.bin").await;
+
+        assert!(api_result.is_err());
+        assert_eq!(downloaded.unwrap().size, 15);
+        mock_slow_path.assert();
+    }
+
+    #[tokio::test]
+    async fn test_download_file_timeout() {
+        let _env = TestEnv::lock();
+        let mut download_server = mockito::Server::new();
+        let url = download_server.url();
+        env::set_var("OPSML_TRACKING_URI", url);
+        env::set_var("OPSML_FILE_TIMEOUT", "1");
+
+        let output_dir = TestDir::new();
+        let test_dir = output_dir.path();
+        fs::create_dir_all(test_dir).unwrap();
+        let lpath = Path::new(test_dir).join("model.bin");
+
+        // stream stalls after the first chunk. A timed out file is not requested again
+        let mock_stuck_path = download_server
+            .mock("GET", "/opsml/files/download?path=model.bin")
+            .expect(1)
+            .with_status(200)
+            .with_chunked_body(|w| {
+                w.write_all(b"chunk")?;
+                std::thread::sleep(std::time::Duration::from_millis(2500));
+                w.write_all(b"chunk")
+            })
+            .create();
+
+        let downloaded = RouteHelper::download_file(&lpath, "model.bin").await;
+        env::remove_var("OPSML_FILE_TIMEOUT");
+
+        assert_eq!(
+            downloaded.unwrap_err().to_string(),
+            "Timed out downloading model.bin after 1s"
+        );
+        mock_stuck_path.assert();
+    }
+
+    #[tokio::test]
+    async fn test_download_file_retries_empty_body() {
+        let _env = TestEnv::lock();
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        let mut download_server = mockito::Server::new();
+        let url = download_server.url();
+        env::set_var("OPSML_TRACKING_URI", url);
+
+        let output_dir = TestDir::new();
+        let test_dir = output_dir.path();
+        fs::create_dir_all(test_dir).unwrap();
+        let lpath = Path::new(test_dir).join("model.bin");
+
+        // first attempt succeeds with an empty body, second sends the file
+        let attempts = Arc::new(AtomicUsize::new(0));
+        let body_attempts = attempts.clone();
+        let mock_download = download_server
+            .mock("GET", "/opsml/files/download?path=model.bin")
+            .with_status(200)
+            .with_chunked_body(move |w| {
+                if body_attempts.fetch_add(1, Ordering::SeqCst) == 0 {
+                    Ok(())
+                } else {
+                    w.write_all(b"hello world")
+                }
+            })
+            .expect(2)
+            .create();
+
+        let mock_head = download_server
+            .mock("HEAD", "/opsml/files/download?path=model.bin")
+            .with_status(200)
+            .with_header("content-length", "11")
+            .create();
+
+        let downloaded = RouteHelper::download_file(&lpath, "model.bin")
+            .await
+            .unwrap();
+
+        mock_download.assert();
+        mock_head.assert();
+        assert_eq!(attempts.load(Ordering::SeqCst), 2);
+        assert_eq!(downloaded.size, 11);
+        assert_eq!(fs::read_to_string(&lpath).unwrap(), "hello world");
+    }
+
+    #[tokio::test]
+    async fn test_download_file_resumes_interrupted_stream() {
+        let _env = TestEnv::lock();
+        let mut download_server = mockito::Server::new();
+        let url = download_server.url();
+        env::set_var("OPSML_TRACKING_URI", url);
+
+        let output_dir = TestDir::new();
+        let test_dir = output_dir.path();
+        fs::create_dir_all(test_dir).unwrap();
+        let lpath = Path::new(test_dir).join("model.bin");
+
+        // first attempt drops the connection after the first half of the file
+        let mock_interrupted = download_server
+            .mock("GET", "/opsml/files/download?path=model.bin")
+            .match_header("range", mockito::Matcher::Missing)
+            .with_status(200)
+            .with_chunked_body(|w| {
+                w.write_all(b"hello ")?;
+                Err(std::io::Error::new(
+                    std::io::ErrorKind::ConnectionReset,
+                    "connection dropped",
+                ))
+            })
+            .create();
+
+        // retry resumes from the bytes already written
+        let mock_resumed = download_server
+            .mock("GET", "/opsml/files/download?path=model.bin")
+            .match_header("range", "bytes=6-")
+            .with_status(206)
+            .with_body("world")
+            .create();
+
+        let downloaded = RouteHelper::download_file(&lpath, "model.bin")
+            .await
+            .unwrap();
+
+        mock_interrupted.assert();
+        mock_resumed.assert();
+
+        assert_eq!(fs::read_to_string(&lpath).unwrap(), "hello world");
+        assert_eq!(downloaded.size, 11);
+        assert_eq!(
+            downloaded.checksum,
+            "b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_resume_honors_retry_after() {
+        let _env = TestEnv::lock();
+        let mut download_server = mockito::Server::new();
+        let url = download_server.url();
+        env::set_var("OPSML_TRACKING_URI", url);
+
+        let output_dir = TestDir::new();
+        let test_dir = output_dir.path();
+        fs::create_dir_all(test_dir).unwrap();
+        let lpath = Path::new(test_dir).join("model.bin");
+
+        let mock_interrupted = download_server
+            .mock("GET", "/opsml/files/download?path=model.bin")
+            .match_header("range", mockito::Matcher::Missing)
+            .with_status(200)
+            .with_chunked_body(|w| {
+                w.write_all(b"hello ")?;
+                Err(std::io::Error::new(
+                    std::io::ErrorKind::ConnectionReset,
+                    "connection dropped",
+                ))
+            })
+            .create();
+
+        // the first range request is rate limited, the next one resumes
+        let mock_rate_limited = download_server
+            .mock("GET", "/opsml/files/download?path=model.bin")
+            .match_header("range", "bytes=6-")
+            .with_status(429)
+            .with_header("retry-after", "1")
+            .expect(1)
+            .create();
+        let mock_resumed = download_server
+            .mock("GET", "/opsml/files/download?path=model.bin")
+            .match_header("range", "bytes=6-")
+            .with_status(206)
+            .with_body("world")
+            .expect(1)
+            .create();
+
+        // the wait itself is covered by utils::test_retry_delay
+        RouteHelper::download_file(&lpath, "model.bin")
+            .await
+            .unwrap();
+
+        mock_interrupted.assert();
+        mock_rate_limited.assert();
+        mock_resumed.assert();
+        assert_eq!(fs::read_to_string(&lpath).unwrap(), "hello world");
+    }
+
+    #[tokio::test]
+    async fn test_download_file_verifies_checksum() {
+        let _env = TestEnv::lock();
+        let mut download_server = mockito::Server::new();
+        let url = download_server.url();
+        env::set_var("OPSML_TRACKING_URI", url);
+
+        let output_dir = TestDir::new();
+        let test_dir = output_dir.path();
+        fs::create_dir_all(test_dir).unwrap();
+        let lpath = Path::new(test_dir).join("model.bin");
+
+        // algorithm is taken from the checksum prefix
+        let mock_valid = download_server
+            .mock("GET", "/opsml/files/download?path=model.bin")
+            .with_status(200)
+            .with_header(CHECKSUM_HEADER, "md5:5eb63bbbe01eeed093cb22bb8f5acdc3")
+            .with_body("hello world")
+            .create();
+
+        let downloaded = RouteHelper::download_file(&lpath, "model.bin")
+            .await
+            .unwrap();
+        assert_eq!(downloaded.checksum, "5eb63bbbe01eeed093cb22bb8f5acdc3");
+        mock_valid.assert();
+
+        let mock_corrupt = download_server
+            .mock("GET", "/opsml/files/download?path=corrupt.bin")
+            .with_status(200)
+            .with_header(CHECKSUM_HEADER, "md5:5eb63bbbe01eeed093cb22bb8f5acdc3")
+            .with_body("hello")
+            .create();
+
+        let error = RouteHelper::download_file(&lpath, "corrupt.bin")
+            .await
+            .unwrap_err();
+        assert!(format!("{:#}", error).contains("Checksum mismatch"));
+        mock_corrupt.assert();
+    }
+
+    #[tokio::test]
+    async fn test_download_file_cache() {
+        let _env = TestEnv::lock();
+        let mut download_server = mockito::Server::new();
+        let url = download_server.url();
+        env::set_var("OPSML_TRACKING_URI", url);
+
+        let output_dir = TestDir::new();
+        let test_dir = output_dir.path();
+        let cache_dir = Path::new(test_dir).join("cache");
+        fs::create_dir_all(test_dir).unwrap();
+        env::set_var("OPSML_CACHE_DIR", &cache_dir);
+
+        let cached = cache_dir
+            .join("md5")
+            .join("5eb63bbbe01eeed093cb22bb8f5acdc3");
+        let lpath = Path::new(test_dir).join("model.bin");
+        let mock_head = download_server
+            .mock(
+                "HEAD",
+                mockito::Matcher::Regex(r"^/opsml/files/download".to_string()),
+            )
+            .with_status(200)
+            .with_header(CHECKSUM_HEADER, "md5:5eb63bbbe01eeed093cb22bb8f5acdc3")
+            .expect(3)
+            .create();
+
+        // miss: the file is downloaded and added to the cache
+        let mock_miss = download_server
+            .mock("GET", "/opsml/files/download?path=model.bin")
+            .with_status(200)
+            .with_header(CHECKSUM_HEADER, "md5:5eb63bbbe01eeed093cb22bb8f5acdc3")
+            .with_body("hello world")
+            .expect(2)
+            .create();
+
+        RouteHelper::download_file(&lpath, "model.bin")
+            .await
+            .unwrap();
+        assert_eq!(fs::read_to_string(&cached).unwrap(), "hello world");
+
+        // hit: the download request is never sent
+        let mock_hit = download_server
+            .mock("GET", "/opsml/files/download?path=copy.bin")
+            .expect(0)
+            .create();
+
+        let copy_path = Path::new(test_dir).join("copy.bin");
+        let downloaded = RouteHelper::download_file(&copy_path, "copy.bin")
+            .await
+            .unwrap();
+        mock_hit.assert();
+        assert_eq!(fs::read_to_string(&copy_path).unwrap(), "hello world");
+        assert_eq!(downloaded.size, 11);
+        assert_eq!(downloaded.checksum, "5eb63bbbe01eeed093cb22bb8f5acdc3");
+
+        // restored files are copies, so editing one leaves the cache intact
+        fs::write(&copy_path, "edited").unwrap();
+        assert_eq!(fs::read_to_string(&cached).unwrap(), "hello world");
+
+        // a cached file that no longer matches its checksum is downloaded again
+        fs::write(&cached, "hello_world").unwrap();
+        RouteHelper::download_file(&lpath, "model.bin")
+            .await
+            .unwrap();
+        assert_eq!(fs::read_to_string(&lpath).unwrap(), "hello world");
+        assert_eq!(fs::read_to_string(&cached).unwrap(), "hello world");
+        mock_miss.assert();
+
+        // --no-cache downloads again without looking at the cache, leaving it intact
+        env::set_var("OPSML_NO_CACHE", "1");
+        let mock_corrupt = download_server
+            .mock("GET", "/opsml/files/download?path=copy.bin")
+            .with_status(200)
+            .with_header(CHECKSUM_HEADER, "md5:5eb63bbbe01eeed093cb22bb8f5acdc3")
+            .with_body("corrupt")
+            .create();
+        let error = RouteHelper::download_file(&copy_path, "copy.bin")
+            .await
+            .unwrap_err();
+        assert!(format!("{:#}", error).contains("Checksum mismatch"));
+        assert_eq!(fs::read_to_string(&cached).unwrap(), "hello world");
+        mock_corrupt.assert();
+        mock_head.assert();
+    }
+
+    #[tokio::test]
+    async fn test_head_file() {
+        let _env = TestEnv::lock();
+        let mut download_server = mockito::Server::new();
+        let url = download_server.url();
+        env::set_var("OPSML_TRACKING_URI", url);
+
+        let mock_head = download_server
+            .mock("HEAD", "/opsml/files/download?path=model.bin")
+            .with_status(200)
+            .with_header("content-length", "2048")
+            .with_header("last-modified", "Wed, 21 Oct 2015 07:28:00 GMT")
+            .with_header(CHECKSUM_HEADER, "md5:5eb63bbbe01eeed093cb22bb8f5acdc3")
+            .create();
+
+        let file_meta = RouteHelper::head_file("model.bin").await.unwrap();
+        mock_head.assert();
+        assert_eq!(
+            file_meta,
+            types::FileMeta {
+                size: Some(2048),
+                last_modified: Some("Wed, 21 Oct 2015 07:28:00 GMT".to_string()),
+                checksum: Some(ExpectedChecksum {
+                    algo: checksum::ChecksumAlgo::Md5,
+                    digest: "5eb63bbbe01eeed093cb22bb8f5acdc3".to_string(),
+                }),
+            }
+        );
+
+        // HEAD not supported by the server
+        let mock_unsupported = download_server
+            .mock("HEAD", "/opsml/files/download?path=other.bin")
+            .with_status(405)
+            .create();
+
+        let file_meta = RouteHelper::head_file("other.bin").await.unwrap();
+        mock_unsupported.assert();
+        assert_eq!(file_meta, types::FileMeta::default());
+    }
+
+    #[tokio::test]
+    async fn test_response_error_json_body() {
+        let _env = TestEnv::lock();
+        let mut server = mockito::Server::new();
+        let url = server.url();
+        env::set_var("OPSML_TRACKING_URI", url);
+
+        let mock_list_path = server
+            .mock("GET", "/opsml/files/list?path=missing")
+            .with_status(404)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"error": "NotFound", "detail": "Path missing does not exist"}"#)
+            .create();
+
+        let error = RouteHelper::list_files("missing")
+            .await
+            .unwrap_err()
+            .to_string();
+
+        mock_list_path.assert();
+        assert_eq!(error, "Failed to list files: Path missing does not exist");
+
+        // non-string details are rendered as json
+        assert_eq!(
+            RouteHelper::parse_error_body(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                r#"{"detail": [{"loc": ["body", "name"]}]}"#
+            ),
+            r#"[{"loc":["body","name"]}]"#
+        );
+    }
+
+    #[test]
+    fn test_parse_error_body_text() {
+        assert_eq!(
+            RouteHelper::parse_error_body(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Internal Server Error"
+            ),
+            "Internal Server Error"
+        );
+
+        // json without a detail falls back to the raw body
+        assert_eq!(
+            RouteHelper::parse_error_body(StatusCode::BAD_REQUEST, r#"{"error": "BadRequest"}"#),
+            r#"{"error": "BadRequest"}"#
+        );
+
+        assert_eq!(
+            RouteHelper::parse_error_body(StatusCode::BAD_GATEWAY, ""),
+            "502 Bad Gateway"
+        );
+    }
 }
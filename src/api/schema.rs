@@ -0,0 +1,71 @@
+/// Copyright (c) Shipt, Inc.
+/// This source code is licensed under the MIT license found in the
+/// LICENSE file in the root directory of this source tree.
+use crate::api::commands::SchemaKind;
+use crate::api::output;
+use crate::api::types;
+use anyhow::{Context, Result};
+use schemars::schema_for;
+
+/// Generates the JSON Schema of a type used by the cli
+///
+/// # Arguments
+///
+/// * `kind` - Type to generate the schema for
+///
+/// # Returns
+/// * `Result<String, String>` - Pretty printed JSON Schema
+///
+pub fn json_schema(kind: &SchemaKind) -> Result<String, anyhow::Error> {
+    let schema = match kind {
+        SchemaKind::Metadata => schema_for!(types::ModelMetadata),
+    };
+
+    serde_json::to_string_pretty(&schema).with_context(|| "Failed to serialize schema")
+}
+
+/// Prints the JSON Schema of a type used by the cli
+///
+/// # Arguments
+///
+/// * `kind` - Type to print the schema for
+///
+pub fn print_schema(kind: &SchemaKind) -> Result<(), anyhow::Error> {
+    output::print(json_schema(kind)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+
+    #[test]
+    fn test_metadata_schema() {
+        let schema: Value =
+            serde_json::from_str(&json_schema(&SchemaKind::Metadata).unwrap()).unwrap();
+
+        assert_eq!(schema["title"], "ModelMetadata");
+        let properties = schema["properties"].as_object().unwrap();
+        for field in [
+            "model_name",
+            "model_version",
+            "model_uri",
+            "onnx_uri",
+            "data_schema",
+            "opsml_version",
+        ] {
+            assert!(properties.contains_key(field), "{}", field);
+        }
+
+        // optional fields are not required
+        let required: Vec<&str> = schema["required"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|field| field.as_str().unwrap())
+            .collect();
+        assert!(required.contains(&"model_name"));
+        assert!(!required.contains(&"onnx_uri"));
+        assert!(schema["definitions"]["DataSchema"].is_object());
+    }
+}
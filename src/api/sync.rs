@@ -0,0 +1,477 @@
+/// Copyright (c) Shipt, Inc.
+/// This source code is licensed under the MIT license found in the
+/// LICENSE file in the root directory of this source tree.
+use crate::api::cards::CardLister;
+use crate::api::model::{self, FileFilter, ModelDownloader};
+use crate::api::output;
+use crate::api::utils;
+use anyhow::{Context, Result};
+use chrono::Utc;
+use owo_colors::OwoColorize;
+use std::collections::HashMap;
+use std::path::Path;
+use std::{fs, path::PathBuf};
+
+pub struct ModelSyncer<'a> {
+    pub registry: &'a str,
+    pub since: Option<&'a str>,
+    pub write_dir: &'a str,
+    pub state_file: Option<&'a str>,
+    pub onnx: &'a bool,
+    pub preprocessor: &'a bool,
+    pub max_downloads: usize,
+}
+
+impl ModelSyncer<'_> {
+    /// Resolves the date to sync from
+    ///
+    /// A date recorded in the state file takes precedence so that repeated runs are
+    /// incremental. `since` is used for the first run, when no state has been recorded.
+    ///
+    /// # Returns
+    /// * `Result<String, String>` - Minimum card date to sync
+    ///
+    fn resolve_since(&self) -> Result<String, anyhow::Error> {
+        if let Some(state_file) = self.state_file {
+            if Path::new(state_file).exists() {
+                let last_sync = fs::read_to_string(state_file)
+                    .with_context(|| format!("Unable to read state file {}", state_file))?;
+                let last_sync = last_sync.trim();
+
+                if !last_sync.is_empty() {
+                    return Ok(last_sync.to_string());
+                }
+            }
+        }
+
+        self.since
+            .map(|since| since.to_string())
+            .with_context(|| "No previous sync found. Provide --since to set the initial sync date")
+    }
+
+    /// Records the date of a successful sync in the state file
+    ///
+    /// # Arguments
+    ///
+    /// * `sync_date` - Date the sync started
+    ///
+    fn save_state(&self, sync_date: &str) -> Result<(), anyhow::Error> {
+        if let Some(state_file) = self.state_file {
+            let path = Path::new(state_file);
+            utils::create_dir_path(path)?;
+            fs::write(path, sync_date)
+                .with_context(|| format!("Unable to write state file {}", state_file))?;
+        }
+        Ok(())
+    }
+
+    /// Lists model cards registered since the resolved date and downloads each one
+    ///
+    /// # Returns
+    /// * `Result<Vec<PathBuf>, String>` - Directories models were downloaded to
+    ///
+    async fn sync(&self) -> Result<Vec<PathBuf>, anyhow::Error> {
+        if self.registry != "model" {
+            return Err(anyhow::Error::msg(format!(
+                "Invalid registry: {}. Only the model registry can be synced",
+                self.registry
+            )));
+        }
+
+        // capture the date before listing so cards registered mid-sync are picked up next run
+        let sync_date = Utc::now().format("%Y-%m-%d").to_string();
+        let since = self.resolve_since()?;
+
+        let card_lister = CardLister {
+            registry_type: self.registry,
+            name: None,
+            repository: None,
+            version: None,
+            uid: None,
+            limit: None,
+            tags: HashMap::new(),
+            max_date: None,
+            min_date: Some(&since),
+            name_contains: None,
+            ignore_release_candidates: &false,
+            all_versions: &false,
+            table_style: utils::TableStyle::default(),
+            output: output::OutputOptions::default(),
+            group_by: None,
+            columns: None,
+            template: None,
+        };
+        let cards = card_lister.get_card_response().await?;
+        model::check_max_downloads(cards.cards.len(), self.max_downloads)?;
+
+        println!(
+            "{}",
+            output::styled(format!(
+                "\nSyncing {} models registered since {}",
+                cards.cards.len().to_string().bold().green(),
+                since.bold()
+            ))
+        );
+
+        // names and versions come from the server, so check neither can leave write_dir
+        let synced: Vec<PathBuf> = cards
+            .cards
+            .iter()
+            .map(|card| {
+                let name_dir = model::join_dir_name(Path::new(self.write_dir), &card.name, "name")?;
+                model::join_dir_name(&name_dir, &card.version, "version")
+            })
+            .collect::<Result<_, anyhow::Error>>()?;
+        let write_dirs: Vec<String> = synced
+            .iter()
+            .map(|write_dir| write_dir.to_string_lossy().to_string())
+            .collect();
+
+        let model_downloaders: Vec<ModelDownloader> = cards
+            .cards
+            .iter()
+            .zip(write_dirs.iter())
+            .map(|(card, write_dir)| ModelDownloader {
+                name: None,
+                version: None,
+                repository: None,
+                uid: Some(&card.uid),
+                write_dir,
+                ignore_release_candidates: &false,
+                onnx: self.onnx,
+                quantize: &false,
+                preprocessor: self.preprocessor,
+                manifest: &false,
+                archive: None,
+                file_filter: FileFilter::default(),
+                check_space: &true,
+                report: None,
+                batch_size: model::DEFAULT_BATCH_SIZE,
+                metadata_format: model::MetadataFormat::Json,
+                write_checksums: false,
+                resume: false,
+                auto_concurrency: false,
+                write_metadata: true,
+            })
+            .collect();
+
+        // metadata for every card is fetched before any files, so all invalid cards are
+        // reported together
+        let metadata = model::prefetch_metadata(&model_downloaders)
+            .await
+            .with_context(|| "Failed to sync models")?;
+
+        for ((card, model_downloader), model_metadata) in cards
+            .cards
+            .iter()
+            .zip(model_downloaders.iter())
+            .zip(metadata)
+        {
+            model_downloader
+                .download_model_with_metadata(&model_metadata)
+                .await
+                .with_context(|| format!("Failed to sync model {}", card.uid))?;
+        }
+
+        self.save_state(&sync_date)?;
+
+        Ok(synced)
+    }
+}
+
+/// Downloads all models registered since a date
+///
+/// # Arguments
+///
+/// * `registry` - Registry to sync from
+/// * `since` - Initial date to sync from when no state is recorded
+/// * `write_dir` - Directory to write models to
+/// * `state_file` - File recording the last successful sync date
+/// * `onnx` - Flag to download onnx model
+/// * `preprocessor` - Flag to download preprocessors
+/// * `max_downloads` - Maximum number of models to download. 0 for no limit
+///
+#[tokio::main]
+pub async fn sync_models(
+    registry: &str,
+    since: Option<&str>,
+    write_dir: &str,
+    state_file: Option<&str>,
+    onnx: &bool,
+    preprocessor: &bool,
+    max_downloads: usize,
+) -> Result<(), anyhow::Error> {
+    let model_syncer = ModelSyncer {
+        registry,
+        since,
+        write_dir,
+        state_file,
+        onnx,
+        preprocessor,
+        max_downloads,
+    };
+    utils::with_shared_client(model_syncer.sync()).await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::test_utils::{TestDir, TestEnv};
+    use crate::api::types;
+    use mockito::Matcher;
+    use serde_json::json;
+    use std::env;
+
+    #[test]
+    fn test_resolve_since() {
+        let output_dir = TestDir::new();
+        let test_dir = output_dir.path();
+        let state_file = format!("{}/state", test_dir);
+
+        let mut model_syncer = ModelSyncer {
+            registry: "model",
+            since: None,
+            write_dir: test_dir,
+            state_file: Some(&state_file),
+            onnx: &false,
+            preprocessor: &false,
+            max_downloads: model::DEFAULT_MAX_DOWNLOADS,
+        };
+
+        // no state and no since
+        assert!(model_syncer.resolve_since().is_err());
+
+        // since used when no state recorded
+        model_syncer.since = Some("2023-01-01");
+        assert_eq!(model_syncer.resolve_since().unwrap(), "2023-01-01");
+
+        // state takes precedence once recorded
+        model_syncer.save_state("2023-06-01").unwrap();
+        assert_eq!(model_syncer.resolve_since().unwrap(), "2023-06-01");
+    }
+
+    #[tokio::test]
+    async fn test_sync_models() {
+        let _env = TestEnv::lock();
+        let output_dir = TestDir::new();
+        let test_dir = output_dir.path();
+        let state_file = format!("{}/state", test_dir);
+        let metadata = fs::read_to_string("./src/api/test_utils/metadata.json").unwrap();
+
+        let mut server = mockito::Server::new();
+        let url = server.url();
+        env::set_var("OPSML_TRACKING_URI", url);
+
+        let cards = json!({
+            "cards": [
+                {
+                    "name": "model-a",
+                    "repository": "repo",
+                    "date": "2023-01-02",
+                    "contact": "fake_email",
+                    "version": "1.0.0",
+                    "uid": "uid-a",
+                    "tags": {}
+                },
+                {
+                    "name": "model-b",
+                    "repository": "repo",
+                    "date": "2023-01-03",
+                    "contact": "fake_email",
+                    "version": "2.0.0",
+                    "uid": "uid-b",
+                    "tags": {}
+                }
+            ]
+        });
+
+        let mock_list = server
+            .mock("POST", "/opsml/cards/list")
+            .match_body(Matcher::PartialJson(json!({"min_date": "2023-01-01"})))
+            .with_status(201)
+            .with_body(cards.to_string())
+            .create();
+
+        let mut mocks = Vec::new();
+        for model in ["a", "b"] {
+            let rpath = format!("remote/model-{}", model);
+            let file = format!("{}/model.onnx", rpath);
+
+            let mut model_metadata: types::ModelMetadata = serde_json::from_str(&metadata).unwrap();
+            model_metadata.model_uri = rpath.clone();
+
+            let files = types::ListFileResponse {
+                files: vec![file.clone()],
+            };
+
+            mocks.push(
+                server
+                    .mock("POST", "/opsml/models/metadata")
+                    .match_body(Matcher::PartialJson(
+                        json!({ "uid": format!("uid-{}", model) }),
+                    ))
+                    .with_status(201)
+                    .with_body(serde_json::to_string(&model_metadata).unwrap())
+                    .create(),
+            );
+            mocks.push(
+                server
+                    .mock("GET", format!("/opsml/files/list?path={}", rpath).as_str())
+                    .with_status(201)
+                    .with_body(serde_json::to_string(&files).unwrap())
+                    .create(),
+            );
+            mocks.push(
+                server
+                    .mock(
+                        "GET",
+                        format!("/opsml/files/download?path={}", file).as_str(),
+                    )
+                    .with_status(201)
+                    .with_body("model")
+                    .create(),
+            );
+        }
+
+        let model_syncer = ModelSyncer {
+            registry: "model",
+            since: Some("2023-01-01"),
+            write_dir: test_dir,
+            state_file: Some(&state_file),
+            onnx: &false,
+            preprocessor: &false,
+            max_downloads: model::DEFAULT_MAX_DOWNLOADS,
+        };
+
+        let synced = model_syncer.sync().await.unwrap();
+
+        mock_list.assert();
+        for mock in mocks.iter() {
+            mock.assert();
+        }
+
+        assert_eq!(synced.len(), 2);
+        assert!(Path::new(test_dir)
+            .join("model-a/1.0.0/model.onnx")
+            .exists());
+        assert!(Path::new(test_dir)
+            .join("model-b/2.0.0/model.onnx")
+            .exists());
+        assert_eq!(
+            fs::read_to_string(&state_file).unwrap(),
+            Utc::now().format("%Y-%m-%d").to_string()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_sync_models_rejects_unsafe_dirs() {
+        let _env = TestEnv::lock();
+        let output_dir = TestDir::new();
+        let test_dir = output_dir.path();
+
+        let mut server = mockito::Server::new();
+        let url = server.url();
+        env::set_var("OPSML_TRACKING_URI", url);
+
+        for (name, version) in [("../escape", "1.0.0"), ("model", "/tmp")] {
+            let cards = json!({
+                "cards": [
+                    {
+                        "name": name,
+                        "repository": "repo",
+                        "date": "2023-01-02",
+                        "contact": "fake_email",
+                        "version": version,
+                        "uid": "uid-a",
+                        "tags": {}
+                    }
+                ]
+            });
+            let mock_list = server
+                .mock("POST", "/opsml/cards/list")
+                .with_status(201)
+                .with_body(cards.to_string())
+                .create();
+            // nothing is downloaded for a card that would be written outside write_dir
+            let mock_metadata = server
+                .mock("POST", "/opsml/models/metadata")
+                .expect(0)
+                .create();
+
+            let model_syncer = ModelSyncer {
+                registry: "model",
+                since: Some("2023-01-01"),
+                write_dir: test_dir,
+                state_file: None,
+                onnx: &false,
+                preprocessor: &false,
+                max_downloads: model::DEFAULT_MAX_DOWNLOADS,
+            };
+
+            let error = model_syncer.sync().await.unwrap_err().to_string();
+            assert!(error.contains("Invalid model"), "{}", error);
+
+            mock_list.assert();
+            mock_metadata.assert();
+            mock_list.remove();
+            mock_metadata.remove();
+        }
+
+        assert!(!Path::new(test_dir).exists());
+    }
+
+    #[tokio::test]
+    async fn test_sync_models_max_downloads() {
+        let _env = TestEnv::lock();
+        let output_dir = TestDir::new();
+        let test_dir = output_dir.path();
+
+        let mut server = mockito::Server::new();
+        let url = server.url();
+        env::set_var("OPSML_TRACKING_URI", url);
+
+        let cards: Vec<_> = ["a", "b", "c"]
+            .iter()
+            .map(|model| {
+                json!({
+                    "name": format!("model-{}", model),
+                    "repository": "repo",
+                    "date": "2023-01-02",
+                    "contact": "fake_email",
+                    "version": "1.0.0",
+                    "uid": format!("uid-{}", model),
+                    "tags": {}
+                })
+            })
+            .collect();
+        let mock_list = server
+            .mock("POST", "/opsml/cards/list")
+            .with_status(201)
+            .with_body(json!({ "cards": cards }).to_string())
+            .create();
+        // the cap is checked before any metadata is fetched
+        let mock_metadata = server
+            .mock("POST", "/opsml/models/metadata")
+            .expect(0)
+            .create();
+
+        let model_syncer = ModelSyncer {
+            registry: "model",
+            since: Some("2023-01-01"),
+            write_dir: test_dir,
+            state_file: None,
+            onnx: &false,
+            preprocessor: &false,
+            max_downloads: 2,
+        };
+
+        let error = model_syncer.sync().await.unwrap_err().to_string();
+        assert!(error.contains("more than --max-downloads 2"), "{}", error);
+
+        mock_list.assert();
+        mock_metadata.assert();
+        assert!(!Path::new(test_dir).exists());
+    }
+}
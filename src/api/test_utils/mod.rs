@@ -0,0 +1,63 @@
+/// Copyright (c) Shipt, Inc.
+/// This source code is licensed under the MIT license found in the
+/// LICENSE file in the root directory of this source tree.
+use std::env;
+use std::ffi::OsString;
+use std::fs;
+use std::sync::{Mutex, MutexGuard};
+use uuid::Uuid;
+
+static ENV_LOCK: Mutex<()> = Mutex::new(());
+
+/// Serializes tests that change process environment variables and restores the environment
+/// when dropped, so a failed assertion does not leak into other tests
+pub(crate) struct TestEnv {
+    vars: Vec<(OsString, OsString)>,
+    _lock: MutexGuard<'static, ()>,
+}
+
+impl TestEnv {
+    pub(crate) fn lock() -> Self {
+        // a test that panicked while holding the lock still restored the environment
+        let lock = ENV_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+        TestEnv {
+            vars: env::vars_os().collect(),
+            _lock: lock,
+        }
+    }
+}
+
+impl Drop for TestEnv {
+    fn drop(&mut self) {
+        for (key, _) in env::vars_os() {
+            if !self.vars.iter().any(|(saved, _)| *saved == key) {
+                env::remove_var(key);
+            }
+        }
+
+        for (key, value) in &self.vars {
+            env::set_var(key, value);
+        }
+    }
+}
+
+/// Test output directory that is removed when dropped, so a failed assertion does not
+/// leave it behind
+pub(crate) struct TestDir(String);
+
+impl TestDir {
+    pub(crate) fn new() -> Self {
+        TestDir(format!("./src/api/test_utils/{}", Uuid::new_v4()))
+    }
+
+    pub(crate) fn path(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Drop for TestDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
@@ -1,9 +1,13 @@
 /// Copyright (c) Shipt, Inc.
 /// This source code is licensed under the MIT license found in the
 /// LICENSE file in the root directory of this source tree.
+use crate::api::checksum::ExpectedChecksum;
+use indexmap::IndexMap;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::HashMap;
+use std::time::Duration;
 use tabled::Tabled;
 
 #[derive(Debug, Serialize)]
@@ -16,12 +20,15 @@ pub struct ListTableRequest<'a> {
     pub limit: Option<&'a i16>,
     pub tags: &'a HashMap<String, String>,
     pub max_date: Option<&'a str>,
+    pub min_date: Option<&'a str>,
     pub ignore_release_candidates: &'a bool,
+    pub all_versions: &'a bool,
 }
 
 #[derive(Debug, Serialize)]
 pub struct CardRequest<'a> {
     pub name: Option<&'a str>,
+    pub repository: Option<&'a str>,
     pub version: Option<&'a str>,
     pub uid: Option<&'a str>,
 }
@@ -53,7 +60,8 @@ pub struct ListFileResponse {
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ListMetricResponse {
-    pub metrics: HashMap<String, Vec<Metric>>,
+    // keeps the server's metric order so ties and unsorted --top are stable
+    pub metrics: IndexMap<String, Vec<Metric>>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -72,16 +80,6 @@ pub struct MetricTable {
     pub timestamp: String,
 }
 
-#[derive(Tabled)]
-pub struct CompareMetricTable {
-    pub champion_name: String,
-    pub champion_version: Value,
-    pub metric: String,
-    pub champion_value: Value,
-    pub challenger_value: Value,
-    pub challenger_win: bool,
-}
-
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ListCardResponse {
     pub cards: Vec<Card>,
@@ -97,13 +95,31 @@ pub struct CardTable {
     pub uid: String,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize)]
+pub struct RegistryCard<'a> {
+    pub registry: &'a str,
+    #[serde(flatten)]
+    pub card: &'a Card,
+}
+
+#[derive(Tabled)]
+pub struct RegistryCardTable {
+    pub registry: String,
+    pub name: String,
+    pub repository: String,
+    pub date: String,
+    pub contact: String,
+    pub version: String,
+    pub uid: String,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct Feature {
     feature_type: String,
     shape: Value,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct DataSchema {
     data_type: Option<String>,
     input_features: Option<HashMap<String, Feature>>,
@@ -114,7 +130,7 @@ pub struct DataSchema {
     onnx_version: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct ModelMetadata {
     pub model_name: String,
     pub model_class: String,
@@ -134,6 +150,7 @@ pub struct ModelMetadata {
     pub feature_extractor_uri: Option<String>,
     pub feature_extractor_name: Option<String>,
     pub quantized_model_uri: Option<String>,
+    pub opsml_version: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
@@ -159,3 +176,96 @@ pub struct CompareMetricResponse {
     pub challenger_version: String,
     pub report: HashMap<String, Vec<BattleReport>>,
 }
+
+/// A row of a compare-model-metrics pairs file
+#[derive(Debug, PartialEq, Deserialize)]
+pub struct ComparePair {
+    pub challenger_uid: String,
+    pub champion_uid: String,
+    pub metric_name: String,
+    pub lower_is_better: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ServerError {
+    pub error: Option<Value>,
+    pub detail: Option<Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DownloadedFile {
+    pub size: u64,
+    pub checksum: String,
+    pub sha256: String,
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct FileMeta {
+    pub size: Option<u64>,
+    pub last_modified: Option<String>,
+    pub checksum: Option<ExpectedChecksum>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManifestFile {
+    pub remote_path: String,
+    pub local_path: String,
+    pub size: u64,
+    pub checksum: String,
+    #[serde(skip)]
+    pub sha256: String,
+    #[serde(skip)]
+    pub duration: Duration,
+}
+
+#[derive(Tabled)]
+pub struct DownloadReportTable {
+    pub file: String,
+    pub size: String,
+    pub seconds: String,
+    #[tabled(rename = "MBps")]
+    pub mbps: String,
+}
+
+#[derive(Tabled)]
+pub struct ModelSummaryTable {
+    pub model: String,
+    pub status: String,
+    pub size: String,
+    pub seconds: String,
+    pub error: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ModelFileInfo {
+    pub path: String,
+    pub remote_path: String,
+    pub size: Option<u64>,
+}
+
+#[derive(Tabled)]
+pub struct ModelFileTable {
+    pub file: String,
+    pub remote_path: String,
+    pub size: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DownloadManifest {
+    pub name: String,
+    pub version: String,
+    pub uid: Option<String>,
+    pub timestamp: u64,
+    pub files: Vec<ManifestFile>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DownloadSummary {
+    pub name: String,
+    pub version: String,
+    pub uid: Option<String>,
+    pub write_dir: String,
+    pub files: Vec<ManifestFile>,
+    pub total_bytes: u64,
+    pub duration_secs: f64,
+}
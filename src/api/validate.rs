@@ -0,0 +1,195 @@
+/// Copyright (c) Shipt, Inc.
+/// This source code is licensed under the MIT license found in the
+/// LICENSE file in the root directory of this source tree.
+use crate::api::output;
+use crate::api::types;
+use anyhow::{Context, Result};
+use owo_colors::OwoColorize;
+use serde_json::Value;
+use std::fs;
+use std::io::Read;
+
+const REQUIRED_STRING_FIELDS: [&str; 8] = [
+    "model_name",
+    "model_class",
+    "model_type",
+    "model_interface",
+    "model_uri",
+    "model_version",
+    "model_repository",
+    "sample_data_uri",
+];
+
+const OPTIONAL_STRING_FIELDS: [&str; 9] = [
+    "onnx_uri",
+    "onnx_version",
+    "preprocessor_uri",
+    "preprocessor_name",
+    "tokenizer_uri",
+    "tokenizer_name",
+    "feature_extractor_uri",
+    "feature_extractor_name",
+    "quantized_model_uri",
+];
+
+/// Name of a json value's type for error messages
+fn value_type(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+/// Validates model metadata json
+///
+/// # Arguments
+///
+/// * `contents` - Metadata json
+///
+/// # Returns
+/// * `Result<types::ModelMetadata, String>` - Parsed metadata, or an error listing every
+///   missing or malformed field
+///
+pub fn validate_metadata_str(contents: &str) -> Result<types::ModelMetadata, anyhow::Error> {
+    let value: Value =
+        serde_json::from_str(contents).with_context(|| "Metadata is not valid JSON")?;
+
+    let fields = value.as_object().with_context(|| {
+        format!(
+            "Metadata must be a JSON object, found {}",
+            value_type(&value)
+        )
+    })?;
+
+    let mut errors = Vec::new();
+
+    for field in REQUIRED_STRING_FIELDS.iter() {
+        match fields.get(*field) {
+            None | Some(Value::Null) => errors.push(format!("missing required field `{}`", field)),
+            Some(Value::String(_)) => {}
+            Some(other) => errors.push(format!(
+                "field `{}` should be a string, found {}",
+                field,
+                value_type(other)
+            )),
+        }
+    }
+
+    for field in OPTIONAL_STRING_FIELDS.iter() {
+        match fields.get(*field) {
+            None | Some(Value::Null) | Some(Value::String(_)) => {}
+            Some(other) => errors.push(format!(
+                "field `{}` should be a string or null, found {}",
+                field,
+                value_type(other)
+            )),
+        }
+    }
+
+    match fields.get("data_schema") {
+        None | Some(Value::Null) => errors.push("missing required field `data_schema`".to_string()),
+        Some(Value::Object(_)) => {}
+        Some(other) => errors.push(format!(
+            "field `data_schema` should be an object, found {}",
+            value_type(other)
+        )),
+    }
+
+    if !errors.is_empty() {
+        return Err(anyhow::Error::msg(format!(
+            "Invalid metadata:\n  - {}",
+            errors.join("\n  - ")
+        )));
+    }
+
+    serde_json::from_value(value).with_context(|| "Invalid metadata")
+}
+
+/// Reads metadata json to the end of a reader, such as stdin, and validates it
+///
+/// # Arguments
+///
+/// * `reader` - Reader to read metadata json from
+///
+/// # Returns
+/// * `Result<types::ModelMetadata, String>` - Parsed metadata
+///
+pub fn read_metadata<R: Read>(reader: &mut R) -> Result<types::ModelMetadata, anyhow::Error> {
+    let mut contents = String::new();
+    reader
+        .read_to_string(&mut contents)
+        .with_context(|| "Unable to read metadata")?;
+
+    validate_metadata_str(&contents)
+}
+
+/// Validates a local metadata file against the model metadata schema
+///
+/// # Arguments
+///
+/// * `path` - Path to metadata json file. Metadata is read from stdin when None
+///
+pub fn validate_metadata(path: Option<&str>) -> Result<(), anyhow::Error> {
+    let metadata = match path {
+        Some(path) => {
+            let contents = fs::read_to_string(path)
+                .with_context(|| format!("Unable to read metadata file {}", path))?;
+            validate_metadata_str(&contents)?
+        }
+        None => read_metadata(&mut std::io::stdin().lock())?,
+    };
+
+    println!(
+        "{}",
+        output::styled(format!(
+            "{} is valid metadata for model {} version {}",
+            path.unwrap_or("stdin"),
+            metadata.model_name.bold().green(),
+            metadata.model_version.bold().green()
+        ))
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_metadata() {
+        validate_metadata(Some("./src/api/test_utils/metadata.json")).unwrap();
+    }
+
+    #[test]
+    fn test_read_metadata() {
+        let metadata = fs::read_to_string("./src/api/test_utils/metadata.json").unwrap();
+
+        let parsed = read_metadata(&mut std::io::Cursor::new(metadata.as_bytes())).unwrap();
+        assert_eq!(parsed.model_version, "1.1.0");
+
+        let error = read_metadata(&mut std::io::Cursor::new(b"{}"))
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("missing required field `model_name`"));
+    }
+
+    #[test]
+    fn test_validate_metadata_missing_field() {
+        let metadata = fs::read_to_string("./src/api/test_utils/metadata.json").unwrap();
+        let mut value: Value = serde_json::from_str(&metadata).unwrap();
+        value.as_object_mut().unwrap().remove("model_uri");
+        value["model_version"] = Value::from(1);
+
+        let error = validate_metadata_str(&value.to_string())
+            .unwrap_err()
+            .to_string();
+
+        assert!(error.contains("missing required field `model_uri`"));
+        assert!(error.contains("field `model_version` should be a string, found number"));
+    }
+}
@@ -1,24 +1,70 @@
-use api::cards::list_cards;
-use api::metrics::{compare_model_metrics, get_model_metrics};
+use api::cards::{list_cards, list_recent_cards, list_teams};
+#[cfg(feature = "python")]
+use api::launch_app::launch_app;
+use api::metrics::{compare_model_metrics, get_model_metrics, MetricSelection};
 /// Copyright (c) Shipt, Inc.
 /// This source code is licensed under the MIT license found in the
 /// LICENSE file in the root directory of this source tree.
 use api::model::download_model;
 use api::model::download_model_metadata;
+use api::model::download_models_by_uid;
+use api::model::list_files;
+use api::model::model_metadata;
+use api::sync::sync_models;
+use api::validate::validate_metadata;
 mod api;
 use anyhow::{Context, Result};
 use api::cli::{Cli, Commands, LOGO_TEXT};
+use api::config;
+use api::diff::diff_model;
+use api::doctor;
+use api::output;
+use api::schema;
+use api::utils;
 use clap::Parser;
 use owo_colors::OwoColorize;
 
 fn main() -> Result<()> {
-    let cli = Cli::parse();
+    match run() {
+        // error contexts are colored, so drop the colors along with the rest of the output
+        Err(error) if !utils::color_enabled() => {
+            eprintln!("Error: {}", output::styled(format!("{:?}", error)));
+            std::process::exit(1);
+        }
+        result => result,
+    }
+}
+
+fn run() -> Result<()> {
+    let mut cli = Cli::parse();
+    cli.resolve_card_id()
+        .with_context(|| format!("{}", "Invalid card identifier".bold().red()))?;
+
+    // load flags, env file and config before anything reads the environment
+    cli.apply_env();
+    config::load_env_file(cli.env_file.as_deref())
+        .with_context(|| format!("{}", "Failed to load env file".bold().red()))?;
+    config::load_tracking_uri_file()
+        .with_context(|| format!("{}", "Failed to load tracking uri file".bold().red()))?;
+    output::OutputTarget::from_env()
+        .init()
+        .with_context(|| format!("{}", "Failed to create output file".bold().red()))?;
+    // the config command edits the config file, so it must not fail on loading it
+    // config and doctor load the config file themselves
+    if !matches!(
+        cli.command,
+        Some(Commands::Config(_)) | Some(Commands::Doctor)
+    ) {
+        config::apply_config(cli.profile.as_deref())
+            .with_context(|| format!("{}", "Failed to load config".bold().red()))?;
+    }
+    cli.table_style = cli.table_style.with_fallback(utils::unicode_supported());
 
     match &cli.command {
         // subcommand for list cards
         Some(Commands::ListCards(args)) => {
             list_cards(
-                args.registry.as_str(),
+                args.registry.as_deref(),
                 args.name.as_deref(),
                 args.repository.as_deref(),
                 args.version.as_deref(),
@@ -27,13 +73,48 @@ fn main() -> Result<()> {
                 args.tag_name.clone(),
                 args.tag_value.clone(),
                 args.max_date.as_deref(),
+                args.since,
+                args.name_contains.as_deref(),
                 args.ignore_release_candidates,
+                args.all_versions,
+                cli.table_style,
+                output::OutputOptions {
+                    raw: args.raw,
+                    ..cli.output_options()
+                },
+                args.count,
+                args.watch.then_some(args.interval),
+                args.group_by,
+                args.columns.as_deref(),
+                args.format_template.as_deref(),
+                args.fail_if_empty,
             )
             .with_context(|| format!("{}", "Failed to list cards".bold().red()))?;
 
             Ok(())
         }
 
+        // subcommand for listing the most recent cards
+        Some(Commands::Recent(args)) => {
+            list_recent_cards(
+                &args.registry,
+                args.limit,
+                cli.table_style,
+                cli.output_options(),
+            )
+            .with_context(|| format!("{}", "Failed to list recent cards".bold().red()))?;
+
+            Ok(())
+        }
+
+        // subcommand for list teams
+        Some(Commands::ListTeams(args)) => {
+            list_teams(args.registry.as_str())
+                .with_context(|| format!("{}", "Failed to list teams".bold().red()))?;
+
+            Ok(())
+        }
+
         // subcommand for downloading model metadata
         Some(Commands::DownloadModelMetadata(args)) => {
             download_model_metadata(
@@ -43,6 +124,8 @@ fn main() -> Result<()> {
                 args.uid.as_deref(),
                 &args.write_dir,
                 &args.ignore_release_candidates,
+                args.print.as_deref(),
+                args.format,
             )
             .with_context(|| {
                 format!(
@@ -53,20 +136,83 @@ fn main() -> Result<()> {
 
             Ok(())
         }
-        // subcommand for downloading a model
-        Some(Commands::DownloadModel(args)) => {
-            download_model(
+        // subcommand for listing model files without downloading them
+        Some(Commands::ListFiles(args)) => {
+            list_files(
                 args.name.as_deref(),
                 args.version.as_deref(),
                 args.repository.as_deref(),
                 args.uid.as_deref(),
-                &args.write_dir,
                 &args.onnx,
                 &args.quantize,
                 &args.preprocessor,
                 &args.ignore_release_candidates,
+                cli.table_style,
+                cli.output_options(),
+            )
+            .with_context(|| {
+                format!(
+                    "Failed to list model files for {:?}",
+                    args.name.clone().bold().red()
+                )
+            })?;
+
+            Ok(())
+        }
+        // subcommand for printing model metadata
+        Some(Commands::Metadata(args)) => {
+            model_metadata(
+                args.name.as_deref(),
+                args.version.as_deref(),
+                args.repository.as_deref(),
+                args.uid.as_deref(),
+                &args.ignore_release_candidates,
+                cli.output_options(),
             )
             .with_context(|| {
+                format!(
+                    "Failed to get model metadata for {:?}",
+                    args.name.clone().bold().red()
+                )
+            })?;
+
+            Ok(())
+        }
+        // subcommand for diffing the metadata of two model versions
+        Some(Commands::DiffModel(args)) => {
+            diff_model(
+                &args.name,
+                &args.version,
+                &args.other_version,
+                args.repository.as_deref(),
+                &args.ignore_release_candidates,
+                cli.table_style,
+                cli.output_options(),
+            )
+            .with_context(|| {
+                format!(
+                    "Failed to diff model metadata for {:?}",
+                    args.name.bold().red()
+                )
+            })?;
+
+            Ok(())
+        }
+        // subcommand for validating a local metadata file
+        Some(Commands::ValidateMetadata(args)) => {
+            let path = args.path.as_deref();
+            validate_metadata(path).with_context(|| {
+                format!(
+                    "Failed to validate metadata file {:?}",
+                    path.unwrap_or("stdin").bold().red()
+                )
+            })?;
+
+            Ok(())
+        }
+        // subcommand for downloading a model
+        Some(Commands::DownloadModel(args)) => {
+            download_model(args, cli.table_style, cli.output_options()).with_context(|| {
                 format!(
                     "Failed to download model for {:?}",
                     args.name.clone().bold().red()
@@ -74,12 +220,53 @@ fn main() -> Result<()> {
             })?;
             Ok(())
         }
+        // subcommand for downloading several models by uid
+        Some(Commands::DownloadModels(args)) => {
+            download_models_by_uid(
+                &args.uids,
+                &args.write_dir,
+                &args.onnx,
+                &args.quantize,
+                &args.preprocessor,
+                args.jobs.into(),
+                cli.table_style,
+            )
+            .with_context(|| format!("{}", "Failed to download models".bold().red()))?;
+            Ok(())
+        }
+        // subcommand for syncing models registered since a date
+        Some(Commands::SyncModels(args)) => {
+            sync_models(
+                args.registry.as_str(),
+                args.since.as_deref(),
+                &args.write_dir,
+                args.state_file.as_deref(),
+                &args.onnx,
+                &args.preprocessor,
+                args.max_downloads,
+            )
+            .with_context(|| format!("{}", "Failed to sync models".bold().red()))?;
+
+            Ok(())
+        }
         // subcommand for getting model metrics
         Some(Commands::GetModelMetrics(args)) => {
             get_model_metrics(
                 args.name.as_deref(),
+                args.repository.as_deref(),
                 args.version.as_deref(),
                 args.uid.as_deref(),
+                cli.table_style,
+                output::OutputOptions {
+                    raw: args.raw,
+                    ..cli.output_options()
+                },
+                MetricSelection {
+                    sort: args.sort,
+                    ascending: args.ascending,
+                    top: args.top,
+                },
+                args.pivot,
             )
             .with_context(|| {
                 format!(
@@ -98,6 +285,12 @@ fn main() -> Result<()> {
                 &args.lower_is_better,
                 &args.challenger_uid,
                 &args.champion_uid,
+                args.pairs.as_deref(),
+                cli.table_style,
+                output::OutputOptions {
+                    raw: args.raw,
+                    ..cli.output_options()
+                },
             )
             .with_context(|| {
                 format!(
@@ -109,11 +302,53 @@ fn main() -> Result<()> {
             Ok(())
         }
 
+        // subcommand for viewing and setting config values
+        Some(Commands::Config(args)) => {
+            config::run_config(&args.action, cli.profile.as_deref())
+                .with_context(|| format!("{}", "Failed to run config command".bold().red()))?;
+
+            Ok(())
+        }
+
+        // subcommand for printing json schemas
+        Some(Commands::Schema(args)) => {
+            schema::print_schema(&args.kind)
+                .with_context(|| format!("{}", "Failed to print schema".bold().red()))?;
+
+            Ok(())
+        }
+
+        // subcommand for checking the environment
+        Some(Commands::Doctor) => {
+            doctor::run_doctor(cli.profile.as_deref())
+                .with_context(|| format!("{}", "Environment check failed".bold().red()))?;
+
+            Ok(())
+        }
+
+        // subcommand for launching the opsml server
+        #[cfg(feature = "python")]
+        Some(Commands::LaunchApp(args)) => {
+            launch_app(
+                args.port,
+                args.login,
+                &args.host,
+                args.workers,
+                args.startup_timeout,
+            )
+            .with_context(|| format!("{}", "Failed to launch app".bold().red()))?;
+
+            Ok(())
+        }
+
         // subcommand for listing opsml-cli version
         Some(Commands::Version) => {
             println!(
-                "opsml-cli version {}",
-                env!("CARGO_PKG_VERSION").bold().green()
+                "{}",
+                output::styled(format!(
+                    "opsml-cli version {}",
+                    env!("CARGO_PKG_VERSION").bold().green()
+                ))
             );
             Ok(())
         }
@@ -121,9 +356,12 @@ fn main() -> Result<()> {
         // subcommand for listing opsml-cli info
         Some(Commands::Info) => {
             println!(
-                "\n{}\nopsml-cli version {}\n2023 Shipt, Inc.\n",
-                LOGO_TEXT.green(),
-                env!("CARGO_PKG_VERSION").bold().purple(),
+                "{}",
+                output::styled(format!(
+                    "\n{}\nopsml-cli version {}\n2023 Shipt, Inc.\n",
+                    LOGO_TEXT.green(),
+                    env!("CARGO_PKG_VERSION").bold().purple(),
+                ))
             );
 
             Ok(())